@@ -8,11 +8,15 @@ use super::{
 use super::{GetEvents, GetGovernanceSubjects};
 #[cfg(feature = "approval")]
 use crate::approval::manager::ApprovalAPI;
-use crate::commons::models::request::TapleRequest;
+use crate::commons::models::notification::NotificationEntry;
+use crate::commons::models::request::{RequestFailure, RequestState, TapleRequest};
 use crate::commons::models::state::SubjectData;
+#[cfg(feature = "evaluation")]
+use crate::evaluator::EvaluatorAPI;
 use crate::event::manager::EventAPI;
 use crate::ledger::manager::EventManagerAPI;
 use crate::signature::Signature;
+use crate::subscription::{NotificationSubscription, SubscriptionError};
 #[cfg(feature = "approval")]
 use crate::ApprovalEntity;
 use crate::ValidationProof;
@@ -21,12 +25,16 @@ use crate::{
 };
 use crate::{
     commons::channel::{ChannelData, MpscChannel, SenderEnd},
-    Notification,
+    Notification, NotificationFilter, NotificationKind,
 };
-use crate::{identifier::DigestIdentifier, DatabaseCollection, DB};
+use crate::{identifier::Derivable, identifier::DigestIdentifier, DatabaseCollection, DB};
+#[cfg(feature = "evaluation")]
+use crate::{FactSimulation, GovernanceDiagnostic, ValueWrapper};
 use crate::{KeyDerivator, KeyIdentifier};
 use libp2p::PeerId;
 use log::{error, info};
+use std::time::Duration;
+use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
 /// Object that allows interaction with a TAPLE node.
@@ -38,6 +46,8 @@ pub struct Api {
     controller_id: String,
     public_key: Vec<u8>,
     sender: SenderEnd<APICommands, ApiResponses>,
+    subscription_tx: broadcast::Sender<NotificationEntry>,
+    token: CancellationToken,
 }
 
 impl Api {
@@ -46,12 +56,67 @@ impl Api {
         controller_id: String,
         public_key: Vec<u8>,
         sender: SenderEnd<APICommands, ApiResponses>,
+        subscription_tx: broadcast::Sender<NotificationEntry>,
+        token: CancellationToken,
     ) -> Self {
         Self {
             peer_id,
             controller_id,
             public_key,
             sender,
+            subscription_tx,
+            token,
+        }
+    }
+
+    /// Creates an independent subscription to the node notifications.
+    ///
+    /// Only the notifications that pass the `filter` are returned by the subscription. Each
+    /// subscription has its own buffer, so a slow subscription never blocks the node nor the
+    /// other subscriptions; instead it loses the oldest notifications and receives a
+    /// [SubscriptionError::Lagged](crate::SubscriptionError::Lagged) error.
+    /// Only the notifications generated after the call are received.
+    pub fn subscribe(&self, filter: NotificationFilter) -> NotificationSubscription {
+        NotificationSubscription::new(self.subscription_tx.subscribe(), filter, self.token.clone())
+    }
+
+    /// Creates a subscription that resumes the stream of notifications from a cursor.
+    ///
+    /// All the notifications stored in the notification log from `cursor` onwards are
+    /// received first, followed by the live ones. To resume after a restart, pass the cursor
+    /// following the one of the last [NotificationEntry] processed. Notifications are delivered
+    /// at least once: if the subscription lags behind, the lost notifications are read again
+    /// from the log instead of being reported as an error. If `cursor` is no longer in the log,
+    /// the replay starts with the oldest notification kept.
+    pub fn subscribe_from(&self, cursor: u64, filter: NotificationFilter) -> NotificationSubscription {
+        NotificationSubscription::resume(
+            self.subscription_tx.subscribe(),
+            filter,
+            self.token.clone(),
+            self.sender.clone(),
+            cursor,
+        )
+    }
+
+    /// Allows to obtain up to `quantity` notifications from the notification log of the node,
+    /// starting with the one identified by `cursor`, or with the oldest one kept if it has
+    /// already been removed from the log.
+    /// # Possible errors
+    /// • [ApiError::DatabaseError] if the notification log could not be read.
+    pub async fn get_notifications(
+        &self,
+        cursor: u64,
+        quantity: usize,
+    ) -> Result<Vec<NotificationEntry>, ApiError> {
+        let response = self
+            .sender
+            .ask(APICommands::GetNotifications(cursor, quantity))
+            .await
+            .unwrap();
+        if let ApiResponses::GetNotifications(data) = response {
+            data
+        } else {
+            unreachable!()
         }
     }
 
@@ -67,6 +132,10 @@ impl Api {
         &self.public_key
     }
 
+    /// Allows to obtain the tracking data of a request of this node: its state, the stages it
+    /// has gone through with the signers that answered in each one, and the reason why it has
+    /// not succeeded, if any. Every change of stage or state is also notified through
+    /// [Notification::RequestUpdated](crate::Notification::RequestUpdated).
     pub async fn get_request(
         &self,
         request_id: DigestIdentifier,
@@ -103,6 +172,184 @@ impl Api {
         }
     }
 
+    /// Cancels a request whose event is being completed by the node. The evaluation or approval
+    /// of the event is stopped and the request is marked as [RequestState::Cancelled], so a new
+    /// event request can be sent for the subject. Events already sent to validation can not be
+    /// cancelled, since validators do not sign two different events with the same sn.
+    /// # Possible errors
+    /// • [ApiError::NotFound] if the request does not exist.
+    /// • [ApiError::Conflict] if the event of the request is not being completed or is being
+    /// validated.
+    pub async fn cancel_request(&self, request_id: DigestIdentifier) -> Result<(), ApiError> {
+        let response = self
+            .sender
+            .ask(APICommands::CancelRequest(request_id))
+            .await
+            .unwrap();
+        if let ApiResponses::CancelRequest(data) = response {
+            data
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Evaluates a fact against the current state of a subject without generating an event.
+    ///
+    /// The contract of the subject is executed locally, as if the fact were invoked by this
+    /// node, and nothing is signed nor sent to other nodes. The result includes the patch, the
+    /// resulting state, whether approval would be required and whether the new state is valid
+    /// according to the schema of the subject.
+    /// # Possible errors
+    /// • [ApiError::NotFound] if the subject or the contract of its schema does not exist.
+    /// • [ApiError::EvaluationFailed] if the contract could not be executed.
+    #[cfg(feature = "evaluation")]
+    pub async fn simulate_fact(
+        &self,
+        subject_id: DigestIdentifier,
+        payload: ValueWrapper,
+    ) -> Result<FactSimulation, ApiError> {
+        let response = self
+            .sender
+            .ask(APICommands::SimulateFact(subject_id, payload))
+            .await
+            .unwrap();
+        if let ApiResponses::SimulateFact(data) = response {
+            data
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Validates a governance document without submitting it.
+    ///
+    /// The document is checked against the JSON Schema of the governances, the members,
+    /// policies, schemas and roles are checked for consistency as the governance contract does,
+    /// and the contracts of the schemas are built. Every problem found is returned with the
+    /// JSON pointer to the part of the document where it was found, so an empty list means that
+    /// the document is valid. Building the contracts may take a while the first time.
+    /// # Possible errors
+    /// • [ApiError::GovernanceValidationFailed] if the document could not be validated.
+    #[cfg(feature = "evaluation")]
+    pub async fn validate_governance(
+        &self,
+        document: ValueWrapper,
+    ) -> Result<Vec<GovernanceDiagnostic>, ApiError> {
+        let response = self
+            .sender
+            .ask(APICommands::ValidateGovernance(document))
+            .await
+            .unwrap();
+        if let ApiResponses::ValidateGovernance(data) = response {
+            data
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Validates the governance document that results from applying a JSON patch to the
+    /// current state of a governance, as a patch event of the governance would do. The
+    /// problems are reported as in [Api::validate_governance], and a patch that can not be
+    /// applied is reported as a problem of the whole document.
+    /// # Possible errors
+    /// • [ApiError::NotFound] if the governance does not exist.
+    /// • [ApiError::InvalidParameters] if the subject is not a governance.
+    /// • [ApiError::GovernanceValidationFailed] if the document could not be validated.
+    #[cfg(feature = "evaluation")]
+    pub async fn validate_governance_patch(
+        &self,
+        governance_id: DigestIdentifier,
+        patch: ValueWrapper,
+    ) -> Result<Vec<GovernanceDiagnostic>, ApiError> {
+        let response = self
+            .sender
+            .ask(APICommands::ValidateGovernancePatch(governance_id, patch))
+            .await
+            .unwrap();
+        if let ApiResponses::ValidateGovernance(data) = response {
+            data
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Makes a request to the node from an external Invoker and waits until its event is
+    /// stored in the ledger.
+    ///
+    /// The event is returned even if it has not modified the state of the subject because
+    /// its evaluation failed or its approval was rejected. The reason is available through
+    /// [Api::get_request].
+    /// # Possible errors
+    /// • [ApiError::RequestFailed] if the processing of the request has been stopped.
+    /// • [ApiError::RequestCancelled] if the request has been cancelled.
+    /// • [ApiError::RequestTimeout] if the event has not been stored within `timeout`. The
+    /// request keeps being processed and can be followed with [Api::get_request].
+    /// • Any of the errors of [Api::external_request].
+    pub async fn external_request_and_wait(
+        &self,
+        event_request: Signed<EventRequest>,
+        timeout: Duration,
+    ) -> Result<Signed<Event>, ApiError> {
+        let mut filter = NotificationFilter::default();
+        filter.kinds.insert(NotificationKind::NewEvent);
+        filter.kinds.insert(NotificationKind::RequestUpdated);
+        // The subscription is created first so that no notification is missed
+        let mut subscription = self.subscribe(filter);
+        let request_id = self.external_request(event_request).await?;
+        let request_key = request_id.to_str();
+        let wait = async {
+            loop {
+                let request = self.get_request(request_id.clone()).await?;
+                match request.state {
+                    RequestState::Finished => {
+                        let (Some(subject_id), Some(sn)) = (request.subject_id, request.sn) else {
+                            return Err(ApiError::UnexpectedError);
+                        };
+                        return self.get_event(subject_id, sn).await;
+                    }
+                    RequestState::Error => {
+                        return Err(ApiError::RequestFailed {
+                            request_id: request_key.clone(),
+                            failure: request.failure.unwrap_or(RequestFailure::Error {
+                                stage: None,
+                                error: "Unknown error".to_owned(),
+                            }),
+                        })
+                    }
+                    RequestState::Cancelled => {
+                        return Err(ApiError::RequestCancelled(request_key.clone()))
+                    }
+                    RequestState::Processing => {}
+                }
+                let subject_id = request.subject_id.map(|subject_id| subject_id.to_str());
+                // Waits for a notification that may change the state of the request
+                loop {
+                    let notification = match subscription.recv().await {
+                        Ok(notification) => notification,
+                        // Lost notifications may refer to the request
+                        Err(SubscriptionError::Lagged(_)) => break,
+                        // The node has been stopped
+                        Err(_) => return Err(ApiError::UnexpectedError),
+                    };
+                    let updated = match &notification {
+                        Notification::RequestUpdated { id, .. } => id == &request_key,
+                        Notification::NewEvent {
+                            subject_id: event_subject_id,
+                            ..
+                        } => Some(event_subject_id) == subject_id.as_ref(),
+                        _ => false,
+                    };
+                    if updated {
+                        break;
+                    }
+                }
+            }
+        };
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(result) => result,
+            Err(_) => Err(ApiError::RequestTimeout(request_key)),
+        }
+    }
+
     /// It allows to obtain all the voting requests pending to be resolved in the node.
     /// These requests are received from other nodes in the network when they try to update
     /// a governance subject. It is necessary to vote their agreement or disagreement with
@@ -458,6 +705,7 @@ impl<C: DatabaseCollection> ApiManager<C> {
         input: MpscChannel<APICommands, ApiResponses>,
         event_api: EventAPI,
         #[cfg(feature = "approval")] approval_api: ApprovalAPI,
+        #[cfg(feature = "evaluation")] evaluator_api: EvaluatorAPI,
         authorized_subjects_api: AuthorizedSubjectsAPI,
         ledger_api: EventManagerAPI,
         token: CancellationToken,
@@ -472,6 +720,8 @@ impl<C: DatabaseCollection> ApiManager<C> {
                 db,
                 #[cfg(feature = "approval")]
                 approval_api,
+                #[cfg(feature = "evaluation")]
+                evaluator_api,
                 ledger_api,
             ),
             token,
@@ -538,6 +788,9 @@ impl<C: DatabaseCollection> ApiManager<C> {
                         let response = self.inner_api.handle_external_request(event_request).await;
                         response?
                     }
+                    APICommands::CancelRequest(request_id) => {
+                        self.inner_api.cancel_request(request_id).await?
+                    }
                     APICommands::SetPreauthorizedSubject(subject_id, providers) => {
                         self.inner_api
                             .set_preauthorized_subject(subject_id, providers)
@@ -574,6 +827,25 @@ impl<C: DatabaseCollection> ApiManager<C> {
                     APICommands::GetSubjectByGovernance(params, gov_id) => {
                         self.inner_api.get_subjects_by_governance(params, gov_id)
                     }
+                    APICommands::GetNotifications(cursor, quantity) => {
+                        self.inner_api.get_notifications(cursor, quantity)
+                    }
+                    #[cfg(feature = "evaluation")]
+                    APICommands::SimulateFact(subject_id, payload) => {
+                        self.inner_api.simulate_fact(subject_id, payload).await
+                    }
+                    #[cfg(feature = "evaluation")]
+                    APICommands::ValidateGovernance(document) => {
+                        // Contracts may take long to build, so the API keeps answering meanwhile
+                        self.inner_api.validate_governance(None, document, sx);
+                        return Ok(());
+                    }
+                    #[cfg(feature = "evaluation")]
+                    APICommands::ValidateGovernancePatch(governance_id, patch) => {
+                        self.inner_api
+                            .validate_governance(Some(governance_id), patch, sx);
+                        return Ok(());
+                    }
                 };
                 sx.send(response)
                     .map_err(|_| APIInternalError::OneshotUnavailable)?;
@@ -585,3 +857,190 @@ impl<C: DatabaseCollection> ApiManager<C> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, collections::VecDeque, str::FromStr, time::Duration};
+
+    use borsh::BorshDeserialize;
+    use libp2p::PeerId;
+    use serde_json::json;
+    use tokio::{runtime::Runtime, sync::broadcast};
+    use tokio_util::sync::CancellationToken;
+
+    use super::Api;
+    use crate::api::{APICommands, ApiError, ApiResponses};
+    use crate::commons::channel::{ChannelData, MpscChannel};
+    use crate::commons::crypto::{Ed25519KeyPair, KeyGenerator, KeyPair};
+    use crate::commons::models::HashId;
+    use crate::identifier::{Derivable, DigestIdentifier};
+    use crate::request::{FactRequest, RequestFailure, RequestState, TapleRequest};
+    use crate::signature::{Signature, Signed};
+    use crate::{
+        DigestDerivator, Event, EventRequest, Notification, NotificationEntry, ValueWrapper,
+    };
+
+    const SUBJECT_ID: &str = "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs";
+
+    fn sign<T: HashId + BorshDeserialize + Clone>(content: T) -> Signed<T> {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[1]));
+        Signed {
+            signature: Signature::new(&content, &keys, DigestDerivator::Blake3_256).unwrap(),
+            content,
+        }
+    }
+
+    fn event_request() -> Signed<EventRequest> {
+        sign(EventRequest::Fact(FactRequest {
+            subject_id: DigestIdentifier::from_str(SUBJECT_ID).unwrap(),
+            payload: ValueWrapper(json!({})),
+        }))
+    }
+
+    /// The request in each of the given states, in order.
+    fn request_states(
+        states: Vec<(RequestState, Option<RequestFailure>)>,
+    ) -> VecDeque<TapleRequest> {
+        let request: TapleRequest = event_request().try_into().unwrap();
+        states
+            .into_iter()
+            .map(|(state, failure)| TapleRequest {
+                sn: (state == RequestState::Finished).then_some(1),
+                state,
+                failure,
+                ..request.clone()
+            })
+            .collect()
+    }
+
+    /// Answers the commands of the API as a node whose request moves to the next of its
+    /// states each time it is read, notifying the change.
+    async fn node(
+        mut receiver: MpscChannel<APICommands, ApiResponses>,
+        subscription_tx: broadcast::Sender<NotificationEntry>,
+        mut requests: VecDeque<TapleRequest>,
+    ) {
+        let mut cursor = 0;
+        while let Some(ChannelData::AskData(data)) = receiver.receive().await {
+            let (sender, command) = data.get();
+            let response = match command {
+                APICommands::ExternalRequest(_) => {
+                    ApiResponses::HandleExternalRequest(Ok(requests[0].id.clone()))
+                }
+                APICommands::GetRequest(_) if requests.len() > 1 => {
+                    let request = requests.pop_front().unwrap();
+                    let _ = sender.send(ApiResponses::GetRequest(Ok(request)));
+                    let notification = Notification::RequestUpdated {
+                        id: requests[0].id.to_str(),
+                        subject_id: Some(SUBJECT_ID.to_owned()),
+                        state: requests[0].state.clone(),
+                        stage: None,
+                    };
+                    let _ = subscription_tx.send(NotificationEntry {
+                        cursor,
+                        notification,
+                        governance_id: None,
+                        schema_id: None,
+                    });
+                    cursor += 1;
+                    continue;
+                }
+                APICommands::GetRequest(_) => ApiResponses::GetRequest(Ok(requests[0].clone())),
+                APICommands::GetEvent(subject_id, sn) => {
+                    let event = Event {
+                        subject_id,
+                        event_request: event_request(),
+                        sn,
+                        gov_version: 0,
+                        patch: ValueWrapper(json!([])),
+                        state_hash: DigestIdentifier::default(),
+                        eval_success: true,
+                        appr_required: true,
+                        approved: requests[0].failure.is_none(),
+                        hash_prev_event: DigestIdentifier::default(),
+                        evaluators: HashSet::new(),
+                        approvers: HashSet::new(),
+                        dependencies: vec![],
+                    };
+                    ApiResponses::GetEvent(Ok(sign(event)))
+                }
+                _ => unreachable!(),
+            };
+            let _ = sender.send(response);
+        }
+    }
+
+    fn request_and_wait(
+        states: Vec<(RequestState, Option<RequestFailure>)>,
+        timeout: Duration,
+    ) -> Result<Signed<Event>, ApiError> {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async move {
+            let (receiver, sender) = MpscChannel::new(10);
+            let (subscription_tx, _) = broadcast::channel(10);
+            let api = Api::new(
+                PeerId::random(),
+                String::new(),
+                vec![],
+                sender,
+                subscription_tx.clone(),
+                CancellationToken::new(),
+            );
+            tokio::spawn(node(receiver, subscription_tx, request_states(states)));
+            api.external_request_and_wait(event_request(), timeout)
+                .await
+        })
+    }
+
+    #[test]
+    fn request_and_wait_returns_the_stored_event() {
+        let states = vec![
+            (RequestState::Processing, None),
+            (RequestState::Processing, None),
+            (RequestState::Finished, None),
+        ];
+        let event = request_and_wait(states, Duration::from_secs(10)).unwrap();
+        assert_eq!(event.content.sn, 1);
+        assert!(event.content.approved);
+    }
+
+    #[test]
+    fn request_and_wait_returns_rejected_events() {
+        let states = vec![
+            (RequestState::Processing, None),
+            (
+                RequestState::Finished,
+                Some(RequestFailure::ApprovalRejected),
+            ),
+        ];
+        let event = request_and_wait(states, Duration::from_secs(10)).unwrap();
+        assert!(!event.content.approved);
+    }
+
+    #[test]
+    fn request_and_wait_fails_with_the_request() {
+        let failure = RequestFailure::Error {
+            stage: None,
+            error: "Subject not found".to_owned(),
+        };
+        let states = vec![
+            (RequestState::Processing, None),
+            (RequestState::Error, Some(failure)),
+        ];
+        let result = request_and_wait(states, Duration::from_secs(10));
+        assert!(matches!(result, Err(ApiError::RequestFailed { .. })));
+        let states = vec![
+            (RequestState::Processing, None),
+            (RequestState::Cancelled, None),
+        ];
+        let result = request_and_wait(states, Duration::from_secs(10));
+        assert!(matches!(result, Err(ApiError::RequestCancelled(_))));
+    }
+
+    #[test]
+    fn request_and_wait_times_out() {
+        let states = vec![(RequestState::Processing, None)];
+        let result = request_and_wait(states, Duration::from_millis(100));
+        assert!(matches!(result, Err(ApiError::RequestTimeout(_))));
+    }
+}
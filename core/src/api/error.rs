@@ -1,7 +1,10 @@
 //! Errors that may occur when interacting with a TAPLE node through its API
 
 pub use crate::protocol::errors::EventCreationError;
-use crate::{approval::error::ApprovalErrorResponse, event::errors::EventError};
+use crate::{
+    approval::error::ApprovalErrorResponse, event::errors::EventError,
+    request::RequestFailure,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -61,4 +64,22 @@ pub enum ApiError {
     DatabaseError(String),
     #[error("Conflict: {0}")]
     Conflict(String),
+    /// The request has been processed but its event could not be completed
+    #[error("Request {request_id} failed: {failure:?}")]
+    RequestFailed {
+        request_id: String,
+        failure: RequestFailure,
+    },
+    /// The request has not been completed within the time given. It may still be completed
+    #[error("Request {0} has not been completed in time")]
+    RequestTimeout(String),
+    /// The request has been cancelled before its event was completed
+    #[error("Request {0} has been cancelled")]
+    RequestCancelled(String),
+    /// The fact could not be evaluated against the state of the subject
+    #[error("Evaluation failed: {0}")]
+    EvaluationFailed(String),
+    /// The governance document could not be validated
+    #[error("Governance validation failed: {0}")]
+    GovernanceValidationFailed(String),
 }
@@ -5,6 +5,10 @@ use crate::approval::error::ApprovalErrorResponse;
 #[cfg(feature = "approval")]
 use crate::approval::manager::{ApprovalAPI, ApprovalAPIInterface};
 use crate::authorized_subjecs::manager::AuthorizedSubjectsAPI;
+#[cfg(feature = "evaluation")]
+use crate::evaluator::errors::{EvaluatorErrorResponses, ExecutorErrorResponses};
+#[cfg(feature = "evaluation")]
+use crate::evaluator::{EvaluatorAPI, EvaluatorAPIInterface};
 use crate::event::errors::EventError;
 use crate::event::manager::{EventAPI, EventAPIInterface};
 use crate::event::EventResponse;
@@ -13,6 +17,8 @@ use crate::ledger::manager::{EventManagerAPI, EventManagerInterface};
 use crate::signature::Signed;
 #[cfg(feature = "approval")]
 use crate::ApprovalState;
+#[cfg(feature = "evaluation")]
+use crate::ValueWrapper;
 use crate::{KeyDerivator, KeyIdentifier};
 // use crate::ledger::errors::LedgerManagerError;
 use crate::{
@@ -35,6 +41,8 @@ pub(crate) struct InnerApi<C: DatabaseCollection> {
     event_api: EventAPI,
     #[cfg(feature = "approval")]
     approval_api: ApprovalAPI,
+    #[cfg(feature = "evaluation")]
+    evaluator_api: EvaluatorAPI,
     authorized_subjects_api: AuthorizedSubjectsAPI,
     ledger_api: EventManagerAPI,
     db: DB<C>,
@@ -48,12 +56,15 @@ impl<C: DatabaseCollection> InnerApi<C> {
         authorized_subjects_api: AuthorizedSubjectsAPI,
         db: DB<C>,
         #[cfg(feature = "approval")] approval_api: ApprovalAPI,
+        #[cfg(feature = "evaluation")] evaluator_api: EvaluatorAPI,
         ledger_api: EventManagerAPI,
     ) -> Self {
         Self {
             event_api,
             #[cfg(feature = "approval")]
             approval_api,
+            #[cfg(feature = "evaluation")]
+            evaluator_api,
             authorized_subjects_api,
             db,
             ledger_api,
@@ -78,6 +89,98 @@ impl<C: DatabaseCollection> InnerApi<C> {
         }
     }
 
+    pub async fn cancel_request(
+        &self,
+        request_id: DigestIdentifier,
+    ) -> Result<ApiResponses, APIInternalError> {
+        let EventResponse::CancelRequest(response) =
+            self.event_api.cancel_request(request_id).await
+        else {
+            return Err(APIInternalError::UnexpectedManagerResponse);
+        };
+        match response {
+            Ok(()) => Ok(ApiResponses::CancelRequest(Ok(()))),
+            Err(EventError::RequestNotFound(request_id)) => Ok(ApiResponses::CancelRequest(Err(
+                ApiError::NotFound(format!("Request {}", request_id)),
+            ))),
+            Err(EventError::RequestNotInProgress(request_id)) => {
+                Ok(ApiResponses::CancelRequest(Err(ApiError::Conflict(
+                    format!("Request {} is not in progress", request_id),
+                ))))
+            }
+            Err(EventError::RequestInValidation(request_id)) => {
+                Ok(ApiResponses::CancelRequest(Err(ApiError::Conflict(
+                    format!("The event of request {} is being validated", request_id),
+                ))))
+            }
+            Err(error) => Ok(ApiResponses::CancelRequest(Err(
+                ApiError::EventCreationError { source: error },
+            ))),
+        }
+    }
+
+    #[cfg(feature = "evaluation")]
+    pub async fn simulate_fact(
+        &self,
+        subject_id: DigestIdentifier,
+        payload: ValueWrapper,
+    ) -> ApiResponses {
+        let response = match self.evaluator_api.simulate_fact(subject_id, payload).await {
+            Ok(simulation) => Ok(simulation),
+            Err(EvaluatorErrorResponses::ContractExecutionError(
+                ExecutorErrorResponses::SubjectNotFound(subject_id),
+            )) => Err(ApiError::NotFound(format!("Subject {}", subject_id))),
+            Err(EvaluatorErrorResponses::ContractExecutionError(
+                ExecutorErrorResponses::ContractNotFound(schema_id, governance_id),
+            )) => Err(ApiError::NotFound(format!(
+                "Contract for schema {} of governance {}",
+                schema_id, governance_id
+            ))),
+            Err(error) => Err(ApiError::EvaluationFailed(error.to_string())),
+        };
+        ApiResponses::SimulateFact(response)
+    }
+
+    /// Validates a governance document, or the result of applying a patch to the current
+    /// state of `governance_id` if present, and answers through `sender` once done.
+    #[cfg(feature = "evaluation")]
+    pub fn validate_governance(
+        &self,
+        governance_id: Option<DigestIdentifier>,
+        document: ValueWrapper,
+        sender: tokio::sync::oneshot::Sender<ApiResponses>,
+    ) {
+        let evaluator_api = self.evaluator_api.clone();
+        tokio::spawn(async move {
+            let result = match &governance_id {
+                Some(governance_id) => {
+                    evaluator_api
+                        .validate_governance_patch(governance_id.clone(), document)
+                        .await
+                }
+                None => evaluator_api.validate_governance(document).await,
+            };
+            let response = match result {
+                Ok(diagnostics) => Ok(diagnostics),
+                Err(EvaluatorErrorResponses::ContractExecutionError(
+                    ExecutorErrorResponses::GovernanceNotFound,
+                )) => Err(ApiError::NotFound(format!(
+                    "Governance {}",
+                    governance_id.map(|id| id.to_str()).unwrap_or_default()
+                ))),
+                Err(EvaluatorErrorResponses::ContractExecutionError(
+                    ExecutorErrorResponses::NotGovernance(subject_id),
+                )) => Err(ApiError::InvalidParameters(format!(
+                    "Subject {} is not a governance",
+                    subject_id
+                ))),
+                Err(error) => Err(ApiError::GovernanceValidationFailed(error.to_string())),
+            };
+            // The caller may have stopped waiting for the answer
+            let _ = sender.send(ApiResponses::ValidateGovernance(response));
+        });
+    }
+
     #[cfg(feature = "approval")]
     pub async fn emit_vote(
         &self,
@@ -143,6 +246,15 @@ impl<C: DatabaseCollection> InnerApi<C> {
         ApiResponses::GetSubjectByGovernance(Ok(result))
     }
 
+    pub fn get_notifications(&self, cursor: u64, quantity: usize) -> ApiResponses {
+        match self.db.get_notifications(cursor, quantity) {
+            Ok(notifications) => ApiResponses::GetNotifications(Ok(notifications)),
+            Err(error) => {
+                ApiResponses::GetNotifications(Err(ApiError::DatabaseError(error.to_string())))
+            }
+        }
+    }
+
     pub fn get_all_subjects(&self, data: GetSubjects) -> ApiResponses {
         let (from, quantity) = self.get_from_and_quantity(data);
         let result = match self.db.get_subjects(from, quantity) {
@@ -1,10 +1,13 @@
 #[cfg(feature = "approval")]
 use crate::commons::models::approval::ApprovalEntity;
+use crate::commons::models::notification::NotificationEntry;
 use crate::commons::models::request::TapleRequest;
 use crate::commons::models::state::SubjectData;
 use crate::identifier::DigestIdentifier;
 use crate::signature::Signature;
 use crate::signature::Signed;
+#[cfg(feature = "evaluation")]
+use crate::{FactSimulation, GovernanceDiagnostic, ValueWrapper};
 use crate::{ApprovalState, Event, EventRequest, KeyDerivator, KeyIdentifier, ValidationProof};
 use std::collections::HashSet;
 
@@ -28,6 +31,7 @@ pub enum APICommands {
     #[cfg(feature = "approval")]
     VoteResolve(bool, DigestIdentifier),
     ExternalRequest(Signed<EventRequest>),
+    CancelRequest(DigestIdentifier),
     #[cfg(feature = "approval")]
     GetPendingRequests,
     #[cfg(feature = "approval")]
@@ -42,6 +46,13 @@ pub enum APICommands {
     GetApproval(DigestIdentifier),
     #[cfg(feature = "approval")]
     GetApprovals(GetApprovals),
+    GetNotifications(u64, usize),
+    #[cfg(feature = "evaluation")]
+    SimulateFact(DigestIdentifier, ValueWrapper),
+    #[cfg(feature = "evaluation")]
+    ValidateGovernance(ValueWrapper),
+    #[cfg(feature = "evaluation")]
+    ValidateGovernancePatch(DigestIdentifier, ValueWrapper),
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +63,7 @@ pub enum ApiResponses {
     GetSubject(Result<SubjectData, ApiError>),
     GetEvents(Result<Vec<Signed<Event>>, ApiError>),
     HandleExternalRequest(Result<DigestIdentifier, ApiError>),
+    CancelRequest(Result<(), ApiError>),
     #[cfg(feature = "approval")]
     VoteResolve(Result<ApprovalEntity, ApiError>),
     #[cfg(feature = "approval")]
@@ -69,6 +81,11 @@ pub enum ApiResponses {
     GetApprovals(Result<Vec<ApprovalEntity>, ApiError>),
     SetPreauthorizedSubjectCompleted,
     GetAllPreauthorizedSubjects(Result<Vec<(DigestIdentifier, HashSet<KeyIdentifier>)>, ApiError>),
+    GetNotifications(Result<Vec<NotificationEntry>, ApiError>),
+    #[cfg(feature = "evaluation")]
+    SimulateFact(Result<FactSimulation, ApiError>),
+    #[cfg(feature = "evaluation")]
+    ValidateGovernance(Result<Vec<GovernanceDiagnostic>, ApiError>),
 }
 
 #[derive(Debug, Clone)]
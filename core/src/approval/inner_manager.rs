@@ -144,6 +144,13 @@ impl<G: GovernanceInterface, N: NotifierInterface, C: DatabaseCollection>
             let subject_id = {
                 match approval_entity.request.content.event_request.content {
                     EventRequest::Fact(ref fact_request) => fact_request.subject_id.clone(),
+                    EventRequest::Transfer(ref transfer_request) => {
+                        transfer_request.subject_id.clone()
+                    }
+                    EventRequest::EOL(ref eol_request) => eol_request.subject_id.clone(),
+                    EventRequest::Migrate(ref migrate_request) => {
+                        migrate_request.subject_id.clone()
+                    }
                     EventRequest::Create(ref create_request) => generate_subject_id(
                         &create_request.namespace,
                         &create_request.schema_id,
@@ -374,6 +381,10 @@ fn subject_id_by_request(
 ) -> Result<DigestIdentifier, ApprovalManagerError> {
     let subject_id = match request {
         EventRequest::Fact(ref fact_request) => fact_request.subject_id.clone(),
+        // Only approved if the contract declares their hooks
+        EventRequest::Transfer(ref transfer_request) => transfer_request.subject_id.clone(),
+        EventRequest::EOL(ref eol_request) => eol_request.subject_id.clone(),
+        EventRequest::Migrate(ref migrate_request) => migrate_request.subject_id.clone(),
         EventRequest::Create(ref create_request) => generate_subject_id(
             &create_request.namespace,
             &create_request.schema_id,
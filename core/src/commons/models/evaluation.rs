@@ -29,12 +29,36 @@ pub struct EvaluationRequest {
 pub struct SubjectContext {
     pub governance_id: DigestIdentifier,
     pub schema_id: String,
+    pub schema_version: u64,
     pub is_owner: bool,
     pub state: ValueWrapper,
     pub namespace: String,
     //pub governance_version: u64, // está en evento
 }
 
+/// The context of a fact that smart contracts obtain through the `get_context` host function.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ContractContext {
+    /// The identifier of the subject.
+    pub subject_id: String,
+    /// The identifier of the governance of the subject.
+    pub governance_id: String,
+    /// The identifier of the schema of the subject.
+    pub schema_id: String,
+    /// The namespace of the subject.
+    pub namespace: String,
+    /// The identifier of the key that signed the fact.
+    pub invoker: String,
+    /// Whether the fact was signed by the owner of the subject.
+    pub is_owner: bool,
+    /// The sequence number of the event being evaluated.
+    pub sn: u64,
+    /// The version of the governance used in the evaluation.
+    pub gov_version: u64,
+    /// The timestamp, in nanoseconds, of the signature of the fact.
+    pub timestamp: u64,
+}
+
 /// A struct representing an evaluation response.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct EvaluationResponse {
@@ -48,6 +72,98 @@ pub struct EvaluationResponse {
     pub eval_success: bool,
     /// Whether approval is required for the evaluation to be applied to the state.
     pub appr_required: bool,
+    /// The states of other subjects read by the contract.
+    pub dependencies: Vec<SubjectDependency>,
+}
+
+/// The state of another subject read by a smart contract during its evaluation.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SubjectDependency {
+    /// The identifier of the subject read.
+    pub subject_id: DigestIdentifier,
+    /// The sequence number at which the subject was read.
+    pub sn: u64,
+    /// The hash of the state of the subject at that sequence number.
+    pub state_hash: DigestIdentifier,
+}
+
+/// The state of a subject that smart contracts obtain through the `get_subject_state` host
+/// function.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ContractSubjectState {
+    /// The properties of the subject.
+    pub properties: ValueWrapper,
+    /// Whether the subject was active.
+    pub active: bool,
+}
+
+/// The outcome of a fact simulated against the current state of a subject.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FactSimulation {
+    /// Whether the contract accepted the fact.
+    pub success: bool,
+    /// The patch that the fact would apply to the state.
+    pub patch: ValueWrapper,
+    /// The state of the subject after the fact.
+    pub state: ValueWrapper,
+    /// Whether the event would require approval.
+    pub approval_required: bool,
+    /// Whether the resulting state is valid according to the schema of the subject.
+    pub valid_state: bool,
+    /// The messages logged by the contract during the execution.
+    pub logs: Vec<ContractLog>,
+}
+
+/// A problem found in a governance document.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GovernanceDiagnostic {
+    /// JSON pointer to the part of the document with the problem.
+    pub pointer: String,
+    /// The description of the problem.
+    pub message: String,
+}
+
+/// A message logged by a smart contract through the `log` host function.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContractLog {
+    /// The level of the message.
+    pub level: ContractLogLevel,
+    /// The message itself.
+    pub message: String,
+}
+
+/// The level of a message logged by a smart contract.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ContractLogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<u32> for ContractLogLevel {
+    fn from(level: u32) -> Self {
+        match level {
+            0 => Self::Error,
+            1 => Self::Warn,
+            2 => Self::Info,
+            3 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+}
+
+impl From<ContractLogLevel> for log::Level {
+    fn from(level: ContractLogLevel) -> Self {
+        match level {
+            ContractLogLevel::Error => log::Level::Error,
+            ContractLogLevel::Warn => log::Level::Warn,
+            ContractLogLevel::Info => log::Level::Info,
+            ContractLogLevel::Debug => log::Level::Debug,
+            ContractLogLevel::Trace => log::Level::Trace,
+        }
+    }
 }
 
 impl HashId for EvaluationResponse {
@@ -57,6 +173,7 @@ impl HashId for EvaluationResponse {
             &self.state_hash,
             self.eval_success,
             self.appr_required,
+            &self.dependencies,
         ), derivator)
         .map_err(|_| {
             SubjectError::SignatureCreationFails("HashId for EvaluationResponse Fails".to_string())
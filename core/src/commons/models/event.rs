@@ -6,6 +6,7 @@ use crate::{
         crypto::{KeyMaterial, KeyPair},
         errors::SubjectError,
     },
+    governance::{votes_weight, SignerWeights},
     identifier::{DigestIdentifier, KeyIdentifier},
     request::EventRequest,
     signature::{Signature, Signed},
@@ -17,12 +18,16 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use super::{
-    approval::ApprovalRequest, evaluation::SubjectContext, state::generate_subject_id,
-    value_wrapper::ValueWrapper, HashId,
+    approval::ApprovalRequest,
+    deserialize_marked_subject_id,
+    evaluation::{SubjectContext, SubjectDependency},
+    state::generate_subject_id,
+    value_wrapper::ValueWrapper,
+    HashId, DEPENDENCIES_MARKER,
 };
 
 /// A struct representing an event.
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Event {
     /// The identifier of the subject of the event.
     pub subject_id: DigestIdentifier,
@@ -48,6 +53,58 @@ pub struct Event {
     pub evaluators: HashSet<Signature>,
     /// The set of approvers who have approved the event.
     pub approvers: HashSet<Signature>,
+    /// The states of other subjects read by the contract during the evaluation of the event.
+    pub dependencies: Vec<SubjectDependency>,
+}
+
+// Events without dependencies keep the encoding they had before dependencies existed
+impl BorshSerialize for Event {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if !self.dependencies.is_empty() {
+            BorshSerialize::serialize(&DEPENDENCIES_MARKER, writer)?;
+        }
+        BorshSerialize::serialize(&self.subject_id, writer)?;
+        BorshSerialize::serialize(&self.event_request, writer)?;
+        BorshSerialize::serialize(&self.sn, writer)?;
+        BorshSerialize::serialize(&self.gov_version, writer)?;
+        BorshSerialize::serialize(&self.patch, writer)?;
+        BorshSerialize::serialize(&self.state_hash, writer)?;
+        BorshSerialize::serialize(&self.eval_success, writer)?;
+        BorshSerialize::serialize(&self.appr_required, writer)?;
+        BorshSerialize::serialize(&self.approved, writer)?;
+        BorshSerialize::serialize(&self.hash_prev_event, writer)?;
+        BorshSerialize::serialize(&self.evaluators, writer)?;
+        BorshSerialize::serialize(&self.approvers, writer)?;
+        if !self.dependencies.is_empty() {
+            BorshSerialize::serialize(&self.dependencies, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Event {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let (has_dependencies, subject_id) = deserialize_marked_subject_id(reader)?;
+        let mut event = Self {
+            subject_id,
+            event_request: BorshDeserialize::deserialize_reader(reader)?,
+            sn: BorshDeserialize::deserialize_reader(reader)?,
+            gov_version: BorshDeserialize::deserialize_reader(reader)?,
+            patch: BorshDeserialize::deserialize_reader(reader)?,
+            state_hash: BorshDeserialize::deserialize_reader(reader)?,
+            eval_success: BorshDeserialize::deserialize_reader(reader)?,
+            appr_required: BorshDeserialize::deserialize_reader(reader)?,
+            approved: BorshDeserialize::deserialize_reader(reader)?,
+            hash_prev_event: BorshDeserialize::deserialize_reader(reader)?,
+            evaluators: BorshDeserialize::deserialize_reader(reader)?,
+            approvers: BorshDeserialize::deserialize_reader(reader)?,
+            dependencies: vec![],
+        };
+        if has_dependencies {
+            event.dependencies = BorshDeserialize::deserialize_reader(reader)?;
+        }
+        Ok(event)
+    }
 }
 
 impl Event {
@@ -127,6 +184,7 @@ impl Signed<Event> {
             hash_prev_event: DigestIdentifier::default(),
             evaluators: HashSet::new(),
             approvers: HashSet::new(),
+            dependencies: vec![],
         };
         let subject_signature_event =
             Signature::new(&content, &subject_keys, derivator).map_err(|_| {
@@ -144,13 +202,16 @@ impl Signed<Event> {
         self.content.event_request.verify()
     }
 
+    /// Verifies the evaluation and approval signatures of the event. Events that did not have to
+    /// be evaluated only need to be successful and approved.
     pub fn verify_eval_appr(
         &self,
+        evaluated: bool,
         subject_context: SubjectContext,
-        eval_sign_info: (&HashSet<KeyIdentifier>, u32, u32),
-        appr_sign_info: (&HashSet<KeyIdentifier>, u32, u32),
+        eval_sign_info: (&SignerWeights, u32, u32),
+        appr_sign_info: (&SignerWeights, u32, u32),
     ) -> Result<(), SubjectError> {
-        if !self.content.event_request.content.requires_eval_appr()
+        if !evaluated
             && self.content.eval_success
             && self.content.approved
             && !self.content.appr_required
@@ -171,6 +232,7 @@ impl Signed<Event> {
             state_hash: self.content.state_hash.clone(),
             eval_success: self.content.eval_success,
             appr_required: self.content.appr_required,
+            dependencies: self.content.dependencies.clone(),
         };
         let mut evaluators = HashSet::new();
         for eval_signature in self.content.evaluators.iter() {
@@ -181,7 +243,10 @@ impl Signed<Event> {
             }
             eval_signature.verify(&eval_response)?;
         }
-        if !evaluators.is_subset(eval_sign_info.0) {
+        if !evaluators
+            .iter()
+            .all(|evaluator| eval_sign_info.0.contains_key(evaluator))
+        {
             return Err(SubjectError::SignersError(
                 "Incorrect Evaluators signed".to_string(),
             ));
@@ -191,7 +256,7 @@ impl Signed<Event> {
         } else {
             eval_sign_info.2
         };
-        if evaluators.len() < quorum_size_eval as usize {
+        if votes_weight(eval_sign_info.0, &evaluators) < quorum_size_eval {
             return Err(SubjectError::SignersError(
                 "Not enough Evaluators signed".to_string(),
             ));
@@ -222,7 +287,10 @@ impl Signed<Event> {
             }
             appr_signature.verify(&appr_response)?;
         }
-        if !approvers.is_subset(appr_sign_info.0) {
+        if !approvers
+            .iter()
+            .all(|approver| appr_sign_info.0.contains_key(approver))
+        {
             return Err(SubjectError::SignersError(
                 "Incorrect Approvers signed".to_string(),
             ));
@@ -232,7 +300,7 @@ impl Signed<Event> {
         } else {
             appr_sign_info.2
         };
-        if approvers.len() < quorum_size_appr as usize {
+        if votes_weight(appr_sign_info.0, &approvers) < quorum_size_appr {
             return Err(SubjectError::SignersError(
                 "Not enough Approvers signed".to_string(),
             ));
@@ -255,3 +323,111 @@ pub struct Metadata {
     /// The identifier of the schema used to validate the event.
     pub schema_id: String,
 }
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, str::FromStr};
+
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use serde_json::json;
+
+    use super::Event;
+    use crate::{
+        commons::{
+            crypto::{Ed25519KeyPair, KeyGenerator, KeyPair},
+            models::evaluation::SubjectDependency,
+        },
+        identifier::DigestIdentifier,
+        request::{EventRequest, FactRequest},
+        signature::{Signature, Signed},
+        DigestDerivator, ValueWrapper,
+    };
+
+    const SUBJECT_ID: &str = "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs";
+
+    /// Layout of the events stored before they carried dependencies.
+    #[derive(BorshSerialize)]
+    struct EventV1 {
+        subject_id: DigestIdentifier,
+        event_request: Signed<EventRequest>,
+        sn: u64,
+        gov_version: u64,
+        patch: ValueWrapper,
+        state_hash: DigestIdentifier,
+        eval_success: bool,
+        appr_required: bool,
+        approved: bool,
+        hash_prev_event: DigestIdentifier,
+        evaluators: HashSet<Signature>,
+        approvers: HashSet<Signature>,
+    }
+
+    fn event() -> Event {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[1]));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let request = EventRequest::Fact(FactRequest {
+            subject_id: subject_id.clone(),
+            payload: ValueWrapper(json!({ "a": 1 })),
+        });
+        let signature = Signature::new(&request, &keys, DigestDerivator::Blake3_256).unwrap();
+        Event {
+            subject_id,
+            event_request: Signed {
+                content: request,
+                signature: signature.clone(),
+            },
+            sn: 1,
+            gov_version: 0,
+            patch: ValueWrapper(json!([])),
+            state_hash: DigestIdentifier::default(),
+            eval_success: true,
+            appr_required: false,
+            approved: true,
+            hash_prev_event: DigestIdentifier::default(),
+            evaluators: HashSet::from([signature]),
+            approvers: HashSet::new(),
+            dependencies: vec![],
+        }
+    }
+
+    #[test]
+    fn events_without_dependencies_keep_their_encoding() {
+        let event = event();
+        let legacy = EventV1 {
+            subject_id: event.subject_id.clone(),
+            event_request: event.event_request.clone(),
+            sn: event.sn,
+            gov_version: event.gov_version,
+            patch: event.patch.clone(),
+            state_hash: event.state_hash.clone(),
+            eval_success: event.eval_success,
+            appr_required: event.appr_required,
+            approved: event.approved,
+            hash_prev_event: event.hash_prev_event.clone(),
+            evaluators: event.evaluators.clone(),
+            approvers: event.approvers.clone(),
+        };
+        let bytes = event.try_to_vec().unwrap();
+        assert_eq!(bytes, legacy.try_to_vec().unwrap());
+        assert_eq!(Event::try_from_slice(&bytes).unwrap(), event);
+    }
+
+    #[test]
+    fn events_with_dependencies_are_decoded() {
+        let mut event = event();
+        event.dependencies.push(SubjectDependency {
+            subject_id: event.subject_id.clone(),
+            sn: 3,
+            state_hash: DigestIdentifier::default(),
+        });
+        let signed = Signed {
+            signature: event.evaluators.iter().next().unwrap().clone(),
+            content: event,
+        };
+        // The signature that follows the event is read after its dependencies
+        let bytes = signed.try_to_vec().unwrap();
+        let decoded = Signed::<Event>::try_from_slice(&bytes).unwrap();
+        assert_eq!(decoded.content, signed.content);
+        assert_eq!(decoded.signature, signed.signature);
+    }
+}
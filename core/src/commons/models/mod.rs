@@ -1,4 +1,6 @@
-use borsh::BorshSerialize;
+use std::io::Read;
+
+use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::{DigestIdentifier, DigestDerivator};
 
@@ -18,3 +20,24 @@ pub mod value_wrapper;
 pub trait HashId: BorshSerialize {
     fn hash_id(&self, derivator: DigestDerivator) -> Result<DigestIdentifier, SubjectError>;
 }
+
+/// First byte of the Borsh encoding of the events and validation proofs that carry subject
+/// dependencies, which are appended at the end. Without dependencies they keep the layout
+/// they had before, so stored ledgers, hashes and signatures are unchanged. That layout
+/// begins with the digest derivator of the subject identifier, which is never this byte.
+pub(crate) const DEPENDENCIES_MARKER: u8 = u8::MAX;
+
+/// Reads the subject identifier at the start of an encoding that may begin with
+/// [DEPENDENCIES_MARKER], and tells whether the marker was present.
+pub(crate) fn deserialize_marked_subject_id<R: Read>(
+    reader: &mut R,
+) -> std::io::Result<(bool, DigestIdentifier)> {
+    let first: u8 = BorshDeserialize::deserialize_reader(reader)?;
+    if first == DEPENDENCIES_MARKER {
+        let subject_id = BorshDeserialize::deserialize_reader(reader)?;
+        return Ok((true, subject_id));
+    }
+    let mut reader = (&[first][..]).chain(reader);
+    let subject_id = BorshDeserialize::deserialize_reader(&mut reader)?;
+    Ok((false, subject_id))
+}
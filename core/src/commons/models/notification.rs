@@ -1,8 +1,15 @@
+use std::collections::HashSet;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::commons::models::request::{RequestStage, RequestState};
+use crate::identifier::{Derivable, DigestIdentifier};
+
 /// Notifications generated by [NotificationHandler].
 /// These notifications identify the type of message and its content.
 /// In addition, the message is accompanied by the data used to construct
 /// the message, so that it can be used to construct customized messages.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
 pub enum Notification {
     /// A new subject has been generated
     NewSubject {
@@ -33,4 +40,118 @@ pub enum Notification {
     UnrecoverableError {
         error: String,
     },
+    /// A request of the node has reached a new stage or has ended
+    RequestUpdated {
+        id: String,
+        subject_id: Option<String>,
+        state: RequestState,
+        stage: Option<RequestStage>,
+    },
+}
+
+/// [Notification] stored in the notification log of the node.
+///
+/// Cursors are assigned in increasing order as the notifications are generated, so a
+/// consumer can resume the stream from the cursor following the last one it processed.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct NotificationEntry {
+    /// Position of the notification in the log
+    pub cursor: u64,
+    pub notification: Notification,
+    /// Governance of the subject the notification refers to, if known
+    pub governance_id: Option<DigestIdentifier>,
+    /// Schema of the subject the notification refers to, if known
+    pub schema_id: Option<String>,
+}
+
+/// Kind of a [Notification], without its content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    NewSubject,
+    NewEvent,
+    StateUpdated,
+    ApprovalReceived,
+    ObsoletedApproval,
+    UnrecoverableError,
+    RequestUpdated,
+}
+
+impl Notification {
+    /// Returns the kind of the notification.
+    pub fn kind(&self) -> NotificationKind {
+        match self {
+            Notification::NewSubject { .. } => NotificationKind::NewSubject,
+            Notification::NewEvent { .. } => NotificationKind::NewEvent,
+            Notification::StateUpdated { .. } => NotificationKind::StateUpdated,
+            Notification::ApprovalReceived { .. } => NotificationKind::ApprovalReceived,
+            Notification::ObsoletedApproval { .. } => NotificationKind::ObsoletedApproval,
+            Notification::UnrecoverableError { .. } => NotificationKind::UnrecoverableError,
+            Notification::RequestUpdated { .. } => NotificationKind::RequestUpdated,
+        }
+    }
+
+    /// Returns the subject the notification refers to, if any.
+    pub fn subject_id(&self) -> Option<&str> {
+        match self {
+            Notification::NewSubject { subject_id }
+            | Notification::NewEvent { subject_id, .. }
+            | Notification::StateUpdated { subject_id, .. }
+            | Notification::ApprovalReceived { subject_id, .. }
+            | Notification::ObsoletedApproval { subject_id, .. } => Some(subject_id),
+            Notification::RequestUpdated { subject_id, .. } => subject_id.as_deref(),
+            Notification::UnrecoverableError { .. } => None,
+        }
+    }
+}
+
+/// Filter applied to the notifications of a subscription.
+///
+/// Every field restricts the notifications received. An empty set does not apply any
+/// restriction, so the default filter receives all the notifications of the node.
+#[derive(Clone, Debug, Default)]
+pub struct NotificationFilter {
+    /// Subjects whose notifications are received
+    pub subject_ids: HashSet<DigestIdentifier>,
+    /// Governances whose subjects' notifications are received
+    pub governance_ids: HashSet<DigestIdentifier>,
+    /// Schemas whose subjects' notifications are received
+    pub schema_ids: HashSet<String>,
+    /// Kinds of notifications received
+    pub kinds: HashSet<NotificationKind>,
+}
+
+impl NotificationFilter {
+    /// Checks if a notification passes the filter. `governance_id` and `schema_id` are those
+    /// of the subject the notification refers to, if known.
+    pub(crate) fn matches(
+        &self,
+        notification: &Notification,
+        governance_id: Option<&DigestIdentifier>,
+        schema_id: Option<&str>,
+    ) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&notification.kind()) {
+            return false;
+        }
+        if !self.subject_ids.is_empty() {
+            let Some(subject_id) = notification.subject_id() else {
+                return false;
+            };
+            if !self.subject_ids.iter().any(|id| id.to_str() == subject_id) {
+                return false;
+            }
+        }
+        if !self.governance_ids.is_empty() {
+            match governance_id {
+                Some(governance_id) if self.governance_ids.contains(governance_id) => {}
+                _ => return false,
+            }
+        }
+        if !self.schema_ids.is_empty() {
+            match schema_id {
+                Some(schema_id) if self.schema_ids.contains(schema_id) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
 }
@@ -1,10 +1,14 @@
 //! Contains all valid event requests
 
+use std::collections::HashSet;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     commons::errors::SubjectError,
+    commons::models::timestamp::TimeStamp,
+    commons::schema_handler::gov_models::ContractHook,
     signature::{Signature, Signed},
     DigestIdentifier, KeyIdentifier, ValueWrapper, DigestDerivator,
 };
@@ -12,7 +16,7 @@ use crate::{
 use super::HashId;
 
 /// An enum representing a TAPLE event request.
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum EventRequest {
     /// A request to create a new subject.
     Create(StartRequest),
@@ -22,10 +26,12 @@ pub enum EventRequest {
     Transfer(TransferRequest),
     /// A request to mark a subject as end-of-life.
     EOL(EOLRequest),
+    /// A request to migrate the state of a subject to the current definition of its schema.
+    Migrate(MigrateRequest),
 }
 
 /// A struct representing a request to create a new subject.
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct StartRequest {
     /// The identifier of the governance contract.
     pub governance_id: DigestIdentifier,
@@ -37,6 +43,9 @@ pub struct StartRequest {
     pub name: String,
     /// The identifier of the public key of the subject owner.
     pub public_key: KeyIdentifier,
+    /// Optional data passed to the `init` entry point of the schema contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<ValueWrapper>,
 }
 
 /// A struct representing a request to add a fact to a subject.
@@ -64,11 +73,126 @@ pub struct EOLRequest {
     pub subject_id: DigestIdentifier,
 }
 
+/// A struct representing a request to migrate the state of a subject through the
+/// `migrate_function` entry point of the schema contract.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct MigrateRequest {
+    /// The identifier of the subject to migrate.
+    pub subject_id: DigestIdentifier,
+    /// The version of the schema the subject is upgraded to. If not present, the state is
+    /// migrated within the current version of the schema of the subject.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u64>,
+}
+
 impl EventRequest {
+    /// Whether the request is always evaluated. Transfers and EOLs are only evaluated when the
+    /// contract of the schema declares their [`contract_hook`](Self::contract_hook).
     pub fn requires_eval_appr(&self) -> bool {
         match self {
-            EventRequest::Fact(_) => true,
-            EventRequest::Create(_) | EventRequest::Transfer(_) | EventRequest::EOL(_) => false,
+            EventRequest::Fact(_) | EventRequest::Migrate(_) => true,
+            EventRequest::Create(start_request) => start_request.payload.is_some(),
+            EventRequest::Transfer(_) | EventRequest::EOL(_) => false,
+        }
+    }
+
+    /// Hook that a contract declares to evaluate this kind of request, if any.
+    pub fn contract_hook(&self) -> Option<ContractHook> {
+        match self {
+            EventRequest::Transfer(_) => Some(ContractHook::Transfer),
+            EventRequest::EOL(_) => Some(ContractHook::EOL),
+            EventRequest::Migrate(_) => Some(ContractHook::Migration),
+            EventRequest::Create(_) | EventRequest::Fact(_) => None,
+        }
+    }
+}
+
+// Creation requests without payload keep the original encoding so that the hashes and signatures
+// of existing events remain valid. Creation requests with payload use the tag 4.
+impl BorshSerialize for EventRequest {
+    #[inline]
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match &self {
+            EventRequest::Create(start_request) => {
+                match start_request.payload {
+                    Some(_) => BorshSerialize::serialize(&4u8, writer)?,
+                    None => BorshSerialize::serialize(&0u8, writer)?,
+                }
+                BorshSerialize::serialize(&start_request.governance_id, writer)?;
+                BorshSerialize::serialize(&start_request.schema_id, writer)?;
+                BorshSerialize::serialize(&start_request.namespace, writer)?;
+                BorshSerialize::serialize(&start_request.name, writer)?;
+                BorshSerialize::serialize(&start_request.public_key, writer)?;
+                match &start_request.payload {
+                    Some(payload) => BorshSerialize::serialize(payload, writer),
+                    None => Ok(()),
+                }
+            }
+            EventRequest::Fact(fact_request) => {
+                BorshSerialize::serialize(&1u8, writer)?;
+                BorshSerialize::serialize(fact_request, writer)
+            }
+            EventRequest::Transfer(transfer_request) => {
+                BorshSerialize::serialize(&2u8, writer)?;
+                BorshSerialize::serialize(transfer_request, writer)
+            }
+            EventRequest::EOL(eol_request) => {
+                BorshSerialize::serialize(&3u8, writer)?;
+                BorshSerialize::serialize(eol_request, writer)
+            }
+            EventRequest::Migrate(migrate_request) => {
+                BorshSerialize::serialize(&5u8, writer)?;
+                BorshSerialize::serialize(migrate_request, writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for EventRequest {
+    #[inline]
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let order: u8 = BorshDeserialize::deserialize_reader(reader)?;
+        match order {
+            0 | 4 => {
+                let governance_id = BorshDeserialize::deserialize_reader(reader)?;
+                let schema_id = BorshDeserialize::deserialize_reader(reader)?;
+                let namespace = BorshDeserialize::deserialize_reader(reader)?;
+                let name = BorshDeserialize::deserialize_reader(reader)?;
+                let public_key = BorshDeserialize::deserialize_reader(reader)?;
+                let payload = if order == 4 {
+                    Some(BorshDeserialize::deserialize_reader(reader)?)
+                } else {
+                    None
+                };
+                Ok(EventRequest::Create(StartRequest {
+                    governance_id,
+                    schema_id,
+                    namespace,
+                    name,
+                    public_key,
+                    payload,
+                }))
+            }
+            1 => {
+                let fact_request = BorshDeserialize::deserialize_reader(reader)?;
+                Ok(EventRequest::Fact(fact_request))
+            }
+            2 => {
+                let transfer_request = BorshDeserialize::deserialize_reader(reader)?;
+                Ok(EventRequest::Transfer(transfer_request))
+            }
+            3 => {
+                let eol_request = BorshDeserialize::deserialize_reader(reader)?;
+                Ok(EventRequest::EOL(eol_request))
+            }
+            5 => {
+                let migrate_request = BorshDeserialize::deserialize_reader(reader)?;
+                Ok(EventRequest::Migrate(migrate_request))
+            }
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid EventRequest representation: {}", order),
+            )),
         }
     }
 }
@@ -100,6 +224,48 @@ pub enum RequestState {
     Finished,
     Error,
     Processing,
+    /// The request was cancelled by the node before its event was stored.
+    Cancelled,
+}
+
+/// Stages an event request goes through until its event is stored in the ledger.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum RequestStage {
+    Evaluation,
+    Approval,
+    Validation,
+    Distribution,
+    /// The request waits for the migration of the state of its subject.
+    Migration,
+}
+
+/// Record of a stage reached by a request.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RequestStageRecord {
+    /// The stage reached.
+    pub stage: RequestStage,
+    /// The moment the request entered the stage.
+    pub timestamp: TimeStamp,
+    /// The signers that have answered during the stage so far.
+    pub signers: HashSet<KeyIdentifier>,
+}
+
+/// Reason why a request has not succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum RequestFailure {
+    /// The evaluators reached a negative quorum. The event is stored without modifying the
+    /// state of the subject. Subjects whose creation fails are not created.
+    EvaluationFailed,
+    /// The approvers rejected the event. The event is stored without modifying the state
+    /// of the subject. Subjects whose creation is rejected are not created.
+    ApprovalRejected,
+    /// The processing of the request was stopped during the given stage, if it had reached any.
+    Error {
+        stage: Option<RequestStage>,
+        error: String,
+    },
+    /// The retry policy of the given stage was exhausted before reaching a quorum.
+    RetriesExhausted { stage: RequestStage },
 }
 
 /// A struct representing a TAPLE request.
@@ -117,6 +283,51 @@ pub struct TapleRequest {
     pub state: RequestState,
     /// The success status of the request, if any.
     pub success: Option<bool>,
+    /// The stages reached by the request, in order.
+    pub stages: Vec<RequestStageRecord>,
+    /// The reason why the request has not succeeded, if any.
+    pub failure: Option<RequestFailure>,
+}
+
+impl TapleRequest {
+    /// Returns the last stage reached by the request, if any.
+    pub fn current_stage(&self) -> Option<&RequestStage> {
+        self.stages.last().map(|record| &record.stage)
+    }
+
+    pub(crate) fn start_stage(&mut self, stage: RequestStage) {
+        self.stages.push(RequestStageRecord {
+            stage,
+            timestamp: TimeStamp::now(),
+            signers: HashSet::new(),
+        });
+    }
+
+    /// Registers a signer that has answered during the current stage.
+    pub(crate) fn add_signer(&mut self, signer: KeyIdentifier) {
+        if let Some(record) = self.stages.last_mut() {
+            record.signers.insert(signer);
+        }
+    }
+
+    /// Marks the request as failed during its current stage.
+    pub(crate) fn fail(&mut self, error: String) {
+        let stage = self.current_stage().cloned();
+        self.fail_with(RequestFailure::Error { stage, error });
+    }
+
+    /// Marks the request as failed for the given reason.
+    pub(crate) fn fail_with(&mut self, failure: RequestFailure) {
+        self.state = RequestState::Error;
+        self.success = Some(false);
+        self.failure = Some(failure);
+    }
+
+    /// Marks the request as cancelled.
+    pub(crate) fn cancel(&mut self) {
+        self.state = RequestState::Cancelled;
+        self.success = Some(false);
+    }
 }
 
 impl TryFrom<Signed<EventRequest>> for TapleRequest {
@@ -130,6 +341,9 @@ impl TryFrom<Signed<EventRequest>> for TapleRequest {
             crate::EventRequest::Fact(fact_request) => Some(fact_request.subject_id.clone()),
             crate::EventRequest::Transfer(transfer_res) => Some(transfer_res.subject_id.clone()),
             crate::EventRequest::EOL(eol_request) => Some(eol_request.subject_id.clone()),
+            crate::EventRequest::Migrate(migrate_request) => {
+                Some(migrate_request.subject_id.clone())
+            }
         };
         Ok(Self {
             id,
@@ -138,6 +352,8 @@ impl TryFrom<Signed<EventRequest>> for TapleRequest {
             event_request,
             state: RequestState::Processing,
             success: None,
+            stages: Vec::new(),
+            failure: None,
         })
     }
 }
@@ -11,7 +11,11 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use json_patch::{patch, Patch};
 use serde::{Deserialize, Serialize};
 
-use super::{evaluation::SubjectContext, request::EventRequest, value_wrapper::ValueWrapper};
+use super::{
+    evaluation::SubjectContext,
+    request::{EventRequest, MigrateRequest},
+    value_wrapper::ValueWrapper,
+};
 
 /// A struct representing a TAPLE subject.
 #[derive(Debug, Deserialize, Serialize, Clone, BorshSerialize, BorshDeserialize)]
@@ -34,6 +38,8 @@ pub struct Subject {
     pub name: String,
     /// The identifier of the schema used to validate the subject.
     pub schema_id: String,
+    /// The version of the schema used to validate the subject.
+    pub schema_version: u64,
     /// The identifier of the public key of the subject owner.
     pub owner: KeyIdentifier,
     /// The identifier of the public key of the subject creator.
@@ -61,6 +67,8 @@ pub struct SubjectData {
     pub name: String,
     /// The identifier of the schema used to validate the subject.
     pub schema_id: String,
+    /// The version of the schema used to validate the subject.
+    pub schema_version: u64,
     /// The identifier of the public key of the subject owner.
     pub owner: KeyIdentifier,
     /// The identifier of the public key of the subject creator.
@@ -80,6 +88,7 @@ impl From<Subject> for SubjectData {
             public_key: subject.public_key,
             namespace: subject.namespace,
             schema_id: subject.schema_id,
+            schema_version: subject.schema_version,
             owner: subject.owner,
             creator: subject.creator,
             properties: subject.properties,
@@ -128,6 +137,7 @@ impl Subject {
         SubjectContext {
             governance_id: self.governance_id.clone(),
             schema_id: self.schema_id.clone(),
+            schema_version: self.schema_version,
             is_owner: invoker == self.owner,
             state: self.properties.clone(),
             namespace: self.namespace.clone(),
@@ -137,6 +147,7 @@ impl Subject {
     pub fn from_genesis_event(
         event: Signed<Event>,
         init_state: ValueWrapper,
+        schema_version: u64,
         keys: Option<KeyPair>,
         derivator: DigestDerivator
     ) -> Result<Self, SubjectError> {
@@ -159,6 +170,7 @@ impl Subject {
             public_key: create_request.public_key,
             namespace: create_request.namespace.clone(),
             schema_id: create_request.schema_id.clone(),
+            schema_version,
             owner: event.content.event_request.signature.signer.clone(),
             creator: event.content.event_request.signature.signer.clone(),
             properties: init_state,
@@ -168,6 +180,44 @@ impl Subject {
         })
     }
 
+    /// Builds the subject a creation request would create, before its genesis event exists.
+    pub fn from_start_request(
+        event_request: &Signed<EventRequest>,
+        init_state: ValueWrapper,
+        schema_version: u64,
+        keys: Option<KeyPair>,
+        gov_version: u64,
+        derivator: DigestDerivator,
+    ) -> Result<Self, SubjectError> {
+        let EventRequest::Create(create_request) = &event_request.content else {
+            return Err(SubjectError::NotCreateEvent);
+        };
+        let subject_id = generate_subject_id(
+            &create_request.namespace,
+            &create_request.schema_id,
+            create_request.public_key.to_str(),
+            create_request.governance_id.to_str(),
+            gov_version,
+            derivator,
+        )?;
+        Ok(Subject {
+            keys,
+            subject_id,
+            governance_id: create_request.governance_id.clone(),
+            sn: 0,
+            public_key: create_request.public_key.clone(),
+            namespace: create_request.namespace.clone(),
+            schema_id: create_request.schema_id.clone(),
+            schema_version,
+            owner: event_request.signature.signer.clone(),
+            creator: event_request.signature.signer.clone(),
+            properties: init_state,
+            active: true,
+            name: create_request.name.clone(),
+            genesis_gov_version: gov_version,
+        })
+    }
+
     pub fn update_subject(
         &mut self,
         json_patch: ValueWrapper,
@@ -208,6 +258,19 @@ impl Subject {
         self.active = false;
     }
 
+    /// Moves the subject to the schema version of an upgrade event that has succeeded.
+    pub fn upgrade_schema(&mut self, event: &Event) {
+        if let EventRequest::Migrate(MigrateRequest {
+            schema_version: Some(schema_version),
+            ..
+        }) = &event.event_request.content
+        {
+            if event.eval_success && event.approved {
+                self.schema_version = *schema_version;
+            }
+        }
+    }
+
     pub fn state_hash_after_apply(
         &self,
         json_patch: ValueWrapper,
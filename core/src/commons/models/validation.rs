@@ -1,6 +1,9 @@
 //! Contains the data structures related to event  to send to approvers, or to validators if approval is not required.
 use crate::{
-    commons::{errors::SubjectError, models::event::Metadata},
+    commons::{
+        errors::SubjectError,
+        models::{evaluation::SubjectDependency, event::Metadata},
+    },
     identifier::{DigestIdentifier, KeyIdentifier},
     request::StartRequest,
     signature::Signature, DigestDerivator,
@@ -8,10 +11,10 @@ use crate::{
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
-use super::{state::Subject, HashId};
+use super::{deserialize_marked_subject_id, state::Subject, HashId, DEPENDENCIES_MARKER};
 
 /// A struct representing a validation proof.
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct ValidationProof {
     /// The identifier of the subject being validated.
     pub subject_id: DigestIdentifier,
@@ -35,6 +38,56 @@ pub struct ValidationProof {
     pub event_hash: DigestIdentifier,
     /// The version of the governance contract used to validate the subject.
     pub governance_version: u64,
+    /// The states of other subjects read by the contract during the evaluation of the event.
+    pub dependencies: Vec<SubjectDependency>,
+}
+
+// Proofs without dependencies keep the encoding they had before dependencies existed
+impl BorshSerialize for ValidationProof {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if !self.dependencies.is_empty() {
+            BorshSerialize::serialize(&DEPENDENCIES_MARKER, writer)?;
+        }
+        BorshSerialize::serialize(&self.subject_id, writer)?;
+        BorshSerialize::serialize(&self.schema_id, writer)?;
+        BorshSerialize::serialize(&self.namespace, writer)?;
+        BorshSerialize::serialize(&self.name, writer)?;
+        BorshSerialize::serialize(&self.subject_public_key, writer)?;
+        BorshSerialize::serialize(&self.governance_id, writer)?;
+        BorshSerialize::serialize(&self.genesis_governance_version, writer)?;
+        BorshSerialize::serialize(&self.sn, writer)?;
+        BorshSerialize::serialize(&self.prev_event_hash, writer)?;
+        BorshSerialize::serialize(&self.event_hash, writer)?;
+        BorshSerialize::serialize(&self.governance_version, writer)?;
+        if !self.dependencies.is_empty() {
+            BorshSerialize::serialize(&self.dependencies, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for ValidationProof {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let (has_dependencies, subject_id) = deserialize_marked_subject_id(reader)?;
+        let mut proof = Self {
+            subject_id,
+            schema_id: BorshDeserialize::deserialize_reader(reader)?,
+            namespace: BorshDeserialize::deserialize_reader(reader)?,
+            name: BorshDeserialize::deserialize_reader(reader)?,
+            subject_public_key: BorshDeserialize::deserialize_reader(reader)?,
+            governance_id: BorshDeserialize::deserialize_reader(reader)?,
+            genesis_governance_version: BorshDeserialize::deserialize_reader(reader)?,
+            sn: BorshDeserialize::deserialize_reader(reader)?,
+            prev_event_hash: BorshDeserialize::deserialize_reader(reader)?,
+            event_hash: BorshDeserialize::deserialize_reader(reader)?,
+            governance_version: BorshDeserialize::deserialize_reader(reader)?,
+            dependencies: vec![],
+        };
+        if has_dependencies {
+            proof.dependencies = BorshDeserialize::deserialize_reader(reader)?;
+        }
+        Ok(proof)
+    }
 }
 
 impl HashId for ValidationProof {
@@ -50,6 +103,7 @@ impl ValidationProof {
         event_hash: DigestIdentifier,
         governance_version: u64,
         subject_id: DigestIdentifier,
+        dependencies: Vec<SubjectDependency>,
     ) -> Self {
         Self {
             governance_id: start_request.governance_id,
@@ -63,6 +117,7 @@ impl ValidationProof {
             subject_public_key: start_request.public_key,
             genesis_governance_version: governance_version,
             name: start_request.name,
+            dependencies,
         }
     }
     pub fn new_from_transfer_event(
@@ -72,6 +127,7 @@ impl ValidationProof {
         event_hash: DigestIdentifier,
         governance_version: u64,
         subject_public_key: KeyIdentifier,
+        dependencies: Vec<SubjectDependency>,
     ) -> Self {
         Self {
             governance_id: subject.governance_id.clone(),
@@ -85,6 +141,7 @@ impl ValidationProof {
             subject_public_key,
             genesis_governance_version: subject.genesis_gov_version,
             name: subject.name.clone(),
+            dependencies,
         }
     }
 
@@ -94,6 +151,7 @@ impl ValidationProof {
         prev_event_hash: DigestIdentifier,
         event_hash: DigestIdentifier,
         governance_version: u64,
+        dependencies: Vec<SubjectDependency>,
     ) -> Self {
         Self {
             governance_id: subject.governance_id.clone(),
@@ -107,6 +165,7 @@ impl ValidationProof {
             subject_public_key: subject.public_key.clone(),
             genesis_governance_version: subject.genesis_gov_version,
             name: subject.name.clone(),
+            dependencies,
         }
     }
 
@@ -131,6 +190,7 @@ impl ValidationProof {
             && self.subject_public_key == other.subject_public_key
             && self.genesis_governance_version == other.genesis_governance_version
             && self.name == other.name
+            && self.dependencies == other.dependencies
     }
 }
 
@@ -150,3 +210,85 @@ pub struct ValidationEventResponse {
     pub validation_signature: Signature,
     pub gov_version_validation: u64,
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::ValidationProof;
+    use crate::{
+        commons::{
+            crypto::{Ed25519KeyPair, KeyGenerator, KeyMaterial},
+            models::evaluation::SubjectDependency,
+        },
+        identifier::{DigestIdentifier, KeyIdentifier},
+        request::StartRequest,
+    };
+
+    const SUBJECT_ID: &str = "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs";
+
+    /// Layout of the proofs stored before they carried dependencies.
+    #[derive(BorshSerialize)]
+    struct ValidationProofV1 {
+        subject_id: DigestIdentifier,
+        schema_id: String,
+        namespace: String,
+        name: String,
+        subject_public_key: KeyIdentifier,
+        governance_id: DigestIdentifier,
+        genesis_governance_version: u64,
+        sn: u64,
+        prev_event_hash: DigestIdentifier,
+        event_hash: DigestIdentifier,
+        governance_version: u64,
+    }
+
+    #[test]
+    fn proofs_without_dependencies_keep_their_encoding() {
+        let keypair = Ed25519KeyPair::from_seed(&[1]);
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let start_request = StartRequest {
+            governance_id: subject_id.clone(),
+            schema_id: "test".into(),
+            namespace: "".into(),
+            name: "name".into(),
+            public_key: KeyIdentifier::new(
+                crate::KeyDerivator::Ed25519,
+                &keypair.public_key_bytes(),
+            ),
+            payload: None,
+        };
+        let mut proof = ValidationProof::new_from_genesis_event(
+            start_request,
+            DigestIdentifier::default(),
+            0,
+            subject_id.clone(),
+            vec![],
+        );
+        let legacy = ValidationProofV1 {
+            subject_id: proof.subject_id.clone(),
+            schema_id: proof.schema_id.clone(),
+            namespace: proof.namespace.clone(),
+            name: proof.name.clone(),
+            subject_public_key: proof.subject_public_key.clone(),
+            governance_id: proof.governance_id.clone(),
+            genesis_governance_version: proof.genesis_governance_version,
+            sn: proof.sn,
+            prev_event_hash: proof.prev_event_hash.clone(),
+            event_hash: proof.event_hash.clone(),
+            governance_version: proof.governance_version,
+        };
+        let bytes = proof.try_to_vec().unwrap();
+        assert_eq!(bytes, legacy.try_to_vec().unwrap());
+        assert_eq!(ValidationProof::try_from_slice(&bytes).unwrap(), proof);
+        proof.dependencies.push(SubjectDependency {
+            subject_id,
+            sn: 3,
+            state_hash: DigestIdentifier::default(),
+        });
+        let bytes = proof.try_to_vec().unwrap();
+        assert_eq!(ValidationProof::try_from_slice(&bytes).unwrap(), proof);
+    }
+}
@@ -8,7 +8,11 @@ pub enum Quorum {
     MAJORITY,
     FIXED { fixed: u32 },
     PERCENTAGE { percentage: f64 },
-    // BFT { BFT: f64 },
+    // Byzantine fault tolerant quorum. More than two thirds of the signers, which is 2f + 1
+    // of 3f + 1 signers when up to f of them are faulty
+    BFT,
+    // Fraction of the summed weight of the signers
+    WEIGHTED { weighted: f64 },
 }
 
 impl Serialize for Quorum {
@@ -27,11 +31,13 @@ impl Serialize for Quorum {
                 let mut map = serializer.serialize_map(Some(1))?;
                 map.serialize_entry("PERCENTAGE", percentage)?;
                 map.end()
-            } // Quorum::BFT { BFT } => {
-              //     let mut map = serializer.serialize_map(Some(1))?;
-              //     map.serialize_entry("BFT", BFT)?;
-              //     map.end()
-              // }
+            }
+            Quorum::BFT => serializer.serialize_str("BFT"),
+            Quorum::WEIGHTED { weighted } => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("WEIGHTED", weighted)?;
+                map.end()
+            }
         }
     }
 }
@@ -53,25 +59,25 @@ impl<'de> Deserialize<'de> for Quorum {
             {
                 // Solo deberían tener una entrada
                 let Some(key) = map.next_key::<String>()? else {
-                    return Err(serde::de::Error::missing_field("FIXED or PERCENTAGE"))
+                    return Err(serde::de::Error::missing_field("FIXED, PERCENTAGE or WEIGHTED"))
                 };
                 let result = match key.as_str() {
                     "FIXED" => {
                         let fixed: u32 = map.next_value()?;
                         Quorum::FIXED { fixed }
                     }
-                    // "BFT" => {
-                    //     let bft: f64 = map.next_value()?;
-                    //     Quorum::BFT { BFT: bft }
-                    // }
                     "PERCENTAGE" => {
                         let percentage: f64 = map.next_value()?;
                         Quorum::PERCENTAGE { percentage }
                     }
+                    "WEIGHTED" => {
+                        let weighted: f64 = map.next_value()?;
+                        Quorum::WEIGHTED { weighted }
+                    }
                     _ => {
                         return Err(serde::de::Error::unknown_field(
                             &key,
-                            &["FIXED", "PERCENTAGE"],
+                            &["FIXED", "PERCENTAGE", "WEIGHTED"],
                         ))
                     }
                 };
@@ -86,7 +92,11 @@ impl<'de> Deserialize<'de> for Quorum {
             {
                 match v.as_str() {
                     "MAJORITY" => Ok(Self::Value::MAJORITY),
-                    other => Err(serde::de::Error::unknown_variant(other, &["MAJORITY"])),
+                    "BFT" => Ok(Self::Value::BFT),
+                    other => Err(serde::de::Error::unknown_variant(
+                        other,
+                        &["MAJORITY", "BFT"],
+                    )),
                 }
             }
             fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
@@ -95,7 +105,11 @@ impl<'de> Deserialize<'de> for Quorum {
             {
                 match v {
                     "MAJORITY" => Ok(Self::Value::MAJORITY),
-                    other => Err(serde::de::Error::unknown_variant(other, &["MAJORITY"])),
+                    "BFT" => Ok(Self::Value::BFT),
+                    other => Err(serde::de::Error::unknown_variant(
+                        other,
+                        &["MAJORITY", "BFT"],
+                    )),
                 }
             }
         }
@@ -296,6 +310,10 @@ impl<'de> Deserialize<'de> for SchemaEnum {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Schema {
     pub id: String,
+    /// Version of the schema. Several versions of a schema may coexist, and each subject
+    /// uses the version it was created with until it is upgraded.
+    #[serde(default)]
+    pub version: u64,
     pub schema: serde_json::Value,
     pub initial_value: serde_json::Value,
     pub contract: Contract,
@@ -311,7 +329,60 @@ pub struct Role {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Contract {
+    /// Base64 encoded Rust source of the contract. Empty if the contract is precompiled.
+    #[serde(default)]
     pub raw: String,
+    /// Base64 encoded WASM module of the contract. If present, `raw` is not compiled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<String>,
+    /// Digest identifier of the WASM module bytes. Required with `wasm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm_hash: Option<String>,
+    /// Fuel available to each execution. The node setting is used if not present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// Memory limit, in bytes, of each execution. The node setting is used if not present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+    /// Built-in contract executed instead of `raw` or `wasm`. It is never compiled and its
+    /// events never require approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ContractMode>,
+    /// JSON pointers that each issuer may modify through a built-in contract.
+    /// Any pointer may be modified if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<ContractRole>,
+    /// Events other than facts that are evaluated by the contract. Ignored with `mode`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<ContractHook>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractMode {
+    /// The payload of the fact is a JSON patch applied to the state.
+    Patch,
+    /// The payload of the fact is the new state.
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractHook {
+    /// Transfers are evaluated by the `transfer_function` entry point.
+    Transfer,
+    /// End of life events are evaluated by the `eol_function` entry point.
+    EOL,
+    /// States that no longer match the schema are migrated by the `migrate_function` entry point.
+    Migration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractRole {
+    pub who: Who,
+    pub namespace: String,
+    /// JSON pointers of the state, including everything below them.
+    pub pointers: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -320,6 +391,9 @@ pub struct Member {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    // Weight of the member's votes in weighted quorums. 1 if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -37,6 +37,17 @@ impl Schema {
             }
         }
     }
+
+    /// Returns the JSON pointer to the invalid part of the value and the description of every
+    /// validation error found
+    pub fn validation_errors(&self, value: &Value) -> Vec<(String, String)> {
+        match self.json_schema.validate(value) {
+            Ok(_) => vec![],
+            Err(errors) => errors
+                .map(|error| (error.instance_path.to_string(), error.to_string()))
+                .collect(),
+        }
+    }
 }
 
 fn validate_gov_keyidentifiers(key: &str) -> bool {
@@ -53,11 +64,44 @@ pub fn get_governance_schema() -> Value {
           "type": "string",
           "enum": ["VALIDATOR", "CREATOR", "ISSUER", "WITNESS", "APPROVER", "EVALUATOR"]
         },
+        "who": {
+          "oneOf": [
+            {
+              "type": "object",
+              "properties": {
+                "ID": {
+                  "type": "string"
+                }
+              },
+              "required": ["ID"],
+              "additionalProperties": false
+            },
+            {
+              "type": "object",
+              "properties": {
+                "NAME": {
+                  "type": "string"
+                }
+              },
+              "required": ["NAME"],
+              "additionalProperties": false
+            },
+            {
+              "const": "MEMBERS"
+            },
+            {
+              "const": "ALL"
+            },
+            {
+              "const": "NOT_MEMBERS"
+            }
+          ]
+        },
         "quorum": {
           "oneOf": [
             {
               "type": "string",
-              "enum": ["MAJORITY"]
+              "enum": ["MAJORITY", "BFT"]
             },
             {
               "type": "object",
@@ -83,6 +127,18 @@ pub fn get_governance_schema() -> Value {
               "required": ["PERCENTAGE"],
               "additionalProperties": false
             },
+            {
+              "type": "object",
+              "properties": {
+                "WEIGHTED": {
+                  "type": "number",
+                  "exclusiveMinimum": 0,
+                  "maximum": 1
+                }
+              },
+              "required": ["WEIGHTED"],
+              "additionalProperties": false
+            },
           ]
         }
       },
@@ -109,6 +165,11 @@ pub fn get_governance_schema() -> Value {
               },
               "description": {
                 "type": "string"
+              },
+              "weight": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": 4294967295
               }
             },
             "required": [
@@ -124,62 +185,32 @@ pub fn get_governance_schema() -> Value {
             "type": "object",
             "properties": {
               "who": {
+                "$ref": "#/$defs/who"
+              },
+              "namespace": {
+                "type": "string"
+              },
+              "role": {
+                "$ref": "#/$defs/role"
+              },
+              "schema": {
                 "oneOf": [
-                {
-                  "type": "object",
-                  "properties": {
-                    "ID": {
-                      "type": "string"
-                    }
-                  },
-                  "required": ["ID"],
-                  "additionalProperties": false
-                },
-                {
-                  "type": "object",
-                  "properties": {
-                    "NAME": {
-                      "type": "string"
-                    }
+                  {
+                    "type": "object",
+                    "properties": {
+                      "ID": {
+                        "type": "string"
+                      }
+                    },
+                    "required": ["ID"],
+                    "additionalProperties": false
                   },
-                  "required": ["NAME"],
-                  "additionalProperties": false
-                },
-                {
-                  "const": "MEMBERS"
-                },
-                {
-                  "const": "ALL"
-                },
-                {
-                  "const": "NOT_MEMBERS"
-                }
-              ]
-            },
-            "namespace": {
-              "type": "string"
-            },
-            "role": {
-              "$ref": "#/$defs/role"
-            },
-            "schema": {
-              "oneOf": [
-                {
-                  "type": "object",
-                  "properties": {
-                    "ID": {
-                      "type": "string"
-                    }
+                  {
+                    "const": "ALL"
                   },
-                  "required": ["ID"],
-                  "additionalProperties": false
-                },
-                {
-                  "const": "ALL"
-                },
-                {
-                  "const": "NOT_GOVERNANCE"
-                }
+                  {
+                    "const": "NOT_GOVERNANCE"
+                  }
                 ]
               }
             },
@@ -196,6 +227,10 @@ pub fn get_governance_schema() -> Value {
               "id": {
                 "type": "string"
               },
+              "version": {
+                "type": "integer",
+                "minimum": 0
+              },
               "schema": {
                 "$schema": "http://json-schema.org/draft/2020-12/schema",
                 "$id": "http://json-schema.org/draft/2020-12/schema",
@@ -636,9 +671,62 @@ pub fn get_governance_schema() -> Value {
                   "raw": {
                     "type": "string"
                   },
+                  "wasm": {
+                    "type": "string"
+                  },
+                  "wasm_hash": {
+                    "type": "string"
+                  },
+                  "fuel": {
+                    "type": "integer",
+                    "minimum": 1
+                  },
+                  "memory": {
+                    "type": "integer",
+                    "minimum": 1
+                  },
+                  "mode": {
+                    "type": "string",
+                    "enum": ["patch", "replace"]
+                  },
+                  "roles": {
+                    "type": "array",
+                    "items": {
+                      "type": "object",
+                      "properties": {
+                        "who": {
+                          "$ref": "#/$defs/who"
+                        },
+                        "namespace": {
+                          "type": "string"
+                        },
+                        "pointers": {
+                          "type": "array",
+                          "items": {
+                            "type": "string",
+                            "format": "json-pointer"
+                          }
+                        }
+                      },
+                      "required": ["who", "namespace", "pointers"],
+                      "additionalProperties": false
+                    }
+                  },
+                  "hooks": {
+                    "type": "array",
+                    "items": {
+                      "type": "string",
+                      "enum": ["transfer", "eol", "migration"]
+                    },
+                    "uniqueItems": true
+                  }
                 },
                 "additionalProperties": false,
-                "required": ["raw"]
+                "anyOf": [
+                  { "required": ["raw"] },
+                  { "required": ["wasm", "wasm_hash"] },
+                  { "required": ["mode"] }
+                ]
               },
             },
             "required": [
@@ -708,4 +796,57 @@ mod test {
         let gov_schema = super::get_governance_schema();
         let _schema = Schema::compile(&gov_schema).expect("gov schema compiles");
     }
+
+    #[test]
+    fn bft_quorum_is_accepted() {
+        let gov_schema = super::get_governance_schema();
+        let schema = Schema::compile(&gov_schema).expect("gov schema compiles");
+        let mut governance = super::initial_state::get_governance_initial_state().0;
+        governance["policies"][0]["validate"]["quorum"] = serde_json::json!("BFT");
+        assert!(schema.validate(&governance));
+        let parsed: super::gov_models::Governance =
+            serde_json::from_value(governance.clone()).expect("governance deserializes");
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), governance);
+    }
+
+    #[test]
+    fn weighted_quorum_is_accepted() {
+        let gov_schema = super::get_governance_schema();
+        let schema = Schema::compile(&gov_schema).expect("gov schema compiles");
+        let mut governance = super::initial_state::get_governance_initial_state().0;
+        governance["members"] = serde_json::json!([{
+            "id": "EF3E6fTSLrsEWzkD2tkB6QbJU9R7IOkunImqp0PB_ejg",
+            "name": "Headquarters",
+            "weight": 3
+        }]);
+        governance["policies"][0]["approve"]["quorum"] = serde_json::json!({ "WEIGHTED": 0.5 });
+        assert!(schema.validate(&governance));
+        let parsed: super::gov_models::Governance =
+            serde_json::from_value(governance.clone()).expect("governance deserializes");
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), governance);
+        governance["members"][0]["weight"] = serde_json::json!(0);
+        assert!(!schema.validate(&governance));
+        governance["members"][0]["weight"] = serde_json::json!(u64::from(u32::MAX) + 1);
+        assert!(!schema.validate(&governance));
+        governance["members"][0]["weight"] = serde_json::json!(u32::MAX);
+        assert!(schema.validate(&governance));
+        governance["policies"][0]["approve"]["quorum"] = serde_json::json!({ "WEIGHTED": 0 });
+        assert!(!schema.validate(&governance));
+    }
+
+    #[test]
+    fn validation_errors_point_to_invalid_values() {
+        let gov_schema = super::get_governance_schema();
+        let schema = Schema::compile(&gov_schema).expect("gov schema compiles");
+        let mut governance = super::initial_state::get_governance_initial_state().0;
+        assert!(schema.validation_errors(&governance).is_empty());
+        governance["members"] = serde_json::json!([{
+            "id": "EF3E6fTSLrsEWzkD2tkB6QbJU9R7IOkunImqp0PB_ejg",
+            "name": "Headquarters",
+            "weight": 0
+        }]);
+        let errors = schema.validation_errors(&governance);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "/members/0/weight");
+    }
 }
@@ -244,12 +244,32 @@ pub struct NodeSettings {
     /// Percentage of network nodes receiving protocol messages in one iteration
     #[serde(rename = "replicationfactor")]
     pub replication_factor: f64,
-    /// Timeout to be used between protocol iterations
+    /// Deprecated and ignored. The time between protocol iterations is given by the
+    /// policies of `retry`, and a warning is logged at startup if it differs from the default
     pub timeout: u32,
+    /// Number of signers up to which the messages of an event are sent to all the signers
+    /// that have not answered in every attempt, instead of to a fraction of them in turns
+    #[serde(rename = "allatoncesigners", default)]
+    pub all_at_once_signers: usize,
+    /// Retry policies followed while the messages of each protocol stage get no response
+    #[serde(default)]
+    pub retry: RetryPolicies,
+    /// Number of the most recent notifications kept in the notification log. Subscriptions can
+    /// only be resumed from the cursors still in the log
+    #[serde(
+        rename = "notificationretention",
+        default = "default_notification_retention"
+    )]
+    pub notification_retention: u64,
     #[doc(hidden)]
     pub passvotation: u8,
     #[cfg(feature = "evaluation")]
     pub smartcontracts_directory: String,
+    /// Resources available to a smart contract execution, unless its schema sets its own.
+    /// Evaluators of the same governance should share these values
+    #[cfg(feature = "evaluation")]
+    #[serde(rename = "contractlimits", default)]
+    pub contract_limits: ContractLimits,
 }
 
 impl Default for NodeSettings {
@@ -260,10 +280,135 @@ impl Default for NodeSettings {
             digest_derivator:
                 crate::commons::identifier::derive::digest::DigestDerivator::Blake3_256,
             replication_factor: 0.25f64,
-            timeout: 3000u32,
+            timeout: DEFAULT_TIMEOUT,
+            all_at_once_signers: 0,
+            retry: RetryPolicies::default(),
+            notification_retention: default_notification_retention(),
             passvotation: 0,
             #[cfg(feature = "evaluation")]
             smartcontracts_directory: "./contracts".into(),
+            #[cfg(feature = "evaluation")]
+            contract_limits: ContractLimits::default(),
+        }
+    }
+}
+
+/// Value of the deprecated [NodeSettings::timeout] that is not warned about
+pub(crate) const DEFAULT_TIMEOUT: u32 = 3000;
+
+fn default_notification_retention() -> u64 {
+    100_000
+}
+
+/// Resources that a smart contract can consume in one execution.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ContractLimits {
+    /// Fuel units available. Each WASM instruction consumes, at least, one unit
+    pub fuel: u64,
+    /// Maximum bytes of the WASM linear memory and of the memory shared with the node
+    pub memory: u64,
+}
+
+impl Default for ContractLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 1_000_000_000u64,
+            memory: 64 * 1024 * 1024u64,
+        }
+    }
+}
+
+/// Policy followed to resend a protocol message while no response is received.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Milliseconds to wait after the first attempt
+    #[serde(rename = "initialdelay")]
+    pub initial_delay: u32,
+    /// Upper limit, in milliseconds, of the time to wait between two attempts
+    #[serde(rename = "maxdelay")]
+    pub max_delay: u32,
+    /// Factor applied to the time to wait after each attempt
+    pub multiplier: f64,
+    /// Fraction of the time to wait that is randomly added or subtracted in each attempt
+    pub jitter: f64,
+    /// Maximum number of attempts. Unlimited if not specified
+    #[serde(rename = "maxattempts")]
+    pub max_attempts: Option<u32>,
+    /// Milliseconds after the first attempt from which no more attempts are made.
+    /// Unlimited if not specified
+    pub deadline: Option<u32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: 2000u32,
+            max_delay: 30000u32,
+            multiplier: 2.0f64,
+            jitter: 0.1f64,
+            max_attempts: None,
+            deadline: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that waits the same time between attempts and never gives up.
+    pub fn fixed(interval: u32) -> Self {
+        Self {
+            initial_delay: interval,
+            max_delay: interval,
+            multiplier: 1.0f64,
+            jitter: 0.0f64,
+            max_attempts: None,
+            deadline: None,
+        }
+    }
+
+    /// Returns whether the policy limits the number of attempts or their duration.
+    pub fn is_bounded(&self) -> bool {
+        self.max_attempts.is_some() || self.deadline.is_some()
+    }
+}
+
+/// Retry policies of each protocol stage.
+///
+/// When the policy of the evaluation or approval of an event is exhausted, the request of the
+/// event fails with
+/// [RequestFailure::RetriesExhausted](crate::request::RequestFailure::RetriesExhausted).
+/// The messages of a stage are sent again from the first attempt every time one of the
+/// signers answers.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicies {
+    /// Policy of the evaluation requests sent to the evaluators
+    pub evaluation: RetryPolicy,
+    /// Policy of the approval requests sent to the approvers
+    pub approval: RetryPolicy,
+    /// Policy of the validation requests sent to the validators. Its maximum attempts and
+    /// deadline are ignored, since validators that have signed an event would not sign
+    /// another one for the same sn
+    pub validation: RetryPolicy,
+    /// Policy of the distribution of the events to the witnesses
+    pub distribution: RetryPolicy,
+    /// Policy of the requests for the last event of a subject. These requests stop when an
+    /// event of the subject is received
+    pub lce: RetryPolicy,
+}
+
+impl Default for RetryPolicies {
+    fn default() -> Self {
+        Self {
+            evaluation: RetryPolicy::default(),
+            approval: RetryPolicy::default(),
+            validation: RetryPolicy::default(),
+            distribution: RetryPolicy::default(),
+            lce: RetryPolicy {
+                max_attempts: Some(3),
+                ..RetryPolicy::default()
+            },
         }
     }
 }
@@ -0,0 +1,62 @@
+/// A single write contained in a [WriteBatch].
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteOperation {
+    /// Associates the value with the key.
+    Put(String, Vec<u8>),
+    /// Removes the value associated with the key.
+    Del(String),
+}
+
+/// Set of writes that must be applied to the database as a single unit.
+///
+/// The keys of a batch belong to the keyspace shared by all the collections of a
+/// [DatabaseManager](super::DatabaseManager), so the batch can be written through any of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriteBatch {
+    operations: Vec<WriteOperation>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+        }
+    }
+
+    /// Adds a put operation to the batch.
+    pub fn put(&mut self, key: &str, data: Vec<u8>) {
+        self.operations
+            .push(WriteOperation::Put(key.to_string(), data));
+    }
+
+    /// Adds a delete operation to the batch.
+    pub fn del(&mut self, key: &str) {
+        self.operations.push(WriteOperation::Del(key.to_string()));
+    }
+
+    /// Appends all the operations of another batch.
+    pub fn extend(&mut self, other: WriteBatch) {
+        self.operations.extend(other.operations);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn operations(&self) -> &[WriteOperation] {
+        &self.operations
+    }
+}
+
+impl IntoIterator for WriteBatch {
+    type Item = WriteOperation;
+    type IntoIter = std::vec::IntoIter<WriteOperation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.operations.into_iter()
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use crate::commons::models::approval::ApprovalEntity;
+use crate::commons::models::notification::NotificationEntry;
 use crate::commons::models::request::TapleRequest;
 use crate::commons::models::state::Subject;
 use crate::commons::models::validation::ValidationProof;
@@ -12,6 +13,7 @@ use crate::{ApprovalState, Event, EventRequest};
 
 use super::error::Error;
 use super::layers::lce_validation_proofs::LceValidationProofs;
+use super::layers::notification::NotificationDb;
 use super::layers::request::RequestDb;
 use super::{
     layers::{
@@ -22,7 +24,7 @@ use super::{
         subject_by_governance::SubjectByGovernanceDb, validation::ValidationDb,
         witness_signatures::WitnessSignaturesDb,
     },
-    DatabaseCollection, DatabaseManager,
+    DatabaseCollection, DatabaseManager, WriteBatch,
 };
 
 /// A struct representing a database that stores TAPLE data.
@@ -57,6 +59,11 @@ pub struct DB<C: DatabaseCollection> {
     lce_validation_proofs_db: LceValidationProofs<C>,
     /// The approvals database.
     approvals_db: ApprovalsDb<C>,
+    /// The notification log database.
+    notification_db: NotificationDb<C>,
+    /// Collection used to commit write batches. Batches can contain keys of every layer since
+    /// all the collections share the keyspace of the manager (see [DatabaseManager]).
+    batch_collection: C,
 }
 
 impl<C: DatabaseCollection> DB<C> {
@@ -77,6 +84,8 @@ impl<C: DatabaseCollection> DB<C> {
             PreauthorizedSbujectsAndProovidersDb::new(&manager);
         let lce_validation_proofs_db = LceValidationProofs::new(&manager);
         let approvals_db = ApprovalsDb::new(&manager);
+        let notification_db = NotificationDb::new(&manager);
+        let batch_collection = manager.create_collection("batch");
         Self {
             signature_db,
             subject_db,
@@ -93,9 +102,19 @@ impl<C: DatabaseCollection> DB<C> {
             preauthorized_subjects_and_providers_db,
             lce_validation_proofs_db,
             approvals_db,
+            notification_db,
+            batch_collection,
         }
     }
 
+    /// Atomically applies a batch built with the `*_batch` methods.
+    pub fn write_batch(&self, batch: WriteBatch) -> Result<(), Error> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.batch_collection.write_batch(batch)
+    }
+
     pub fn get_signatures(
         &self,
         subject_id: &DigestIdentifier,
@@ -115,10 +134,32 @@ impl<C: DatabaseCollection> DB<C> {
             .set_signatures(subject_id, sn, signatures, validation_proof)
     }
 
+    pub fn set_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+        signatures: HashSet<Signature>,
+        validation_proof: ValidationProof,
+    ) -> Result<(), Error> {
+        self.signature_db
+            .set_signatures_batch(batch, subject_id, sn, signatures, validation_proof)
+    }
+
     pub fn del_signatures(&self, subject_id: &DigestIdentifier, sn: u64) -> Result<(), Error> {
         self.signature_db.del_signatures(subject_id, sn)
     }
 
+    pub fn del_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+    ) -> Result<(), Error> {
+        self.signature_db
+            .del_signatures_batch(batch, subject_id, sn)
+    }
+
     pub fn get_validation_proof(
         &self,
         subject_id: &DigestIdentifier,
@@ -138,6 +179,16 @@ impl<C: DatabaseCollection> DB<C> {
         self.subject_db.set_subject(subject_id, subject)
     }
 
+    pub fn set_subject_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        subject: Subject,
+    ) -> Result<(), Error> {
+        self.subject_db
+            .set_subject_batch(batch, subject_id, subject)
+    }
+
     pub fn get_subjects(
         &self,
         from: Option<String>,
@@ -180,10 +231,28 @@ impl<C: DatabaseCollection> DB<C> {
         self.event_db.set_event(subject_id, event)
     }
 
+    pub fn set_event_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        event: Signed<Event>,
+    ) -> Result<(), Error> {
+        self.event_db.set_event_batch(batch, subject_id, event)
+    }
+
     pub fn del_event(&self, subject_id: &DigestIdentifier, sn: u64) -> Result<(), Error> {
         self.event_db.del_event(subject_id, sn)
     }
 
+    pub fn del_event_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+    ) -> Result<(), Error> {
+        self.event_db.del_event_batch(batch, subject_id, sn)
+    }
+
     pub fn get_prevalidated_event(
         &self,
         subject_id: &DigestIdentifier,
@@ -245,6 +314,16 @@ impl<C: DatabaseCollection> DB<C> {
         self.request_db.set_request(request_id, request)
     }
 
+    pub fn set_taple_request_batch(
+        &self,
+        batch: &mut WriteBatch,
+        request_id: &DigestIdentifier,
+        request: &TapleRequest,
+    ) -> Result<(), Error> {
+        self.request_db
+            .set_request_batch(batch, request_id, request)
+    }
+
     pub fn del_taple_request(&self, request_id: &DigestIdentifier) -> Result<(), Error> {
         self.request_db.del_request(request_id)
     }
@@ -277,20 +356,29 @@ impl<C: DatabaseCollection> DB<C> {
         &self,
         governance_id: &DigestIdentifier,
         schema_id: &str,
+        schema_version: u64,
     ) -> Result<(Vec<u8>, DigestIdentifier, u64), Error> {
-        self.contract_db.get_contract(governance_id, schema_id)
+        self.contract_db
+            .get_contract(governance_id, schema_id, schema_version)
     }
 
     pub fn put_contract(
         &self,
         governance_id: &DigestIdentifier,
         schema_id: &str,
+        schema_version: u64,
         contract: Vec<u8>,
         hash: DigestIdentifier,
         gov_version: u64,
     ) -> Result<(), Error> {
-        self.contract_db
-            .put_contract(governance_id, schema_id, contract, hash, gov_version)
+        self.contract_db.put_contract(
+            governance_id,
+            schema_id,
+            schema_version,
+            contract,
+            hash,
+            gov_version,
+        )
     }
 
     pub fn get_witness_signatures(
@@ -317,6 +405,17 @@ impl<C: DatabaseCollection> DB<C> {
             .set_witness_signatures(subject_id, sn, signatures)
     }
 
+    pub fn replace_witness_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+        signatures: HashSet<Signature>,
+    ) -> Result<(), Error> {
+        self.witness_signatures_db
+            .replace_witness_signatures_batch(batch, subject_id, sn, signatures)
+    }
+
     pub fn del_witness_signatures(&self, subject_id: &DigestIdentifier) -> Result<(), Error> {
         self.witness_signatures_db
             .del_witness_signatures(subject_id)
@@ -331,6 +430,16 @@ impl<C: DatabaseCollection> DB<C> {
             .set_governance_index(subject_id, governance_id)
     }
 
+    pub fn set_governance_index_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        governance_id: &DigestIdentifier,
+    ) -> Result<(), Error> {
+        self.subject_by_governance_db
+            .set_governance_index_batch(batch, subject_id, governance_id)
+    }
+
     pub fn get_subjects_by_governance(
         &self,
         governance_id: &DigestIdentifier,
@@ -374,6 +483,14 @@ impl<C: DatabaseCollection> DB<C> {
         self.keys_db.del_keys(public_key)
     }
 
+    pub fn del_keys_batch(
+        &self,
+        batch: &mut WriteBatch,
+        public_key: &KeyIdentifier,
+    ) -> Result<(), Error> {
+        self.keys_db.del_keys_batch(batch, public_key)
+    }
+
     pub fn get_preauthorized_subject_and_providers(
         &self,
         subject_id: &DigestIdentifier,
@@ -417,6 +534,16 @@ impl<C: DatabaseCollection> DB<C> {
             .set_lce_validation_proof(subject_id, proof)
     }
 
+    pub fn set_lce_validation_proof_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        proof: ValidationProof,
+    ) -> Result<(), Error> {
+        self.lce_validation_proofs_db
+            .set_lce_validation_proof_batch(batch, subject_id, proof)
+    }
+
     pub fn del_lce_validation_proof(&self, subject_id: &DigestIdentifier) -> Result<(), Error> {
         self.lce_validation_proofs_db
             .del_lce_validation_proof(subject_id)
@@ -496,4 +623,28 @@ impl<C: DatabaseCollection> DB<C> {
         self.approvals_db
             .set_governance_approval_index(governance_id, request_id)
     }
+
+    pub fn get_last_notification_cursor(&self) -> Result<Option<u64>, Error> {
+        self.notification_db.get_last_notification_cursor()
+    }
+
+    pub fn get_notifications(
+        &self,
+        from: u64,
+        quantity: usize,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        self.notification_db.get_notifications(from, quantity)
+    }
+
+    pub fn set_notifications(
+        &self,
+        entries: &[NotificationEntry],
+        retention: u64,
+    ) -> Result<(), Error> {
+        self.notification_db.set_notifications(entries, retention)
+    }
+
+    pub fn del_notifications_before(&self, cursor: u64) -> Result<(), Error> {
+        self.notification_db.del_notifications_before(cursor)
+    }
 }
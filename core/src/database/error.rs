@@ -26,4 +26,14 @@ pub enum Error {
     CustomError(String),
     #[error("State non existent, possibilities are: Pending or Voted.")]
     NonExistentStatus,
+    /// The collection was written by a newer version of TAPLE
+    #[error("Collection {collection} has schema version {version}, but the newest supported is {supported}")]
+    UnsupportedSchemaVersion {
+        collection: String,
+        version: u32,
+        supported: u32,
+    },
+    /// There is no migration to upgrade the collection from the stored version
+    #[error("No migration available for collection {collection} from schema version {version}")]
+    MissingMigration { collection: String, version: u32 },
 }
@@ -0,0 +1,537 @@
+use super::{Error, WriteBatch, WriteOperation};
+use crate::{test_database_manager_trait, DatabaseCollection, DatabaseManager};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Read, Write},
+    ops::Bound,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+const LOG_FILE: &str = "taple.log";
+const COMPACTION_FILE: &str = "taple.log.compact";
+/// Minimum size of the log before a compaction is considered
+const COMPACTION_MIN_SIZE: u64 = 4 * 1024 * 1024;
+
+const OP_PUT: u8 = 0;
+const OP_DEL: u8 = 1;
+
+struct Inner {
+    data: BTreeMap<String, Vec<u8>>,
+    log: File,
+    /// Bytes currently written in the log file
+    log_size: u64,
+    /// Bytes of the log that belong to live entries
+    live_size: u64,
+    /// Set if a failed write could not be removed from the log. Later records would be
+    /// discarded with it on the next start, so no more writes are accepted
+    poisoned: bool,
+}
+
+/// Append-only log backing a [FileManager].
+///
+/// Each record is written as `[len: u32][checksum: 4 bytes][payload]`, where the payload is a list
+/// of operations. Single writes and whole [WriteBatch]es are stored as one record. A record is only
+/// applied if it is complete and its checksum matches, so a write interrupted by a crash is
+/// discarded on the next start instead of corrupting the store.
+/// The whole content is kept in memory to serve reads and ordered iterations.
+struct FileStore {
+    path: PathBuf,
+    inner: RwLock<Inner>,
+}
+
+impl FileStore {
+    fn open(path: &Path) -> Result<Self, Error> {
+        fs::create_dir_all(path).map_err(io_error)?;
+        // A leftover compaction file means that the process died before the rename
+        let _ = fs::remove_file(path.join(COMPACTION_FILE));
+        let log_path = path.join(LOG_FILE);
+        let mut data = BTreeMap::new();
+        let valid_size = if log_path.exists() {
+            let file = File::open(&log_path).map_err(io_error)?;
+            replay(BufReader::new(file), &mut data)?
+        } else {
+            0
+        };
+        let log = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(&log_path)
+            .map_err(io_error)?;
+        // Discards any torn record at the end of the log
+        log.set_len(valid_size).map_err(io_error)?;
+        log.sync_all().map_err(io_error)?;
+        let log = OpenOptions::new()
+            .append(true)
+            .open(&log_path)
+            .map_err(io_error)?;
+        let live_size = data.iter().map(|(key, value)| entry_size(key, value)).sum();
+        Ok(Self {
+            path: path.to_path_buf(),
+            inner: RwLock::new(Inner {
+                data,
+                log,
+                log_size: valid_size,
+                live_size,
+                poisoned: false,
+            }),
+        })
+    }
+
+    fn write(&self, operations: Vec<WriteOperation>) -> Result<(), Error> {
+        let record = encode_record(&operations);
+        let mut inner = self.inner.write().unwrap();
+        if inner.poisoned {
+            return Err(Error::CustomError(
+                "The database log is inconsistent after a failed write".to_owned(),
+            ));
+        }
+        let result = inner
+            .log
+            .write_all(&record)
+            .and_then(|_| inner.log.sync_data());
+        if let Err(error) = result {
+            // A partial record would make the next start discard the records written after it.
+            // The log is opened in append mode, so the next record is written at the new end
+            if inner.log.set_len(inner.log_size).is_err() {
+                inner.poisoned = true;
+            }
+            return Err(io_error(error));
+        }
+        inner.log_size += record.len() as u64;
+        for operation in operations {
+            match operation {
+                WriteOperation::Put(key, value) => {
+                    let size = entry_size(&key, &value);
+                    if let Some(old) = inner.data.insert(key.clone(), value) {
+                        inner.live_size -= entry_size(&key, &old);
+                    }
+                    inner.live_size += size;
+                }
+                WriteOperation::Del(key) => {
+                    if let Some(old) = inner.data.remove(&key) {
+                        inner.live_size -= entry_size(&key, &old);
+                    }
+                }
+            }
+        }
+        if inner.log_size > COMPACTION_MIN_SIZE && inner.log_size > 2 * inner.live_size {
+            // The write is already durable, so a failed compaction is retried on a later write
+            if let Err(error) = self.compact(&mut inner) {
+                log::warn!("The database log could not be compacted: {}", error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrites the log with only the live entries. The new log is fully written and synced
+    /// before atomically replacing the previous one. The current log is kept if the compaction
+    /// fails before the replacement.
+    fn compact(&self, inner: &mut Inner) -> Result<(), Error> {
+        let compaction_path = self.path.join(COMPACTION_FILE);
+        let log_path = self.path.join(LOG_FILE);
+        let operations: Vec<WriteOperation> = inner
+            .data
+            .iter()
+            .map(|(key, value)| WriteOperation::Put(key.clone(), value.clone()))
+            .collect();
+        let record = encode_record(&operations);
+        let written = File::create(&compaction_path).and_then(|mut file| {
+            file.write_all(&record)?;
+            file.sync_all()
+        });
+        if let Err(error) = written.and_then(|_| fs::rename(&compaction_path, &log_path)) {
+            let _ = fs::remove_file(&compaction_path);
+            return Err(io_error(error));
+        }
+        // The current handle refers to the replaced log, so the new one is opened before
+        // reporting any error. Writes to the replaced log would be lost.
+        let synced = sync_dir(&self.path);
+        match OpenOptions::new().append(true).open(&log_path) {
+            Ok(log) => {
+                inner.log = log;
+                inner.log_size = record.len() as u64;
+            }
+            Err(error) => {
+                inner.poisoned = true;
+                return Err(io_error(error));
+            }
+        }
+        synced
+    }
+
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.inner.read().unwrap().data.get(key).cloned()
+    }
+
+    fn iter(&self, reverse: bool, prefix: String) -> FileIterator {
+        FileIterator {
+            store: self,
+            end: prefix_end(&prefix),
+            prefix,
+            reverse,
+            last: None,
+        }
+    }
+}
+
+/// Durable database implementation for TAPLE backed by an append-only file.
+pub struct FileManager {
+    store: Arc<FileStore>,
+    temporary: bool,
+}
+
+impl FileManager {
+    /// Opens (or creates) the database stored in the directory `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self {
+            store: Arc::new(FileStore::open(path.as_ref())?),
+            temporary: false,
+        })
+    }
+}
+
+impl DatabaseManager<FileCollection> for FileManager {
+    fn default() -> Self {
+        let path = std::env::temp_dir().join(format!("taple-db-{:016x}", rand::random::<u64>()));
+        Self {
+            store: Arc::new(FileStore::open(&path).expect("Temporal database could not be opened")),
+            temporary: true,
+        }
+    }
+
+    fn create_collection(&self, _identifier: &str) -> FileCollection {
+        FileCollection {
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl Drop for FileManager {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.store.path);
+        }
+    }
+}
+
+/// Collection for file database implementation. It must be created through [FileManager].
+pub struct FileCollection {
+    store: Arc<FileStore>,
+}
+
+impl DatabaseCollection for FileCollection {
+    fn get(&self, key: &str) -> Result<Vec<u8>, Error> {
+        let Some(data) = self.store.get(key) else {
+            return Err(Error::EntryNotFound);
+        };
+        Ok(data)
+    }
+
+    fn put(&self, key: &str, data: Vec<u8>) -> Result<(), Error> {
+        self.store
+            .write(vec![WriteOperation::Put(key.to_string(), data)])
+    }
+
+    fn del(&self, key: &str) -> Result<(), Error> {
+        self.store.write(vec![WriteOperation::Del(key.to_string())])
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), Error> {
+        self.store.write(batch.into_iter().collect())
+    }
+
+    fn iter<'a>(
+        &'a self,
+        reverse: bool,
+        prefix: String,
+    ) -> Box<dyn Iterator<Item = (String, Vec<u8>)> + 'a> {
+        Box::new(self.store.iter(reverse, prefix))
+    }
+}
+
+/// Iterator over the entries matching a prefix.
+///
+/// The entries are read one by one, each time looking up the one that follows the last returned
+/// key, so the store is only locked while reading an entry. Writes made during the iteration
+/// are seen if they affect keys that have not been reached yet.
+pub struct FileIterator<'a> {
+    store: &'a FileStore,
+    prefix: String,
+    /// Upper bound of the keys matching the prefix
+    end: Bound<String>,
+    reverse: bool,
+    last: Option<String>,
+}
+
+impl<'a> Iterator for FileIterator<'a> {
+    type Item = (String, Vec<u8>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = self.store.inner.read().unwrap();
+        let (key, value) = if self.reverse {
+            let upper = match self.last.take() {
+                Some(last) => Bound::Excluded(last),
+                None => self.end.clone(),
+            };
+            inner
+                .data
+                .range((Bound::Included(self.prefix.clone()), upper))
+                .next_back()?
+        } else {
+            let lower = match self.last.take() {
+                Some(last) => Bound::Excluded(last),
+                None => Bound::Included(self.prefix.clone()),
+            };
+            inner.data.range((lower, self.end.clone())).next()?
+        };
+        self.last = Some(key.clone());
+        Some((key[self.prefix.len()..].to_string(), value.clone()))
+    }
+}
+
+/// Smallest key greater than all the keys starting with `prefix`. Keys are compared by their
+/// bytes, which follows the order of their chars.
+fn prefix_end(prefix: &str) -> Bound<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        if let Some(next) = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32) {
+            chars.push(next);
+            return Bound::Excluded(chars.into_iter().collect());
+        }
+    }
+    Bound::Unbounded
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::CustomError(error.to_string())
+}
+
+fn entry_size(key: &str, value: &[u8]) -> u64 {
+    (key.len() + value.len() + 9) as u64
+}
+
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    let hash = blake3::hash(payload);
+    let mut result = [0u8; 4];
+    result.copy_from_slice(&hash.as_bytes()[0..4]);
+    result
+}
+
+fn encode_record(operations: &[WriteOperation]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for operation in operations {
+        match operation {
+            WriteOperation::Put(key, value) => {
+                payload.push(OP_PUT);
+                payload.extend_from_slice(&(key.len() as u32).to_le_bytes());
+                payload.extend_from_slice(key.as_bytes());
+                payload.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                payload.extend_from_slice(value);
+            }
+            WriteOperation::Del(key) => {
+                payload.push(OP_DEL);
+                payload.extend_from_slice(&(key.len() as u32).to_le_bytes());
+                payload.extend_from_slice(key.as_bytes());
+            }
+        }
+    }
+    let mut record = Vec::with_capacity(payload.len() + 8);
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(&checksum(&payload));
+    record.extend_from_slice(&payload);
+    record
+}
+
+fn decode_payload(payload: &[u8]) -> Option<Vec<WriteOperation>> {
+    fn read_u32(payload: &[u8], cursor: &mut usize) -> Option<usize> {
+        let bytes = payload.get(*cursor..*cursor + 4)?;
+        *cursor += 4;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+    }
+    fn read_bytes<'a>(payload: &'a [u8], cursor: &mut usize) -> Option<&'a [u8]> {
+        let len = read_u32(payload, cursor)?;
+        let bytes = payload.get(*cursor..*cursor + len)?;
+        *cursor += len;
+        Some(bytes)
+    }
+    let mut operations = Vec::new();
+    let mut cursor = 0;
+    while cursor < payload.len() {
+        let op = payload[cursor];
+        cursor += 1;
+        let key = String::from_utf8(read_bytes(payload, &mut cursor)?.to_vec()).ok()?;
+        match op {
+            OP_PUT => {
+                let value = read_bytes(payload, &mut cursor)?.to_vec();
+                operations.push(WriteOperation::Put(key, value));
+            }
+            OP_DEL => operations.push(WriteOperation::Del(key)),
+            _ => return None,
+        }
+    }
+    Some(operations)
+}
+
+/// Applies every valid record of the log and returns the size of the valid part of the file.
+fn replay<R: Read>(mut reader: R, data: &mut BTreeMap<String, Vec<u8>>) -> Result<u64, Error> {
+    let mut valid_size = 0u64;
+    loop {
+        let mut header = [0u8; 8];
+        if !read_full(&mut reader, &mut header)? {
+            break;
+        }
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let mut payload = vec![0u8; len];
+        if !read_full(&mut reader, &mut payload)? {
+            break;
+        }
+        if checksum(&payload) != header[4..8] {
+            break;
+        }
+        let Some(operations) = decode_payload(&payload) else {
+            break;
+        };
+        for operation in operations {
+            match operation {
+                WriteOperation::Put(key, value) => {
+                    data.insert(key, value);
+                }
+                WriteOperation::Del(key) => {
+                    data.remove(&key);
+                }
+            }
+        }
+        valid_size += (len + 8) as u64;
+    }
+    Ok(valid_size)
+}
+
+/// Fills `buffer` completely. Returns `false` if the end of the file is reached before.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<bool, Error> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => return Ok(false),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> Result<(), Error> {
+    File::open(path)
+        .and_then(|dir| dir.sync_all())
+        .map_err(io_error)
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
+test_database_manager_trait! {
+    unit_test_file_manager:crate::FileManager:FileCollection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reopen_and_torn_write_test() {
+        let path = std::env::temp_dir().join(format!("taple-db-{:016x}", rand::random::<u64>()));
+        {
+            let db = FileManager::new(&path).unwrap();
+            let collection = db.create_collection("first");
+            collection.put("a", vec![1]).unwrap();
+            collection.put("b", vec![2]).unwrap();
+            collection.del("a").unwrap();
+        }
+        // Simulates a crash in the middle of a write
+        {
+            let mut log = OpenOptions::new()
+                .append(true)
+                .open(path.join(LOG_FILE))
+                .unwrap();
+            log.write_all(&[200, 0, 0, 0, 1, 2]).unwrap();
+        }
+        {
+            let db = FileManager::new(&path).unwrap();
+            let collection = db.create_collection("first");
+            assert!(collection.get("a").is_err());
+            assert_eq!(collection.get("b").unwrap(), vec![2]);
+            collection.put("c", vec![3]).unwrap();
+        }
+        let db = FileManager::new(&path).unwrap();
+        let collection = db.create_collection("first");
+        let entries: Vec<(String, Vec<u8>)> = collection.iter(false, "".into()).collect();
+        assert_eq!(
+            entries,
+            vec![("b".to_string(), vec![2]), ("c".to_string(), vec![3])]
+        );
+        drop(db);
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn iter_reads_entries_lazily() {
+        let db = <FileManager as DatabaseManager<FileCollection>>::default();
+        let collection = db.create_collection("first");
+        for key in ["a", "b\u{10FFFF}", "b\u{10FFFF}c", "ba", "bb", "c"] {
+            collection.put(key, vec![1]).unwrap();
+        }
+        let mut iter = collection.iter(false, "b".into());
+        assert_eq!(iter.next().unwrap().0, "a");
+        // Entries written during the iteration are seen once they are reached
+        collection.put("bc", vec![2]).unwrap();
+        collection.del("bb").unwrap();
+        let keys: Vec<String> = iter.map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["c", "\u{10FFFF}", "\u{10FFFF}c"]);
+        let keys: Vec<String> = collection
+            .iter(true, "b".into())
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec!["\u{10FFFF}c", "\u{10FFFF}", "c", "a"]);
+        assert_eq!(
+            prefix_end("a\u{D7FF}"),
+            Bound::Excluded("a\u{E000}".to_owned())
+        );
+        assert_eq!(prefix_end("\u{10FFFF}"), Bound::Unbounded);
+    }
+
+    #[test]
+    fn failed_compaction_keeps_the_writes() {
+        let path = std::env::temp_dir().join(format!("taple-db-{:016x}", rand::random::<u64>()));
+        let value = vec![0u8; 1024 * 1024];
+        {
+            let db = FileManager::new(&path).unwrap();
+            // The compaction file can not be created over a directory
+            fs::create_dir(path.join(COMPACTION_FILE)).unwrap();
+            let collection = db.create_collection("first");
+            for index in 0..6u8 {
+                let mut value = value.clone();
+                value[0] = index;
+                collection.put("a", value).unwrap();
+            }
+            assert!(db.store.inner.read().unwrap().log_size > COMPACTION_MIN_SIZE);
+            collection.put("b", vec![1]).unwrap();
+            fs::remove_dir(path.join(COMPACTION_FILE)).unwrap();
+            // The next write compacts the log
+            collection.put("c", vec![2]).unwrap();
+            assert!(db.store.inner.read().unwrap().log_size < COMPACTION_MIN_SIZE);
+            collection.put("d", vec![3]).unwrap();
+        }
+        let db = FileManager::new(&path).unwrap();
+        let collection = db.create_collection("first");
+        assert_eq!(collection.get("a").unwrap()[0], 5);
+        assert_eq!(collection.get("b").unwrap(), vec![1]);
+        assert_eq!(collection.get("c").unwrap(), vec![2]);
+        assert_eq!(collection.get("d").unwrap(), vec![3]);
+        drop(db);
+        let _ = fs::remove_dir_all(&path);
+    }
+}
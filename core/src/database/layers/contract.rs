@@ -21,11 +21,13 @@ impl<C: DatabaseCollection> ContractDb<C> {
         &self,
         governance_id: &DigestIdentifier,
         schema_id: &str,
+        schema_version: u64,
     ) -> Result<(Vec<u8>, DigestIdentifier, u64), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
             Element::S(governance_id.to_str()),
             Element::S(schema_id.to_string()),
+            Element::N(schema_version),
         ];
         let key = get_key(key_elements)?;
         let contract = self.collection.get(&key)?;
@@ -37,6 +39,7 @@ impl<C: DatabaseCollection> ContractDb<C> {
         &self,
         governance_id: &DigestIdentifier,
         schema_id: &str,
+        schema_version: u64,
         contract: Vec<u8>,
         hash: DigestIdentifier,
         gov_version: u64,
@@ -45,6 +48,7 @@ impl<C: DatabaseCollection> ContractDb<C> {
             Element::S(self.prefix.clone()),
             Element::S(governance_id.to_str()),
             Element::S(schema_id.to_string()),
+            Element::N(schema_version),
         ];
         let key = get_key(key_elements)?;
         let Ok(data) = serialize::<(Vec<u8>, DigestIdentifier, u64)>(&(contract, hash, gov_version)) else {
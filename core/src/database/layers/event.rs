@@ -2,6 +2,7 @@ use super::utils::{get_by_range, get_key, Element};
 use crate::signature::Signed;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier, Event};
 use std::sync::Arc;
 
@@ -59,6 +60,17 @@ impl<C: DatabaseCollection> EventDb<C> {
         &self,
         subject_id: &DigestIdentifier,
         event: Signed<Event>,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_event_batch(&mut batch, subject_id, event)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_event_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        event: Signed<Event>,
     ) -> Result<(), DbError> {
         let sn = event.content.sn;
         let key_elements: Vec<Element> = vec![
@@ -70,16 +82,29 @@ impl<C: DatabaseCollection> EventDb<C> {
         let Ok(data) = serialize::<Signed<Event>>(&event) else {
             return Err(DbError::SerializeError);
         };
-        self.collection.put(&key, data)
+        batch.put(&key, data);
+        Ok(())
     }
 
     pub fn del_event(&self, subject_id: &DigestIdentifier, sn: u64) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.del_event_batch(&mut batch, subject_id, sn)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn del_event_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+    ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
             Element::S(subject_id.to_str()),
             Element::N(sn),
         ];
         let key = get_key(key_elements)?;
-        self.collection.del(&key)
+        batch.del(&key);
+        Ok(())
     }
 }
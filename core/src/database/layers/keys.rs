@@ -2,6 +2,7 @@ use super::utils::{get_key, Element};
 use crate::crypto::KeyPair;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, KeyIdentifier};
 use std::sync::Arc;
 
@@ -42,12 +43,23 @@ impl<C: DatabaseCollection> KeysDb<C> {
     }
 
     pub fn del_keys(&self, public_key: &KeyIdentifier) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.del_keys_batch(&mut batch, public_key)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn del_keys_batch(
+        &self,
+        batch: &mut WriteBatch,
+        public_key: &KeyIdentifier,
+    ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
             Element::S(public_key.to_str()),
         ];
         let key = get_key(key_elements)?;
-        self.collection.del(&key)
+        batch.del(&key);
+        Ok(())
     }
 
     pub fn get_all_keys(&self) -> Result<Vec<KeyPair>, DbError> {
@@ -2,6 +2,7 @@ use super::utils::{get_key, Element};
 use crate::commons::models::validation::ValidationProof;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier};
 use std::sync::Arc;
 
@@ -36,6 +37,17 @@ impl<C: DatabaseCollection> LceValidationProofs<C> {
         &self,
         subject_id: &DigestIdentifier,
         lce_validation_proof: ValidationProof,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_lce_validation_proof_batch(&mut batch, subject_id, lce_validation_proof)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_lce_validation_proof_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        lce_validation_proof: ValidationProof,
     ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
@@ -45,15 +57,27 @@ impl<C: DatabaseCollection> LceValidationProofs<C> {
         let Ok(data) = serialize::<ValidationProof>(&lce_validation_proof) else {
             return Err(DbError::SerializeError);
         };
-        self.collection.put(&key, data)
+        batch.put(&key, data);
+        Ok(())
     }
 
     pub fn del_lce_validation_proof(&self, subject_id: &DigestIdentifier) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.del_lce_validation_proof_batch(&mut batch, subject_id)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn del_lce_validation_proof_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+    ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
             Element::S(subject_id.to_str()),
         ];
         let key = get_key(key_elements)?;
-        self.collection.del(&key)
+        batch.del(&key);
+        Ok(())
     }
 }
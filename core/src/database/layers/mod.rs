@@ -5,13 +5,15 @@ pub(crate) mod event;
 pub(crate) mod event_request;
 pub(crate) mod keys;
 pub(crate) mod lce_validation_proofs;
+pub(crate) mod notification;
 pub(crate) mod preauthorized_subjects_and_providers;
 pub(crate) mod prevalidated_event;
 pub(crate) mod request;
+pub(crate) mod schema_version;
 pub(crate) mod signature;
 pub(crate) mod subject;
 pub(crate) mod subject_by_governance;
 pub(crate) mod validation;
 pub(crate) mod witness_signatures;
 
-mod utils;
+pub(super) mod utils;
@@ -0,0 +1,118 @@
+use super::utils::{get_key, Element};
+use crate::commons::models::notification::NotificationEntry;
+use crate::utils::{deserialize, serialize};
+use crate::DatabaseCollection;
+use crate::DatabaseManager;
+use crate::DbError;
+use crate::WriteBatch;
+use std::sync::Arc;
+
+pub(crate) struct NotificationDb<C: DatabaseCollection> {
+    collection: C,
+    prefix: String,
+}
+
+impl<C: DatabaseCollection> NotificationDb<C> {
+    pub fn new<M: DatabaseManager<C>>(manager: &Arc<M>) -> Self {
+        Self {
+            collection: manager.create_collection("notification"),
+            prefix: "notification".to_string(),
+        }
+    }
+
+    pub fn get_last_notification_cursor(&self) -> Result<Option<u64>, DbError> {
+        self.get_edge_cursor(true)
+    }
+
+    pub fn get_first_notification_cursor(&self) -> Result<Option<u64>, DbError> {
+        self.get_edge_cursor(false)
+    }
+
+    fn get_edge_cursor(&self, last: bool) -> Result<Option<u64>, DbError> {
+        let Some((key, _)) = self
+            .collection
+            .iter(last, format!("{}{}", self.prefix, char::MAX))
+            .next()
+        else {
+            return Ok(None);
+        };
+        // Keys are the cursors in hexadecimal, so they can be read without decoding the entry
+        let cursor = u64::from_str_radix(&key, 16).map_err(|_| DbError::DeserializeError)?;
+        Ok(Some(cursor))
+    }
+
+    /// Returns up to `quantity` notifications, starting with the one with the cursor `from`.
+    /// If that notification has already been removed from the log, it starts with the oldest
+    /// one.
+    pub fn get_notifications(
+        &self,
+        from: u64,
+        quantity: usize,
+    ) -> Result<Vec<NotificationEntry>, DbError> {
+        let (Some(first), Some(last)) = (
+            self.get_first_notification_cursor()?,
+            self.get_last_notification_cursor()?,
+        ) else {
+            return Ok(Vec::new());
+        };
+        let mut result = Vec::new();
+        // Entries are looked up by their cursor instead of scanning the log from its start
+        let mut cursor = from.max(first);
+        while cursor <= last && result.len() < quantity {
+            match self.collection.get(&self.get_notification_key(cursor)?) {
+                Ok(entry) => result.push(
+                    deserialize::<NotificationEntry>(&entry)
+                        .map_err(|_| DbError::DeserializeError)?,
+                ),
+                Err(DbError::EntryNotFound) => {}
+                Err(error) => return Err(error),
+            }
+            cursor += 1;
+        }
+        Ok(result)
+    }
+
+    /// Stores some notifications with a single write, removing the ones that leave the last
+    /// `retention` notifications.
+    pub fn set_notifications(
+        &self,
+        entries: &[NotificationEntry],
+        retention: u64,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        for entry in entries {
+            let Ok(data) = serialize::<NotificationEntry>(entry) else {
+                return Err(DbError::SerializeError);
+            };
+            batch.put(&self.get_notification_key(entry.cursor)?, data);
+            // Operations are applied in order, so an entry of the same batch can be removed
+            if let Some(expired) = entry.cursor.checked_sub(retention) {
+                batch.del(&self.get_notification_key(expired)?);
+            }
+        }
+        self.collection.write_batch(batch)
+    }
+
+    /// Removes all the notifications with a cursor lower than `cursor`.
+    pub fn del_notifications_before(&self, cursor: u64) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        for (key, _) in self
+            .collection
+            .iter(false, format!("{}{}", self.prefix, char::MAX))
+        {
+            let current = u64::from_str_radix(&key, 16).map_err(|_| DbError::DeserializeError)?;
+            if current >= cursor {
+                break;
+            }
+            batch.del(&self.get_notification_key(current)?);
+        }
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.collection.write_batch(batch)
+    }
+
+    fn get_notification_key(&self, cursor: u64) -> Result<String, DbError> {
+        get_key(vec![Element::S(self.prefix.clone()), Element::N(cursor)])
+    }
+}
@@ -2,6 +2,7 @@ use super::utils::{get_key, Element};
 use crate::commons::models::request::TapleRequest;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier};
 use std::sync::Arc;
 
@@ -44,6 +45,17 @@ impl<C: DatabaseCollection> RequestDb<C> {
         &self,
         request_id: &DigestIdentifier,
         request: &TapleRequest,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_request_batch(&mut batch, request_id, request)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_request_batch(
+        &self,
+        batch: &mut WriteBatch,
+        request_id: &DigestIdentifier,
+        request: &TapleRequest,
     ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
@@ -53,7 +65,8 @@ impl<C: DatabaseCollection> RequestDb<C> {
         let Ok(data) = serialize::<TapleRequest>(request) else {
             return Err(DbError::SerializeError);
         };
-        self.collection.put(&key, data)
+        batch.put(&key, data);
+        Ok(())
     }
 
     pub fn del_request(&self, request_id: &DigestIdentifier) -> Result<(), DbError> {
@@ -0,0 +1,70 @@
+use super::utils::{get_key, Element};
+use crate::utils::{deserialize, serialize};
+use crate::DbError;
+use crate::WriteBatch;
+use crate::{DatabaseCollection, DatabaseManager};
+use std::sync::Arc;
+
+pub(crate) struct SchemaVersionDb<C: DatabaseCollection> {
+    collection: C,
+    prefix: String,
+}
+
+impl<C: DatabaseCollection> SchemaVersionDb<C> {
+    pub fn new<M: DatabaseManager<C>>(manager: &Arc<M>) -> Self {
+        Self {
+            collection: manager.create_collection("schema-version"),
+            prefix: "schema-version".to_string(),
+        }
+    }
+
+    pub fn get_schema_version(&self, collection: &str) -> Result<Option<u32>, DbError> {
+        let key_elements: Vec<Element> = vec![
+            Element::S(self.prefix.clone()),
+            Element::S(collection.to_string()),
+        ];
+        let key = get_key(key_elements)?;
+        match self.collection.get(&key) {
+            Ok(version) => Ok(Some(
+                deserialize::<u32>(&version).map_err(|_| DbError::DeserializeError)?,
+            )),
+            Err(DbError::EntryNotFound) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn set_schema_version_batch(
+        &self,
+        batch: &mut WriteBatch,
+        collection: &str,
+        version: u32,
+    ) -> Result<(), DbError> {
+        let key_elements: Vec<Element> = vec![
+            Element::S(self.prefix.clone()),
+            Element::S(collection.to_string()),
+        ];
+        let key = get_key(key_elements)?;
+        let Ok(data) = serialize::<u32>(&version) else {
+            return Err(DbError::SerializeError);
+        };
+        batch.put(&key, data);
+        Ok(())
+    }
+
+    /// Checks if there is any entry stored under the prefix of a collection. The entries of
+    /// every collection are visible from this one, since collections share their keyspace.
+    pub fn has_entries(&self, prefix: &str) -> bool {
+        self.collection
+            .iter(false, format!("{}{}", prefix, char::MAX))
+            .next()
+            .is_some()
+    }
+
+    pub fn write_batch(&self, batch: WriteBatch) -> Result<(), DbError> {
+        self.collection.write_batch(batch)
+    }
+
+    pub fn collection(&self) -> &C {
+        &self.collection
+    }
+}
@@ -3,6 +3,7 @@ use crate::commons::models::validation::ValidationProof;
 use crate::signature::Signature;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -44,6 +45,19 @@ impl<C: DatabaseCollection> SignatureDb<C> {
         sn: u64,
         signatures: HashSet<Signature>,
         validation_proof: ValidationProof,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_signatures_batch(&mut batch, subject_id, sn, signatures, validation_proof)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+        signatures: HashSet<Signature>,
+        validation_proof: ValidationProof,
     ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
@@ -64,17 +78,30 @@ impl<C: DatabaseCollection> SignatureDb<C> {
         };
         let total_signatures = serialize(&(total_signatures, validation_proof))
             .map_err(|_| DbError::SerializeError)?;
-        self.collection.put(&key, total_signatures)
+        batch.put(&key, total_signatures);
+        Ok(())
     }
 
     pub fn del_signatures(&self, subject_id: &DigestIdentifier, sn: u64) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.del_signatures_batch(&mut batch, subject_id, sn)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn del_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+    ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
             Element::S(subject_id.to_str()),
             Element::N(sn),
         ];
         let key = get_key(key_elements)?;
-        self.collection.del(&key)
+        batch.del(&key);
+        Ok(())
     }
 
     pub fn get_validation_proof(
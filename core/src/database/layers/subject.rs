@@ -2,6 +2,7 @@ use super::utils::{get_by_range, get_key, Element};
 use crate::commons::models::state::Subject;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier};
 use std::sync::Arc;
 
@@ -32,6 +33,17 @@ impl<C: DatabaseCollection> SubjectDb<C> {
         &self,
         subject_id: &DigestIdentifier,
         subject: Subject,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_subject_batch(&mut batch, subject_id, subject)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_subject_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        subject: Subject,
     ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
@@ -41,7 +53,8 @@ impl<C: DatabaseCollection> SubjectDb<C> {
         let Ok(data) = serialize::<Subject>(&subject) else {
             return Err(DbError::SerializeError);
         };
-        self.collection.put(&key, data)
+        batch.put(&key, data);
+        Ok(())
     }
 
     pub fn del_subject(&self, subject_id: &DigestIdentifier) -> Result<(), DbError> {
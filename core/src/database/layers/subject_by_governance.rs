@@ -2,6 +2,7 @@ use super::utils::{get_by_range, get_key, Element};
 use crate::commons::models::state::Subject;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier};
 use std::sync::Arc;
 
@@ -22,6 +23,17 @@ impl<C: DatabaseCollection> SubjectByGovernanceDb<C> {
         &self,
         subject_id: &DigestIdentifier,
         governance_id: &DigestIdentifier,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_governance_index_batch(&mut batch, subject_id, governance_id)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_governance_index_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        governance_id: &DigestIdentifier,
     ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
@@ -32,7 +44,8 @@ impl<C: DatabaseCollection> SubjectByGovernanceDb<C> {
         let Ok(data) = serialize::<DigestIdentifier>(subject_id) else {
             return Err(DbError::SerializeError);
         };
-        self.collection.put(&key, data)
+        batch.put(&key, data);
+        Ok(())
     }
 
     pub fn get_subjects_by_governance(
@@ -2,6 +2,7 @@ use super::utils::{get_key, Element};
 use crate::signature::Signature;
 use crate::utils::{deserialize, serialize};
 use crate::DbError;
+use crate::WriteBatch;
 use crate::{DatabaseCollection, DatabaseManager, Derivable, DigestIdentifier};
 use std::collections::HashSet;
 use std::str::FromStr;
@@ -55,6 +56,18 @@ impl<C: DatabaseCollection> WitnessSignaturesDb<C> {
         subject_id: &DigestIdentifier,
         sn: u64,
         signatures: HashSet<Signature>,
+    ) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.set_witness_signatures_batch(&mut batch, subject_id, sn, signatures)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn set_witness_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+        signatures: HashSet<Signature>,
     ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
@@ -74,15 +87,47 @@ impl<C: DatabaseCollection> WitnessSignaturesDb<C> {
         let Ok(data) = serialize::<(u64, HashSet<Signature>)>(&(sn, total_signatures)) else {
             return Err(DbError::SerializeError);
         };
-        self.collection.put(&key, data)
+        batch.put(&key, data);
+        Ok(())
+    }
+
+    /// Overwrites the stored signatures instead of merging them with the new ones.
+    pub fn replace_witness_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+        signatures: HashSet<Signature>,
+    ) -> Result<(), DbError> {
+        let key_elements: Vec<Element> = vec![
+            Element::S(self.prefix.clone()),
+            Element::S(subject_id.to_str()),
+        ];
+        let key = get_key(key_elements)?;
+        let Ok(data) = serialize::<(u64, HashSet<Signature>)>(&(sn, signatures)) else {
+            return Err(DbError::SerializeError);
+        };
+        batch.put(&key, data);
+        Ok(())
     }
 
     pub fn del_witness_signatures(&self, subject_id: &DigestIdentifier) -> Result<(), DbError> {
+        let mut batch = WriteBatch::new();
+        self.del_witness_signatures_batch(&mut batch, subject_id)?;
+        self.collection.write_batch(batch)
+    }
+
+    pub fn del_witness_signatures_batch(
+        &self,
+        batch: &mut WriteBatch,
+        subject_id: &DigestIdentifier,
+    ) -> Result<(), DbError> {
         let key_elements: Vec<Element> = vec![
             Element::S(self.prefix.clone()),
             Element::S(subject_id.to_str()),
         ];
         let key = get_key(key_elements)?;
-        self.collection.del(&key)
+        batch.del(&key);
+        Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use super::Error;
+use super::{Error, WriteBatch, WriteOperation};
 use crate::{test_database_manager_trait, DatabaseCollection, DatabaseManager};
 use std::{
     collections::{btree_map::Iter, BTreeMap, HashMap},
@@ -107,6 +107,21 @@ impl DatabaseCollection for MemoryCollection {
             Box::new(self.data.iter(prefix))
         }
     }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), Error> {
+        let mut lock = self.data._get_inner_write_lock();
+        for operation in batch {
+            match operation {
+                WriteOperation::Put(key, data) => {
+                    lock.insert(key, data);
+                }
+                WriteOperation::Del(key) => {
+                    lock.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 type GuardIter<'a, K, V> = (Arc<RwLockReadGuard<'a, BTreeMap<K, V>>>, Iter<'a, K, V>);
@@ -0,0 +1,395 @@
+//! Versioning of the data layouts stored by the database layers.
+//!
+//! Every collection has its schema version stored under the `schema-version` prefix. When a node
+//! starts, [run_migrations] upgrades the collections written by older versions of TAPLE and refuses
+//! to continue if any of them was written by a newer one.
+//!
+//! The migrations read and write the collections through the `schema-version` collection. This
+//! relies on all the collections of a [DatabaseManager] sharing one keyspace, which also allows
+//! each migration step to be stored together with the new version in a single batch.
+use super::layers::schema_version::SchemaVersionDb;
+use super::layers::utils::{get_key, Element};
+use super::{DatabaseCollection, DatabaseManager, Error, WriteBatch};
+use crate::commons::crypto::KeyPair;
+use crate::commons::models::request::{RequestState, TapleRequest};
+use crate::commons::models::state::Subject;
+use crate::identifier::{DigestIdentifier, KeyIdentifier};
+use crate::signature::Signed;
+use crate::utils::{deserialize, serialize};
+use crate::{EventRequest, ValueWrapper};
+use borsh::BorshDeserialize;
+use std::sync::Arc;
+
+/// Current schema version of each collection, identified by the prefix of its keys.
+/// Any change in the key layout or in the encoding of the stored values must increase the
+/// version of the affected collection and add the corresponding step to [migrate].
+pub(crate) const SCHEMA_VERSIONS: &[(&str, u32)] = &[
+    ("signature", 1),
+    ("subject", 2),
+    ("event", 1),
+    ("prevalidated-event", 1),
+    ("request", 1),
+    ("taple-request", 2),
+    ("controller-id", 1),
+    ("validation", 1),
+    ("contract", 2),
+    ("witness-signatures", 1),
+    ("governance-index", 1),
+    ("keys", 1),
+    ("preauthorized-subjects-and-providers", 1),
+    ("lce-validation-proofs", 1),
+    ("approvals", 1),
+    ("subjindex-approval-index", 1),
+    ("governance-approval-index", 1),
+    ("pending-approval-index", 1),
+    ("notification", 1),
+];
+
+/// Upgrades every collection to its current schema version.
+pub(crate) fn run_migrations<M: DatabaseManager<C>, C: DatabaseCollection>(
+    manager: &Arc<M>,
+) -> Result<(), Error> {
+    let versions = SchemaVersionDb::new(manager);
+    for (collection, current) in SCHEMA_VERSIONS {
+        let stored = match versions.get_schema_version(collection)? {
+            Some(version) => version,
+            // Stores created before the versioning have no version registered
+            None if versions.has_entries(collection) => 0,
+            None => {
+                let mut batch = WriteBatch::new();
+                versions.set_schema_version_batch(&mut batch, collection, *current)?;
+                versions.write_batch(batch)?;
+                continue;
+            }
+        };
+        if stored > *current {
+            return Err(Error::UnsupportedSchemaVersion {
+                collection: collection.to_string(),
+                version: stored,
+                supported: *current,
+            });
+        }
+        // Each step is stored together with its new version, so an interrupted
+        // migration is resumed from the last completed step
+        for version in stored..*current {
+            let mut batch = WriteBatch::new();
+            migrate(collection, version, versions.collection(), &mut batch)?;
+            versions.set_schema_version_batch(&mut batch, collection, version + 1)?;
+            versions.write_batch(batch)?;
+            log::info!(
+                "Collection {} migrated to schema version {}",
+                collection,
+                version + 1
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Adds to the batch the changes needed to upgrade a collection from `version` to `version + 1`.
+/// `data` can be any collection of the manager, since they all share the same keyspace.
+fn migrate<C: DatabaseCollection>(
+    collection: &str,
+    version: u32,
+    data: &C,
+    batch: &mut WriteBatch,
+) -> Result<(), Error> {
+    match (collection, version) {
+        // The layouts used before the versioning are the same as version 1
+        (_, 0) => Ok(()),
+        // Requests track the stages they go through
+        ("taple-request", 1) => {
+            for (key, value) in data.iter(false, format!("{}{}", collection, char::MAX)) {
+                let request =
+                    deserialize::<TapleRequestV1>(&value).map_err(|_| Error::DeserializeError)?;
+                let request = TapleRequest {
+                    id: request.id,
+                    subject_id: request.subject_id,
+                    sn: request.sn,
+                    event_request: request.event_request,
+                    state: request.state,
+                    success: request.success,
+                    stages: Vec::new(),
+                    failure: None,
+                };
+                let Ok(value) = serialize::<TapleRequest>(&request) else {
+                    return Err(Error::SerializeError);
+                };
+                batch.put(&format!("{}{}{}", collection, char::MAX, key), value);
+            }
+            Ok(())
+        }
+        // Subjects record the version of their schema
+        ("subject", 1) => {
+            for (key, value) in data.iter(false, format!("{}{}", collection, char::MAX)) {
+                let subject =
+                    deserialize::<SubjectV1>(&value).map_err(|_| Error::DeserializeError)?;
+                let subject = Subject {
+                    keys: subject.keys,
+                    subject_id: subject.subject_id,
+                    governance_id: subject.governance_id,
+                    sn: subject.sn,
+                    genesis_gov_version: subject.genesis_gov_version,
+                    public_key: subject.public_key,
+                    namespace: subject.namespace,
+                    name: subject.name,
+                    schema_id: subject.schema_id,
+                    schema_version: 0,
+                    owner: subject.owner,
+                    creator: subject.creator,
+                    properties: subject.properties,
+                    active: subject.active,
+                };
+                let Ok(value) = serialize::<Subject>(&subject) else {
+                    return Err(Error::SerializeError);
+                };
+                batch.put(&format!("{}{}{}", collection, char::MAX, key), value);
+            }
+            Ok(())
+        }
+        // Contracts are stored per schema version
+        ("contract", 1) => {
+            for (key, value) in data.iter(false, format!("{}{}", collection, char::MAX)) {
+                let new_key = get_key(vec![
+                    Element::S(collection.to_string()),
+                    Element::S(key.clone()),
+                    Element::N(0),
+                ])?;
+                batch.del(&format!("{}{}{}", collection, char::MAX, key));
+                batch.put(&new_key, value);
+            }
+            Ok(())
+        }
+        _ => Err(Error::MissingMigration {
+            collection: collection.to_string(),
+            version,
+        }),
+    }
+}
+
+/// Layout of the subjects stored in version 1 of the `subject` collection.
+#[derive(BorshDeserialize)]
+struct SubjectV1 {
+    keys: Option<KeyPair>,
+    subject_id: DigestIdentifier,
+    governance_id: DigestIdentifier,
+    sn: u64,
+    genesis_gov_version: u64,
+    public_key: KeyIdentifier,
+    namespace: String,
+    name: String,
+    schema_id: String,
+    owner: KeyIdentifier,
+    creator: KeyIdentifier,
+    properties: ValueWrapper,
+    active: bool,
+}
+
+/// Layout of the requests stored in version 1 of the `taple-request` collection.
+#[derive(BorshDeserialize)]
+struct TapleRequestV1 {
+    id: DigestIdentifier,
+    subject_id: Option<DigestIdentifier>,
+    sn: Option<u64>,
+    event_request: Signed<EventRequest>,
+    state: RequestState,
+    success: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemoryCollection, MemoryManager};
+
+    fn get_version(manager: &Arc<MemoryManager>, collection: &str) -> Option<u32> {
+        SchemaVersionDb::<MemoryCollection>::new(manager)
+            .get_schema_version(collection)
+            .unwrap()
+    }
+
+    #[test]
+    fn new_database_is_stamped() {
+        let manager = Arc::new(MemoryManager::new());
+        run_migrations(&manager).unwrap();
+        for (collection, version) in SCHEMA_VERSIONS {
+            assert_eq!(get_version(&manager, collection), Some(*version));
+        }
+    }
+
+    #[test]
+    fn legacy_database_is_migrated() {
+        let manager = Arc::new(MemoryManager::new());
+        let collection = manager.create_collection("event");
+        collection
+            .put(&format!("event{}id", char::MAX), vec![1, 2, 3])
+            .unwrap();
+        run_migrations(&manager).unwrap();
+        assert_eq!(get_version(&manager, "event"), Some(1));
+        assert_eq!(
+            collection.get(&format!("event{}id", char::MAX)).unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn contract_is_migrated() {
+        let manager = Arc::new(MemoryManager::new());
+        let versions = SchemaVersionDb::<MemoryCollection>::new(&manager);
+        let mut batch = WriteBatch::new();
+        versions
+            .set_schema_version_batch(&mut batch, "contract", 1)
+            .unwrap();
+        versions.write_batch(batch).unwrap();
+        // The migration sees the entries written through the collection of the contracts
+        let contracts = manager.create_collection("contract");
+        let key = format!("contract{}governance{}schema", char::MAX, char::MAX);
+        contracts.put(&key, vec![1, 2, 3]).unwrap();
+        run_migrations(&manager).unwrap();
+        assert_eq!(get_version(&manager, "contract"), Some(2));
+        let new_key = get_key(vec![
+            Element::S("contract".into()),
+            Element::S("governance".into()),
+            Element::S("schema".into()),
+            Element::N(0),
+        ])
+        .unwrap();
+        assert_eq!(contracts.get(&new_key).unwrap(), vec![1, 2, 3]);
+        assert_eq!(contracts.get(&key), Err(Error::EntryNotFound));
+    }
+
+    #[test]
+    fn taple_request_is_migrated() {
+        use crate::commons::models::timestamp::TimeStamp;
+        use crate::identifier::derive::signature::SignatureDerivator;
+        use crate::request::EOLRequest;
+        use crate::signature::Signature;
+        use crate::{KeyDerivator, KeyIdentifier, SignatureIdentifier};
+        use borsh::BorshSerialize;
+
+        #[derive(BorshSerialize)]
+        struct LegacyRequest {
+            id: DigestIdentifier,
+            subject_id: Option<DigestIdentifier>,
+            sn: Option<u64>,
+            event_request: Signed<EventRequest>,
+            state: RequestState,
+            success: Option<bool>,
+        }
+
+        let manager = Arc::new(MemoryManager::new());
+        let versions = SchemaVersionDb::<MemoryCollection>::new(&manager);
+        let mut batch = WriteBatch::new();
+        versions
+            .set_schema_version_batch(&mut batch, "taple-request", 1)
+            .unwrap();
+        let event_request = Signed {
+            content: EventRequest::EOL(EOLRequest {
+                subject_id: DigestIdentifier::default(),
+            }),
+            signature: Signature {
+                signer: KeyIdentifier::new(KeyDerivator::Ed25519, &[0; 32]),
+                timestamp: TimeStamp(0),
+                content_hash: DigestIdentifier::default(),
+                value: SignatureIdentifier::new(SignatureDerivator::Ed25519Sha512, &[0; 64]),
+            },
+        };
+        let legacy = LegacyRequest {
+            id: DigestIdentifier::default(),
+            subject_id: None,
+            sn: Some(3),
+            event_request,
+            state: RequestState::Finished,
+            success: Some(true),
+        };
+        let key = format!("taple-request{}id", char::MAX);
+        batch.put(&key, serialize(&legacy).unwrap());
+        versions.write_batch(batch).unwrap();
+        run_migrations(&manager).unwrap();
+        assert_eq!(get_version(&manager, "taple-request"), Some(2));
+        let request =
+            deserialize::<TapleRequest>(&versions.collection().get(&key).unwrap()).unwrap();
+        assert_eq!(request.sn, Some(3));
+        assert_eq!(request.state, RequestState::Finished);
+        assert!(request.stages.is_empty());
+        assert_eq!(request.failure, None);
+    }
+
+    #[test]
+    fn subject_is_migrated() {
+        use crate::KeyDerivator;
+        use borsh::BorshSerialize;
+
+        #[derive(BorshSerialize)]
+        struct LegacySubject {
+            keys: Option<KeyPair>,
+            subject_id: DigestIdentifier,
+            governance_id: DigestIdentifier,
+            sn: u64,
+            genesis_gov_version: u64,
+            public_key: KeyIdentifier,
+            namespace: String,
+            name: String,
+            schema_id: String,
+            owner: KeyIdentifier,
+            creator: KeyIdentifier,
+            properties: ValueWrapper,
+            active: bool,
+        }
+
+        let manager = Arc::new(MemoryManager::new());
+        let versions = SchemaVersionDb::<MemoryCollection>::new(&manager);
+        let mut batch = WriteBatch::new();
+        versions
+            .set_schema_version_batch(&mut batch, "subject", 1)
+            .unwrap();
+        let key_identifier = KeyIdentifier::new(KeyDerivator::Ed25519, &[0; 32]);
+        let legacy = LegacySubject {
+            keys: None,
+            subject_id: DigestIdentifier::default(),
+            governance_id: DigestIdentifier::default(),
+            sn: 4,
+            genesis_gov_version: 1,
+            public_key: key_identifier.clone(),
+            namespace: "namespace".into(),
+            name: "name".into(),
+            schema_id: "schema".into(),
+            owner: key_identifier.clone(),
+            creator: key_identifier,
+            properties: ValueWrapper(serde_json::json!({"a": 1})),
+            active: true,
+        };
+        let key = format!("subject{}id", char::MAX);
+        batch.put(&key, serialize(&legacy).unwrap());
+        versions.write_batch(batch).unwrap();
+        run_migrations(&manager).unwrap();
+        assert_eq!(get_version(&manager, "subject"), Some(2));
+        let subject = deserialize::<Subject>(&versions.collection().get(&key).unwrap()).unwrap();
+        assert_eq!(subject.sn, 4);
+        assert_eq!(subject.schema_id, "schema");
+        assert_eq!(subject.schema_version, 0);
+        assert_eq!(
+            subject.properties,
+            ValueWrapper(serde_json::json!({"a": 1}))
+        );
+    }
+
+    #[test]
+    fn future_version_is_refused() {
+        let manager = Arc::new(MemoryManager::new());
+        let versions = SchemaVersionDb::<MemoryCollection>::new(&manager);
+        let mut batch = WriteBatch::new();
+        versions
+            .set_schema_version_batch(&mut batch, "event", u32::MAX)
+            .unwrap();
+        versions.write_batch(batch).unwrap();
+        let result = run_migrations(&manager);
+        assert_eq!(
+            result,
+            Err(Error::UnsupportedSchemaVersion {
+                collection: "event".into(),
+                version: u32::MAX,
+                supported: 1,
+            })
+        );
+    }
+}
@@ -1,20 +1,35 @@
+mod batch;
 mod db;
 mod error;
+#[cfg(feature = "file-database")]
+mod file;
 mod layers;
 mod memory;
+mod migration;
 
+#[cfg(feature = "file-database")]
+pub use self::file::{FileCollection, FileManager};
 pub use self::memory::{MemoryCollection, MemoryManager};
+pub use batch::{WriteBatch, WriteOperation};
 pub use db::DB;
 pub use error::Error;
+pub(crate) use migration::run_migrations;
 
 /// Trait to define a database compatible with Taple
+///
+/// All the collections created by a manager must share the same keyspace: the identifier of a
+/// collection does not namespace its keys, since every key written by TAPLE already starts with
+/// the prefix of the data it belongs to. As a consequence, a key written through one collection
+/// can be read through any other, and a [WriteBatch] written through any collection applies to
+/// the keys of all of them. TAPLE relies on this to commit at once changes that belong to
+/// several collections.
 pub trait DatabaseManager<C>: Sync + Send
 where
     C: DatabaseCollection,
 {
     /// Default constructor for the database manager. Is is mainly used for the battery test
     fn default() -> Self;
-    /// Creates a database collection
+    /// Creates a database collection over the keyspace shared by all the collections
     /// # Arguments
     /// - identifier: The identifier of the collection
     fn create_collection(&self, identifier: &str) -> C;
@@ -34,6 +49,21 @@ pub trait DatabaseCollection: Sync + Send {
         reverse: bool,
         prefix: String,
     ) -> Box<dyn Iterator<Item = (String, Vec<u8>)> + 'a>;
+    /// Applies all the operations of the batch in order. The keys of the batch may have been
+    /// written through any collection of the same [DatabaseManager].
+    ///
+    /// Implementations should apply the batch atomically, so either all of its operations are
+    /// persisted or none of them is. The default implementation is not atomic and simply applies
+    /// them one by one.
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), Error> {
+        for operation in batch {
+            match operation {
+                WriteOperation::Put(key, data) => self.put(&key, data)?,
+                WriteOperation::Del(key) => self.del(&key)?,
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Allows a TAPLE database implementation to be subjected to a battery of tests.
@@ -181,6 +211,28 @@ macro_rules! test_database_manager_trait {
                 assert!(iter.next().is_none());
             }
 
+            #[test]
+            fn write_batch_test() {
+                let db = <$type>::default();
+                let first_collection: $type2 = db.create_collection("first");
+                let second_collection: $type2 = db.create_collection("second");
+                build_state(&first_collection);
+                // Collections share the keyspace of their manager, so a batch written through
+                // one of them changes the keys written through the others
+                // (see DatabaseManager)
+                let data = get_data().unwrap();
+                let mut batch = $crate::WriteBatch::new();
+                batch.del("a");
+                batch.put("d", data[0].to_owned());
+                batch.put("b", data[2].to_owned());
+                let result = second_collection.write_batch(batch);
+                assert!(result.is_ok());
+                assert!(first_collection.get("a").is_err());
+                assert_eq!(first_collection.get("b").unwrap(), data[2]);
+                assert_eq!(first_collection.get("c").unwrap(), data[2]);
+                assert_eq!(first_collection.get("d").unwrap(), data[0]);
+            }
+
             #[test]
             fn rev_iterator_test() {
                 let db = <$type>::default();
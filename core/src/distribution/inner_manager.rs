@@ -12,13 +12,13 @@ use crate::signature::Signature;
 use crate::utils::message::distribution::{
     create_distribution_request, create_distribution_response,
 };
-use crate::utils::message::ledger::{request_gov_event, request_lce};
+use crate::utils::message::ledger::{lce_task_id, request_gov_event, request_lce};
 use crate::{
     database::{Error as DbError, DB},
     governance::GovernanceInterface,
-    DatabaseCollection,
+    DatabaseCollection, WriteBatch,
 };
-use crate::{Metadata, Settings, DigestDerivator};
+use crate::{Metadata, RetryPolicy, Settings, DigestDerivator};
 
 use super::error::{DistributionErrorResponses, DistributionManagerError};
 use super::StartDistribution;
@@ -27,8 +27,9 @@ pub struct InnerDistributionManager<G: GovernanceInterface, C: DatabaseCollectio
     db: DB<C>,
     messenger_channel: SenderEnd<MessageTaskCommand<TapleMessages>, ()>,
     signature_manager: SelfSignatureManager,
-    timeout: u32,
     replication_factor: f64,
+    retry: RetryPolicy,
+    lce_retry: RetryPolicy,
     derivator: DigestDerivator,
 }
 
@@ -46,8 +47,9 @@ impl<G: GovernanceInterface, C: DatabaseCollection> InnerDistributionManager<G,
             db,
             messenger_channel,
             signature_manager,
-            timeout: settings.node.timeout,
             replication_factor: settings.node.replication_factor,
+            retry: settings.node.retry.distribution,
+            lce_retry: settings.node.retry.lce,
             derivator
         }
     }
@@ -265,12 +267,18 @@ impl<G: GovernanceInterface, C: DatabaseCollection> InnerDistributionManager<G,
             .signature_manager
             .sign(&event, self.derivator)
             .map_err(|_| DistributionManagerError::SignGenerarionFailed)?;
-        // Delete the previous signatures before adding the new ones
+        // The previous signatures are replaced by the new one in a single write
+        let mut batch = WriteBatch::new();
         self.db
-            .del_witness_signatures(&msg.subject_id)
-            .map_err(|_| DistributionManagerError::SignGenerarionFailed)?;
+            .replace_witness_signatures_batch(
+                &mut batch,
+                &msg.subject_id,
+                msg.sn,
+                HashSet::from_iter(vec![signature]),
+            )
+            .map_err(|error| DistributionManagerError::DatabaseError(error.to_string()))?;
         self.db
-            .set_witness_signatures(&msg.subject_id, msg.sn, HashSet::from_iter(vec![signature]))
+            .write_batch(batch)
             .map_err(|error| DistributionManagerError::DatabaseError(error.to_string()))?;
         let subject = self
             .db
@@ -328,10 +336,7 @@ impl<G: GovernanceInterface, C: DatabaseCollection> InnerDistributionManager<G,
                 Some(format!("WITNESS/{}", subject_id_str)),
                 request,
                 Vec::from_iter(targets.into_iter()),
-                MessageConfig {
-                    timeout: self.timeout,
-                    replication_factor: self.replication_factor,
-                },
+                MessageConfig::with_retry(self.retry.clone(), self.replication_factor),
             ))
             .await
             .map_err(|_| DistributionManagerError::MessageChannelNotAvailable)?;
@@ -402,10 +407,10 @@ impl<G: GovernanceInterface, C: DatabaseCollection> InnerDistributionManager<G,
                     };
                     self.messenger_channel
                         .tell(MessageTaskCommand::Request(
-                            None,
+                            Some(lce_task_id(&msg.subject_id)),
                             request,
                             vec![msg.sender_id.clone()],
-                            MessageConfig::direct_response(),
+                            MessageConfig::with_retry(self.lce_retry.clone(), 1.0),
                         ))
                         .await
                         .map_err(|_| DistributionManagerError::MessageChannelNotAvailable)?;
@@ -419,10 +424,10 @@ impl<G: GovernanceInterface, C: DatabaseCollection> InnerDistributionManager<G,
                 );
                 self.messenger_channel
                     .tell(MessageTaskCommand::Request(
-                        None,
+                        Some(lce_task_id(&msg.subject_id)),
                         request,
                         vec![msg.sender_id.clone()],
-                        MessageConfig::direct_response(),
+                        MessageConfig::with_retry(self.lce_retry.clone(), 1.0),
                     ))
                     .await
                     .map_err(|_| DistributionManagerError::MessageChannelNotAvailable)?;
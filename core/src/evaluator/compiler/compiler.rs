@@ -1,55 +1,78 @@
+use crate::commons::schema_handler::gov_models::Contract;
 use crate::database::Error as DbError;
 use crate::evaluator::errors::CompilerError;
 use crate::governance::GovernanceInterface;
 use crate::identifier::{Derivable, DigestIdentifier};
 use crate::{database::DB, evaluator::errors::CompilerErrorResponses, DatabaseCollection};
 use async_std::fs;
-use log::{debug, info};
-use std::collections::HashSet;
-use std::fs::create_dir;
+use futures::future::join_all;
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs::create_dir_all;
 use std::path::Path;
 use std::process::Command;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
 use wasmtime::{Engine, ExternType};
 
 use super::manifest::get_toml;
 
+/// Maximum number of contracts built at the same time. Each build runs its own cargo process
+/// over a workspace with its own target directory.
+const MAX_CONCURRENT_BUILDS: usize = 2;
+
 pub struct Compiler<C: DatabaseCollection, G: GovernanceInterface> {
     database: DB<C>,
     gov_api: G,
-    engine: Engine,
-    contracts_path: String,
-    available_imports_set: HashSet<String>,
+    builder: Arc<ContractBuilder>,
 }
 
 impl<C: DatabaseCollection, G: GovernanceInterface> Compiler<C, G> {
-    pub fn new(database: DB<C>, gov_api: G, engine: Engine, contracts_path: String) -> Self {
-        let available_imports_set = get_sdk_functions_identifier();
+    /// The builder is shared with the checks of governance documents, so a contract is never
+    /// built by both at the same time and their builds are bounded together.
+    pub fn new(database: DB<C>, gov_api: G, builder: Arc<ContractBuilder>) -> Self {
         Self {
             database,
             gov_api,
-            engine,
-            contracts_path,
-            available_imports_set,
+            builder,
         }
     }
 
     pub async fn init(&self) -> Result<(), CompilerError> {
-        // Checks if the governance contract exists in the system
-        // If it does not exist, it compiles and saves it.
-        let cargo_path = format!("{}/Cargo.toml", self.contracts_path);
-        if !Path::new(&cargo_path).exists() {
-            let toml: String = get_toml();
-            // We write cargo.toml
-            fs::write(cargo_path, toml)
-                .await
-                .map_err(|_| CompilerErrorResponses::WriteFileError)?;
+        // Every contract is built in its own workspace. The resulting WASM modules are
+        // kept in a cache keyed by the hash of their source and manifest.
+        let workspaces_path = format!("{}/workspaces", self.builder.contracts_path);
+        if Path::new(&workspaces_path).exists() {
+            // Workspaces left by builds interrupted by a previous shutdown
+            std::fs::remove_dir_all(&workspaces_path).map_err(|e| {
+                CompilerErrorResponses::FolderNotCreated(workspaces_path.clone(), e.to_string())
+            })?;
         }
-        let src_path = format!("{}/src", self.contracts_path);
-        if !Path::new(&src_path).exists() {
-            create_dir(&src_path).map_err(|e| {
-                CompilerErrorResponses::FolderNotCreated(src_path.to_string(), e.to_string())
+        let cache_path = format!("{}/cache", self.builder.contracts_path);
+        if !Path::new(&cache_path).exists() {
+            create_dir_all(&cache_path).map_err(|e| {
+                CompilerErrorResponses::FolderNotCreated(cache_path.to_string(), e.to_string())
             })?;
         }
+        // Modules stored by a previous version of the node may not load with the current
+        // engine configuration, so the contracts of every governance are checked again
+        let governances = self
+            .database
+            .get_governances(None, isize::MAX)
+            .map_err(|error| CompilerError::DatabaseError(error.to_string()))?;
+        for governance in governances {
+            if let Err(error) = self
+                .update_contracts(governance.subject_id.clone(), governance.sn)
+                .await
+            {
+                warn!(
+                    "Contracts of governance {} could not be updated: {}",
+                    governance.subject_id.to_str(),
+                    error
+                );
+            }
+        }
         Ok(())
     }
 
@@ -58,37 +81,59 @@ impl<C: DatabaseCollection, G: GovernanceInterface> Compiler<C, G> {
         governance_id: DigestIdentifier,
         governance_version: u64,
     ) -> Result<(), CompilerErrorResponses> {
-        // TODO: Pick contract from database, check if hash changes and compile, if it doesn't change don't compile
         // Read the contract from database
         let contracts = self
             .gov_api
             .get_contracts(governance_id.clone(), governance_version)
             .await
             .map_err(CompilerErrorResponses::GovernanceError)?;
-        for (contract_info, schema_id) in contracts {
-            let contract_data = match self.database.get_contract(&governance_id, &schema_id) {
-                Ok((contract, hash, contract_gov_version)) => {
-                    Some((contract, hash, contract_gov_version))
-                }
-                Err(DbError::EntryNotFound) => {
-                    // Add in the response
-                    None
-                }
-                Err(error) => return Err(CompilerErrorResponses::DatabaseError(error.to_string())),
+        // Contracts that must be built from their source:
+        // (schema_id, schema_version, source hash, source)
+        let mut pending_builds = Vec::new();
+        for (contract_info, schema_id, schema_version) in contracts {
+            if contract_info.mode.is_some() {
+                // Built-in contracts are executed by the node itself
+                continue;
+            }
+            let contract_data =
+                match self
+                    .database
+                    .get_contract(&governance_id, &schema_id, schema_version)
+                {
+                    Ok((contract, hash, contract_gov_version)) => {
+                        Some((contract, hash, contract_gov_version))
+                    }
+                    Err(DbError::EntryNotFound) => {
+                        // Add in the response
+                        None
+                    }
+                    Err(error) => {
+                        return Err(CompilerErrorResponses::DatabaseError(error.to_string()))
+                    }
+                };
+            let new_contract_hash = if contract_info.wasm.is_some() {
+                // Precompiled contracts are identified by the hash of their WASM module
+                ContractBuilder::wasm_hash(&contract_info)?
+            } else {
+                ContractBuilder::source_hash(&contract_info.raw)?
             };
-            let new_contract_hash =
-                DigestIdentifier::generate_with_blake3(&contract_info.raw)
-                    .map_err(|_| CompilerErrorResponses::BorshSerializeContractError)?;
             if let Some(contract_data) = contract_data {
-                if governance_version == contract_data.2 {
+                // Modules precompiled with another engine configuration are compiled again,
+                // even if they are up to date with the governance
+                let loadable = unsafe {
+                    wasmtime::Module::deserialize(&self.builder.engine, &contract_data.0)
+                }
+                .is_ok();
+                if loadable && governance_version == contract_data.2 {
                     continue;
                 }
-                if contract_data.1 == new_contract_hash {
+                if loadable && contract_data.1 == new_contract_hash {
                     // The associated governance version is updated.
                     self.database
                         .put_contract(
                             &governance_id,
                             &schema_id,
+                            schema_version,
                             contract_data.0,
                             new_contract_hash,
                             governance_version,
@@ -99,83 +144,295 @@ impl<C: DatabaseCollection, G: GovernanceInterface> Compiler<C, G> {
                     continue;
                 }
             }
-            self.compile(
-                contract_info.raw,
-                &governance_id.to_str(),
-                &schema_id,
-                governance_version,
-            )
-            .await?;
-            let compiled_contract = self.add_contract().await?;
+            let Some(wasm) = contract_info.wasm else {
+                pending_builds.push((
+                    schema_id,
+                    schema_version,
+                    new_contract_hash,
+                    contract_info.raw,
+                ));
+                continue;
+            };
+            // Precompiled contracts only need the import check and the AOT compilation
+            let file = ContractBuilder::decode_wasm(&wasm, &new_contract_hash)?;
+            info!(
+                "Loading precompiled contract: {} {} {} {}",
+                schema_id,
+                schema_version,
+                governance_id.to_str(),
+                governance_version
+            );
+            let compiled_contract = self.builder.precompile(&file)?;
             self.database
                 .put_contract(
                     &governance_id,
                     &schema_id,
+                    schema_version,
                     compiled_contract,
                     new_contract_hash,
                     governance_version,
                 )
                 .map_err(|error| CompilerErrorResponses::DatabaseError(error.to_string()))?;
         }
+        // Sources not found in the cache are built concurrently, once per hash
+        let mut builds = HashMap::new();
+        for (schema_id, schema_version, contract_hash, raw) in pending_builds.iter() {
+            if !Path::new(&self.builder.cache_path(contract_hash)).exists() {
+                builds.entry(contract_hash.clone()).or_insert((
+                    schema_id.as_str(),
+                    *schema_version,
+                    raw.clone(),
+                ));
+            }
+        }
+        let results = join_all(builds.into_iter().map(
+            |(contract_hash, (schema_id, schema_version, raw))| {
+                let governance_id = &governance_id;
+                async move {
+                    info!(
+                        "Compiling contract: {} {} {} {}",
+                        schema_id,
+                        schema_version,
+                        governance_id.to_str(),
+                        contract_hash.to_str()
+                    );
+                    self.builder.build(raw, &contract_hash).await?;
+                    info!(
+                        "Compiled success contract: {} {} {} {}",
+                        schema_id,
+                        schema_version,
+                        governance_id.to_str(),
+                        contract_hash.to_str()
+                    );
+                    Ok::<(), CompilerErrorResponses>(())
+                }
+            },
+        ))
+        .await;
+        results.into_iter().collect::<Result<Vec<()>, _>>()?;
+        for (schema_id, schema_version, contract_hash, _) in pending_builds {
+            let compiled_contract = self.builder.add_contract(&contract_hash).await?;
+            self.database
+                .put_contract(
+                    &governance_id,
+                    &schema_id,
+                    schema_version,
+                    compiled_contract,
+                    contract_hash,
+                    governance_version,
+                )
+                .map_err(|error| CompilerErrorResponses::DatabaseError(error.to_string()))?;
+        }
         Ok(())
     }
+}
+
+/// Builds the contracts of the governances and checks that the resulting WASM modules can be
+/// executed by the node.
+pub struct ContractBuilder {
+    engine: Engine,
+    contracts_path: String,
+    available_imports_set: HashSet<String>,
+    required_imports_set: HashSet<String>,
+    /// Bounds the number of cargo processes running at the same time
+    build_permits: Semaphore,
+    /// Locks of the contracts being built, so each one is only built once at a time
+    builds_in_progress: Mutex<HashMap<DigestIdentifier, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ContractBuilder {
+    pub fn new(engine: Engine, contracts_path: String) -> Self {
+        let required_imports_set = get_sdk_functions_identifier();
+        let mut available_imports_set = required_imports_set.clone();
+        available_imports_set.extend(get_optional_sdk_functions_identifier());
+        Self {
+            engine,
+            contracts_path,
+            available_imports_set,
+            required_imports_set,
+            build_permits: Semaphore::new(MAX_CONCURRENT_BUILDS),
+            builds_in_progress: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hash that identifies the module built from a contract source. The manifest is part of
+    /// it, since the same source built with other dependencies gives a different module.
+    pub fn source_hash(raw: &str) -> Result<DigestIdentifier, CompilerErrorResponses> {
+        DigestIdentifier::generate_with_blake3((get_toml(), raw.to_owned()))
+            .map_err(|_| CompilerErrorResponses::BorshSerializeContractError)
+    }
+
+    /// Hash that identifies the module of a precompiled contract, given by its `wasm_hash`.
+    fn wasm_hash(contract: &Contract) -> Result<DigestIdentifier, CompilerErrorResponses> {
+        let Some(wasm_hash) = &contract.wasm_hash else {
+            return Err(CompilerErrorResponses::InvalidContractHash);
+        };
+        DigestIdentifier::from_str(wasm_hash)
+            .map_err(|_| CompilerErrorResponses::InvalidContractHash)
+    }
+
+    /// Decodes the module of a precompiled contract and checks it against its hash.
+    fn decode_wasm(
+        wasm: &str,
+        wasm_hash: &DigestIdentifier,
+    ) -> Result<Vec<u8>, CompilerErrorResponses> {
+        let file = base64::decode(wasm).map_err(|_| CompilerErrorResponses::WasmDecodingError)?;
+        if wasm_hash.derivator.digest(&file) != wasm_hash.digest {
+            return Err(CompilerErrorResponses::ContractHashMismatch);
+        }
+        Ok(file)
+    }
+
+    /// Builds a contract source and stores its module in the cache, unless it is already there.
+    /// The workspace of the build, including its target directory, is removed afterwards.
+    async fn build(
+        &self,
+        raw: String,
+        contract_hash: &DigestIdentifier,
+    ) -> Result<(), CompilerErrorResponses> {
+        let lock = self
+            .builds_in_progress
+            .lock()
+            .unwrap()
+            .entry(contract_hash.clone())
+            .or_default()
+            .clone();
+        let result = {
+            let _guard = lock.lock().await;
+            // Another task may have built the contract while waiting for the lock
+            if Path::new(&self.cache_path(contract_hash)).exists() {
+                Ok(())
+            } else {
+                let _permit = self
+                    .build_permits
+                    .acquire()
+                    .await
+                    .expect("The build permits are never closed");
+                let workspace_path = format!(
+                    "{}/workspaces/{}",
+                    self.contracts_path,
+                    contract_hash.to_str()
+                );
+                let result = self.compile(raw, &workspace_path, contract_hash).await;
+                if let Err(error) = fs::remove_dir_all(&workspace_path).await {
+                    warn!(
+                        "Workspace {} could not be removed: {}",
+                        workspace_path, error
+                    );
+                }
+                result
+            }
+        };
+        let mut builds_in_progress = self.builds_in_progress.lock().unwrap();
+        // Only this task and the map hold the lock if no other task is waiting for it
+        if Arc::strong_count(&lock) == 2 {
+            builds_in_progress.remove(contract_hash);
+        }
+        result
+    }
+
+    /// Checks that a contract of a governance document can be built and executed, as the
+    /// compiler does when the governance is updated, without storing it. The sources are
+    /// built in their own workspace and their modules are kept in the cache.
+    pub async fn check_contract(&self, contract: Contract) -> Result<(), CompilerErrorResponses> {
+        if contract.mode.is_some() {
+            // Built-in contracts are executed by the node itself
+            return Ok(());
+        }
+        if let Some(wasm) = &contract.wasm {
+            let file = Self::decode_wasm(wasm, &Self::wasm_hash(&contract)?)?;
+            return self.precompile(&file).map(|_| ());
+        }
+        let raw = base64::decode(contract.raw)
+            .ok()
+            .and_then(|raw| String::from_utf8(raw).ok())
+            .ok_or(CompilerErrorResponses::SourceDecodingError)?;
+        let contract_hash = Self::source_hash(&raw)?;
+        self.build(raw, &contract_hash).await?;
+        self.add_contract(&contract_hash).await.map(|_| ())
+    }
 
     async fn compile(
         &self,
         contract: String,
-        governance_id: &str,
-        schema_id: &str,
-        sn: u64,
+        workspace_path: &str,
+        contract_hash: &DigestIdentifier,
     ) -> Result<(), CompilerErrorResponses> {
-        fs::write(format!("{}/src/lib.rs", self.contracts_path), contract)
+        let src_path = format!("{}/src", workspace_path);
+        fs::create_dir_all(&src_path).await.map_err(|e| {
+            CompilerErrorResponses::FolderNotCreated(src_path.to_string(), e.to_string())
+        })?;
+        fs::write(format!("{}/Cargo.toml", workspace_path), get_toml())
             .await
             .map_err(|_| CompilerErrorResponses::WriteFileError)?;
-        info!("Compiling contract: {} {} {}", schema_id, governance_id, sn);
-        let status = Command::new("cargo")
-            .arg("build")
-            .arg(format!(
-                "--manifest-path={}/Cargo.toml",
-                self.contracts_path
-            ))
-            .arg("--target")
-            .arg("wasm32-unknown-unknown")
-            .arg("--release")
-            .output()
-            // Does not show stdout. Generates child process and waits
-            .map_err(|_| CompilerErrorResponses::CargoExecError)?;
-        info!(
-            "Compiled success contract: {} {} {}",
-            schema_id, governance_id, sn
-        );
+        fs::write(format!("{}/lib.rs", src_path), contract)
+            .await
+            .map_err(|_| CompilerErrorResponses::WriteFileError)?;
+        let manifest_path = format!("--manifest-path={}/Cargo.toml", workspace_path);
+        let status = tokio::task::spawn_blocking(move || {
+            Command::new("cargo")
+                .arg("build")
+                .arg(manifest_path)
+                .arg("--target")
+                .arg("wasm32-unknown-unknown")
+                .arg("--release")
+                .output()
+        })
+        .await
+        .map_err(|_| CompilerErrorResponses::CargoExecError)?
+        // Does not show stdout. Generates child process and waits
+        .map_err(|_| CompilerErrorResponses::CargoExecError)?;
         debug!("status {:?}", status);
         if !status.status.success() {
-            return Err(CompilerErrorResponses::CargoExecError);
+            return Err(CompilerErrorResponses::ContractBuildFailed(
+                String::from_utf8_lossy(&status.stderr).into_owned(),
+            ));
         }
-
-        std::fs::create_dir_all(format!(
-            "/tmp/taple_contracts/{}/{}",
-            governance_id, schema_id
-        ))
-        .map_err(|_| CompilerErrorResponses::TempFolderCreationFailed)?;
-
+        // The module only enters the cache once it is completely written
+        let cache_path = self.cache_path(contract_hash);
+        let temp_path = format!("{}.tmp", cache_path);
+        fs::copy(
+            format!(
+                "{}/target/wasm32-unknown-unknown/release/contract.wasm",
+                workspace_path
+            ),
+            &temp_path,
+        )
+        .await
+        .map_err(|_| CompilerErrorResponses::WriteFileError)?;
+        fs::rename(&temp_path, &cache_path)
+            .await
+            .map_err(|_| CompilerErrorResponses::WriteFileError)?;
         Ok(())
     }
 
-    async fn add_contract(&self) -> Result<Vec<u8>, CompilerErrorResponses> {
+    fn cache_path(&self, contract_hash: &DigestIdentifier) -> String {
+        format!(
+            "{}/cache/{}.wasm",
+            self.contracts_path,
+            contract_hash.to_str()
+        )
+    }
+
+    async fn add_contract(
+        &self,
+        contract_hash: &DigestIdentifier,
+    ) -> Result<Vec<u8>, CompilerErrorResponses> {
         // AOT COMPILATION
-        let file = fs::read(format!(
-            "{}/target/wasm32-unknown-unknown/release/contract.wasm",
-            self.contracts_path
-        ))
-        .await
-        .map_err(|_| CompilerErrorResponses::AddContractFail)?;
+        let file = fs::read(self.cache_path(contract_hash))
+            .await
+            .map_err(|_| CompilerErrorResponses::AddContractFail)?;
+        self.precompile(&file)
+    }
+
+    fn precompile(&self, file: &[u8]) -> Result<Vec<u8>, CompilerErrorResponses> {
         let module_bytes = self
             .engine
-            .precompile_module(&file)
+            .precompile_module(file)
             .map_err(|_| CompilerErrorResponses::AddContractFail)?;
         let module = unsafe { wasmtime::Module::deserialize(&self.engine, &module_bytes).unwrap() };
         let imports = module.imports();
-        let mut pending_sdk = self.available_imports_set.clone();
+        let mut pending_sdk = self.required_imports_set.clone();
         for import in imports {
             match import.ty() {
                 ExternType::Func(_) => {
@@ -201,8 +458,142 @@ fn get_sdk_functions_identifier() -> HashSet<String> {
             "write_byte".to_owned(),
             "pointer_len".to_owned(),
             "read_byte".to_owned(),
-            // "cout".to_owned(),
         ]
         .into_iter(),
     )
 }
+
+// Functions that contracts may import, but that older SDK versions do not provide
+fn get_optional_sdk_functions_identifier() -> HashSet<String> {
+    HashSet::from_iter(
+        vec![
+            "get_context".to_owned(),
+            "log".to_owned(),
+            "get_subject_state".to_owned(),
+        ]
+        .into_iter(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use serde_json::json;
+    use wasmtime::Engine;
+
+    use super::ContractBuilder;
+    use crate::{
+        commons::schema_handler::gov_models::Contract,
+        evaluator::errors::CompilerErrorResponses,
+        identifier::{Derivable, DigestIdentifier},
+        DigestDerivator,
+    };
+
+    fn build_builder() -> ContractBuilder {
+        let path =
+            std::env::temp_dir().join(format!("taple-contracts-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(path.join("cache")).unwrap();
+        ContractBuilder::new(Engine::default(), path.to_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn source_hash_covers_the_manifest() {
+        let source = "pub fn main() {}";
+        let hash = ContractBuilder::source_hash(source).unwrap();
+        assert_eq!(hash, ContractBuilder::source_hash(source).unwrap());
+        assert_ne!(
+            hash,
+            ContractBuilder::source_hash("pub fn other() {}").unwrap()
+        );
+        assert_ne!(
+            hash,
+            DigestIdentifier::generate_with_blake3(source.to_owned()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_modules_are_not_built_again() {
+        let builder = build_builder();
+        let contract_hash = ContractBuilder::source_hash("not a contract").unwrap();
+        std::fs::write(builder.cache_path(&contract_hash), [0u8]).unwrap();
+        builder
+            .build("not a contract".to_owned(), &contract_hash)
+            .await
+            .unwrap();
+        assert!(!Path::new(&format!("{}/workspaces", builder.contracts_path)).exists());
+        assert!(builder.builds_in_progress.lock().unwrap().is_empty());
+        std::fs::remove_dir_all(&builder.contracts_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_builds_remove_their_workspace() {
+        let builder = build_builder();
+        let contract_hash = ContractBuilder::source_hash("not a contract").unwrap();
+        assert!(builder
+            .build("not a contract".to_owned(), &contract_hash)
+            .await
+            .is_err());
+        let workspace_path = format!(
+            "{}/workspaces/{}",
+            builder.contracts_path,
+            contract_hash.to_str()
+        );
+        assert!(!Path::new(&workspace_path).exists());
+        assert!(!Path::new(&builder.cache_path(&contract_hash)).exists());
+        assert!(builder.builds_in_progress.lock().unwrap().is_empty());
+        std::fs::remove_dir_all(&builder.contracts_path).unwrap();
+    }
+
+    /// Precompiled contract whose `wasm` is the given module.
+    fn precompiled_contract(module: &[u8], wasm_hash: Option<&DigestIdentifier>) -> Contract {
+        serde_json::from_value(json!({
+            "wasm": base64::encode(module),
+            "wasm_hash": wasm_hash.map(|wasm_hash| wasm_hash.to_str()),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn precompiled_contracts_are_checked_against_their_hash() {
+        let builder = build_builder();
+        // The smallest valid module, which lacks the imports of the SDK
+        let module = b"\0asm\x01\0\0\0";
+        let wasm_hash = DigestIdentifier::new(
+            DigestDerivator::Blake3_256,
+            &DigestDerivator::Blake3_256.digest(module),
+        );
+        let contract = precompiled_contract(module, Some(&wasm_hash));
+        assert!(matches!(
+            builder.check_contract(contract).await,
+            Err(CompilerErrorResponses::NoSDKFound)
+        ));
+        let other_hash = DigestIdentifier::new(
+            DigestDerivator::Blake3_256,
+            &DigestDerivator::Blake3_256.digest(b"other module"),
+        );
+        let contract = precompiled_contract(module, Some(&other_hash));
+        assert!(matches!(
+            builder.check_contract(contract).await,
+            Err(CompilerErrorResponses::ContractHashMismatch)
+        ));
+        let contract = precompiled_contract(module, None);
+        assert!(matches!(
+            builder.check_contract(contract).await,
+            Err(CompilerErrorResponses::InvalidContractHash)
+        ));
+        let mut contract = precompiled_contract(module, Some(&wasm_hash));
+        contract.wasm_hash = Some("not a digest".to_owned());
+        assert!(matches!(
+            builder.check_contract(contract).await,
+            Err(CompilerErrorResponses::InvalidContractHash)
+        ));
+        let mut contract = precompiled_contract(module, Some(&wasm_hash));
+        contract.wasm = Some("not base64!".to_owned());
+        assert!(matches!(
+            builder.check_contract(contract).await,
+            Err(CompilerErrorResponses::WasmDecodingError)
+        ));
+        std::fs::remove_dir_all(&builder.contracts_path).unwrap();
+    }
+}
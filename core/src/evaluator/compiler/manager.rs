@@ -1,5 +1,6 @@
+use std::sync::Arc;
+
 use tokio_util::sync::CancellationToken;
-use wasmtime::Engine;
 
 use crate::{
     database::DB,
@@ -8,7 +9,7 @@ use crate::{
     DatabaseCollection, Notification,
 };
 
-use super::compiler::Compiler;
+use super::compiler::{Compiler, ContractBuilder};
 
 pub struct TapleCompiler<C: DatabaseCollection, G: GovernanceInterface> {
     input_channel: tokio::sync::broadcast::Receiver<GovernanceUpdatedMessage>,
@@ -22,14 +23,13 @@ impl<C: DatabaseCollection, G: GovernanceInterface + Send> TapleCompiler<C, G> {
         input_channel: tokio::sync::broadcast::Receiver<GovernanceUpdatedMessage>,
         database: DB<C>,
         gov_api: G,
-        contracts_path: String,
-        engine: Engine,
+        contract_builder: Arc<ContractBuilder>,
         token: CancellationToken,
         notification_tx: tokio::sync::mpsc::Sender<Notification>,
     ) -> Self {
         Self {
             input_channel,
-            inner_compiler: Compiler::<C, G>::new(database, gov_api, engine, contracts_path),
+            inner_compiler: Compiler::<C, G>::new(database, gov_api, contract_builder),
             token,
             notification_tx,
         }
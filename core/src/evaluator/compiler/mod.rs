@@ -1,3 +1,4 @@
 mod compiler;
+pub(crate) use compiler::ContractBuilder;
 pub(crate) mod manager;
 mod manifest;
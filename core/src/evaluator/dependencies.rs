@@ -0,0 +1,329 @@
+#[cfg(feature = "evaluation")]
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+#[cfg(feature = "evaluation")]
+use crate::DigestDerivator;
+use crate::{
+    commons::models::evaluation::{ContractSubjectState, SubjectDependency},
+    database::{Error as DbError, DB},
+    evaluator::errors::ExecutorErrorResponses,
+    identifier::DigestIdentifier,
+    utils::patch::apply_patch,
+    DatabaseCollection, Derivable, EventRequest, ValueWrapper,
+};
+
+/// Source of the states of other subjects that a contract can read.
+#[cfg(feature = "evaluation")]
+pub trait SubjectStateReader: Send {
+    fn read(
+        &self,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+    ) -> Result<(ContractSubjectState, DigestIdentifier), ExecutorErrorResponses>;
+}
+
+/// Reads the subjects of a governance from the database of the node.
+#[cfg(feature = "evaluation")]
+pub struct DatabaseStateReader<C: DatabaseCollection> {
+    database: Arc<DB<C>>,
+    governance_id: DigestIdentifier,
+    derivator: DigestDerivator,
+}
+
+#[cfg(feature = "evaluation")]
+impl<C: DatabaseCollection> DatabaseStateReader<C> {
+    pub fn new(
+        database: Arc<DB<C>>,
+        governance_id: DigestIdentifier,
+        derivator: DigestDerivator,
+    ) -> Self {
+        Self {
+            database,
+            governance_id,
+            derivator,
+        }
+    }
+}
+
+#[cfg(feature = "evaluation")]
+impl<C: DatabaseCollection + 'static> SubjectStateReader for DatabaseStateReader<C> {
+    fn read(
+        &self,
+        subject_id: &DigestIdentifier,
+        sn: u64,
+    ) -> Result<(ContractSubjectState, DigestIdentifier), ExecutorErrorResponses> {
+        let state = read_subject_state(&self.database, &self.governance_id, subject_id, sn)?;
+        let state_hash =
+            DigestIdentifier::from_serializable_borsh(&state.properties, self.derivator)
+                .map_err(|_| ExecutorErrorResponses::StateHashGenerationFailed)?;
+        Ok((state, state_hash))
+    }
+}
+
+/// Checks the states of other subjects read by the contract of an event against the ones
+/// stored by the node. The states of subjects that the node does not have, or has not
+/// received up to the sn read, can not be checked and are accepted.
+pub(crate) fn check_dependencies<C: DatabaseCollection>(
+    database: &DB<C>,
+    governance_id: &DigestIdentifier,
+    dependencies: &[SubjectDependency],
+) -> Result<(), ExecutorErrorResponses> {
+    for dependency in dependencies {
+        let state = match read_subject_state(
+            database,
+            governance_id,
+            &dependency.subject_id,
+            dependency.sn,
+        ) {
+            Ok(state) => state,
+            Err(ExecutorErrorResponses::SubjectStateNotAvailable(..)) => continue,
+            Err(error) => return Err(error),
+        };
+        let state_hash = DigestIdentifier::from_serializable_borsh(
+            &state.properties,
+            dependency.state_hash.derivator,
+        )
+        .map_err(|_| ExecutorErrorResponses::StateHashGenerationFailed)?;
+        if state_hash != dependency.state_hash {
+            return Err(ExecutorErrorResponses::InvalidSubjectDependency(
+                dependency.subject_id.to_str(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Rebuilds the state of a subject of a governance at a sn, by event sourcing if the subject
+/// is already past it.
+fn read_subject_state<C: DatabaseCollection>(
+    database: &DB<C>,
+    governance_id: &DigestIdentifier,
+    subject_id: &DigestIdentifier,
+    sn: u64,
+) -> Result<ContractSubjectState, ExecutorErrorResponses> {
+    let subject = match database.get_subject(subject_id) {
+        Ok(subject) => subject,
+        Err(DbError::EntryNotFound) => {
+            return Err(ExecutorErrorResponses::SubjectStateNotAvailable(
+                subject_id.to_str(),
+                sn,
+            ))
+        }
+        Err(error) => return Err(ExecutorErrorResponses::DatabaseError(error.to_string())),
+    };
+    if &subject.governance_id != governance_id {
+        return Err(ExecutorErrorResponses::InvalidSubjectDependency(
+            subject_id.to_str(),
+        ));
+    }
+    if subject.sn < sn {
+        return Err(ExecutorErrorResponses::SubjectStateNotAvailable(
+            subject_id.to_str(),
+            sn,
+        ));
+    }
+    if subject.sn == sn {
+        return Ok(ContractSubjectState {
+            properties: subject.properties,
+            active: subject.active,
+        });
+    }
+    // Event sourcing from the genesis event up to the requested sn
+    let (_, genesis_patch, _) = get_event_request(database, subject_id, 0)?;
+    let mut properties: Value = apply_patch(genesis_patch.0, json!({}))
+        .map_err(|_| ExecutorErrorResponses::JSONPATCHDeserializationFailed)?;
+    let mut active = true;
+    for i in 1..=sn {
+        let (event_request, patch, approved) = get_event_request(database, subject_id, i)?;
+        match event_request {
+            EventRequest::Create(_) => {}
+            // Transfers and EOLs only carry a patch if the contract evaluates them
+            event_request if approved => {
+                properties = apply_patch(patch.0, properties)
+                    .map_err(|_| ExecutorErrorResponses::JSONPATCHDeserializationFailed)?;
+                if let EventRequest::EOL(_) = event_request {
+                    active = false;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(ContractSubjectState {
+        properties: ValueWrapper(properties),
+        active,
+    })
+}
+
+fn get_event_request<C: DatabaseCollection>(
+    database: &DB<C>,
+    subject_id: &DigestIdentifier,
+    sn: u64,
+) -> Result<(EventRequest, ValueWrapper, bool), ExecutorErrorResponses> {
+    match database.get_event(subject_id, sn) {
+        Ok(event) => Ok((
+            event.content.event_request.content,
+            event.content.patch,
+            event.content.approved,
+        )),
+        Err(DbError::EntryNotFound) => Err(ExecutorErrorResponses::SubjectStateNotAvailable(
+            subject_id.to_str(),
+            sn,
+        )),
+        Err(error) => Err(ExecutorErrorResponses::DatabaseError(error.to_string())),
+    }
+}
+
+#[cfg(all(test, feature = "evaluation"))]
+mod test {
+    use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+    use json_patch::diff;
+    use serde_json::{json, Value};
+
+    use super::{check_dependencies, DatabaseStateReader, SubjectStateReader};
+    use crate::commons::crypto::{Ed25519KeyPair, KeyGenerator, KeyMaterial, KeyPair};
+    use crate::commons::models::{evaluation::SubjectDependency, state::Subject};
+    use crate::evaluator::errors::ExecutorErrorResponses;
+    use crate::request::{EventRequest, FactRequest};
+    use crate::signature::{Signature, Signed};
+    use crate::{
+        DigestDerivator, DigestIdentifier, Event, KeyIdentifier, MemoryManager, ValueWrapper, DB,
+    };
+
+    const GOVERNANCE_ID: &str = "JGSPR6FL-vE7iZxWMd17o09qn7NeTqlcImDVWmijXczw";
+    const SUBJECT_ID: &str = "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs";
+
+    fn state_hash(state: &Value) -> DigestIdentifier {
+        DigestIdentifier::from_serializable_borsh(
+            &ValueWrapper(state.clone()),
+            DigestDerivator::Blake3_256,
+        )
+        .unwrap()
+    }
+
+    fn event(keys: &KeyPair, sn: u64, patch: Value) -> Signed<Event> {
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let request = EventRequest::Fact(FactRequest {
+            subject_id: subject_id.clone(),
+            payload: ValueWrapper(json!({})),
+        });
+        let event_request = Signed::<EventRequest> {
+            signature: Signature::new(&request, keys, DigestDerivator::Blake3_256).unwrap(),
+            content: request,
+        };
+        let event = Event {
+            subject_id,
+            event_request,
+            sn,
+            gov_version: 0,
+            patch: ValueWrapper(patch),
+            state_hash: DigestIdentifier::default(),
+            eval_success: true,
+            appr_required: false,
+            approved: true,
+            hash_prev_event: DigestIdentifier::default(),
+            evaluators: HashSet::new(),
+            approvers: HashSet::new(),
+            dependencies: vec![],
+        };
+        Signed::<Event> {
+            signature: Signature::new(&event, keys, DigestDerivator::Blake3_256).unwrap(),
+            content: event,
+        }
+    }
+
+    /// Stores a subject of the governance that went through the given states.
+    fn build_database(states: &[Value]) -> DB<crate::database::MemoryCollection> {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[1]));
+        let public_key = KeyIdentifier::new(keys.get_key_derivator(), &keys.public_key_bytes());
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let database = DB::new(Arc::new(MemoryManager::new()));
+        let mut previous = json!({});
+        for (sn, state) in states.iter().enumerate() {
+            let patch = serde_json::to_value(diff(&previous, state)).unwrap();
+            database
+                .set_event(&subject_id, event(&keys, sn as u64, patch))
+                .unwrap();
+            previous = state.clone();
+        }
+        database
+            .set_subject(
+                &subject_id,
+                Subject {
+                    keys: None,
+                    subject_id: subject_id.clone(),
+                    governance_id: DigestIdentifier::from_str(GOVERNANCE_ID).unwrap(),
+                    sn: states.len() as u64 - 1,
+                    genesis_gov_version: 0,
+                    public_key: public_key.clone(),
+                    namespace: "".into(),
+                    name: "".into(),
+                    schema_id: "Test".into(),
+                    schema_version: 0,
+                    owner: public_key.clone(),
+                    creator: public_key,
+                    properties: ValueWrapper(previous),
+                    active: true,
+                },
+            )
+            .unwrap();
+        database
+    }
+
+    #[test]
+    fn read_replays_the_events_up_to_the_sn() {
+        let states = [json!({"a": 1}), json!({"a": 2}), json!({"a": 3})];
+        let reader = DatabaseStateReader::new(
+            Arc::new(build_database(&states)),
+            DigestIdentifier::from_str(GOVERNANCE_ID).unwrap(),
+            DigestDerivator::Blake3_256,
+        );
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        for (sn, state) in states.iter().enumerate() {
+            let (read, hash) = reader.read(&subject_id, sn as u64).unwrap();
+            assert_eq!(read.properties.0, *state);
+            assert!(read.active);
+            assert_eq!(hash, state_hash(state));
+        }
+        assert!(matches!(
+            reader.read(&subject_id, 3),
+            Err(ExecutorErrorResponses::SubjectStateNotAvailable(_, 3))
+        ));
+    }
+
+    #[test]
+    fn read_rejects_subjects_of_other_governances() {
+        let reader = DatabaseStateReader::new(
+            Arc::new(build_database(&[json!({"a": 1})])),
+            DigestIdentifier::from_str(SUBJECT_ID).unwrap(),
+            DigestDerivator::Blake3_256,
+        );
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        assert!(matches!(
+            reader.read(&subject_id, 0),
+            Err(ExecutorErrorResponses::InvalidSubjectDependency(_))
+        ));
+    }
+
+    #[test]
+    fn dependencies_are_checked_against_stored_states() {
+        let states = [json!({"a": 1}), json!({"a": 2})];
+        let database = build_database(&states);
+        let governance_id = DigestIdentifier::from_str(GOVERNANCE_ID).unwrap();
+        let check = |sn: u64, state: &Value| {
+            let dependency = SubjectDependency {
+                subject_id: DigestIdentifier::from_str(SUBJECT_ID).unwrap(),
+                sn,
+                state_hash: state_hash(state),
+            };
+            check_dependencies(&database, &governance_id, &[dependency])
+        };
+        assert!(check(0, &states[0]).is_ok());
+        assert!(check(1, &states[1]).is_ok());
+        assert!(check(0, &states[1]).is_err());
+        // States not received yet can not be checked
+        assert!(check(5, &states[1]).is_ok());
+    }
+}
@@ -11,6 +11,8 @@ pub enum EvaluatorError {
     ChannelNotAvailable,
     #[error("Create Request not allowed")]
     CreateRequestNotAllowed,
+    #[error("Contract of schema {0} does not evaluate {1} requests")]
+    ContractHookNotDeclared(String, String),
     #[error("JSON Deserialization failed")]
     JSONDeserializationFailed,
     #[error("Signature generation failed")]
@@ -22,8 +24,12 @@ pub enum EvaluatorError {
 pub enum EvaluatorErrorResponses {
     #[error("Create Request not allowed")]
     CreateRequestNotAllowed,
+    #[error("Contract of schema {0} does not evaluate {1} requests")]
+    ContractHookNotDeclared(String, String),
     #[error("Contract execution error: \"{0}\"")]
     ContractExecutionError(ExecutorErrorResponses),
+    #[error("Evaluator channel not available")]
+    APIChannelNotAvailable,
 }
 
 #[derive(Error, Debug, Clone)]
@@ -33,6 +39,8 @@ pub enum ExecutorErrorResponses {
     SubjectError(#[from] SubjectError),
     #[error("A database error has ocurred at main component {0}")]
     DatabaseError(String),
+    #[error("Subject {0} not found")]
+    SubjectNotFound(String),
     #[error("Contract for schema {0} of governance {1} not found")]
     ContractNotFound(String, String),
     #[error("The contract could not be instantiated")]
@@ -41,6 +49,12 @@ pub enum ExecutorErrorResponses {
     ContractEntryPointNotFound,
     #[error("Contract execution failed")]
     ContractExecutionFailed,
+    #[error("Contract exceeded its {0} limit")]
+    ContractLimitExceeded(String),
+    #[error("State of subject {0} at sn {1} not available")]
+    SubjectStateNotAvailable(String, u64),
+    #[error("Subject {0} can not be read by the contract")]
+    InvalidSubjectDependency(String),
     #[error("Function \"{0}\" could not be linked")]
     FunctionLinkingFailed(String),
     #[error("Deserialization of state failed")]
@@ -63,6 +77,8 @@ pub enum ExecutorErrorResponses {
     OurGovIsHigher,
     #[error("Create Request not allowed")]
     CreateRequestNotAllowed,
+    #[error("Contract of schema {0} does not evaluate {1} requests")]
+    ContractHookNotDeclared(String, String),
     #[error("Governance module error {0}")]
     GovernanceError(#[from] RequestError),
     #[error("Schema compilation failed")]
@@ -77,6 +93,8 @@ pub enum ExecutorErrorResponses {
     ContractNotUpdated,
     #[error("GovernanceNotFound")]
     GovernanceNotFound,
+    #[error("Subject {0} is not a governance")]
+    NotGovernance(String),
 }
 
 #[derive(Error, Debug, Clone)]
@@ -115,6 +133,16 @@ pub enum CompilerErrorResponses {
     InvalidImportFound,
     #[error("No SDK found")]
     NoSDKFound,
+    #[error("Precompiled contract has no valid hash")]
+    InvalidContractHash,
+    #[error("Precompiled contract could not be decoded")]
+    WasmDecodingError,
+    #[error("Precompiled contract does not match its hash")]
+    ContractHashMismatch,
+    #[error("Contract source could not be decoded")]
+    SourceDecodingError,
+    #[error("Contract build failed: {0}")]
+    ContractBuildFailed(String),
 }
 
 #[derive(Error, Debug)]
@@ -129,6 +157,8 @@ pub enum GovernanceStateError {
     NoGvernancePolicy,
     #[error("It is not allowed to specify a different schema for the governnace")]
     GovernanceShchemaIDDetected,
+    #[error("A version of a schema is duplicated")]
+    DuplicatedSchemaVersion,
     #[error("Schema ID is does not have a policy")]
     NoCorrelationSchemaPolicy,
     #[error("There are policies not correlated to any schema")]
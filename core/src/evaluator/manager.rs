@@ -1,24 +1,116 @@
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use tokio_util::sync::CancellationToken;
-use wasmtime::Engine;
+use wasmtime::{Config, Engine};
 
 use super::compiler::manager::TapleCompiler;
-use super::errors::EvaluatorError;
-use super::{EvaluatorMessage, EvaluatorResponse};
+use super::compiler::ContractBuilder;
+use super::errors::{EvaluatorError, EvaluatorErrorResponses};
+use super::{EvaluatorAPIMessage, EvaluatorAPIResponse, EvaluatorMessage, EvaluatorResponse};
 use crate::commons::channel::{ChannelData, MpscChannel, SenderEnd};
+use crate::commons::schema_handler::gov_models::Contract;
 use crate::commons::self_signature_manager::{SelfSignatureInterface, SelfSignatureManager};
 use crate::database::{DatabaseCollection, DatabaseManager, DB};
 use crate::evaluator::errors::ExecutorErrorResponses;
 use crate::evaluator::runner::manager::TapleRunner;
 use crate::governance::{GovernanceInterface, GovernanceUpdatedMessage};
+use crate::identifier::DigestIdentifier;
 use crate::message::{MessageConfig, MessageTaskCommand};
 use crate::protocol::protocol_message_manager::TapleMessages;
-use crate::request::EventRequest;
+use crate::request::StartRequest;
 use crate::signature::Signed;
 use crate::utils::message::event::create_evaluator_response;
-use crate::{EvaluationResponse, Notification, DigestDerivator};
+use crate::{
+    ContractLimits, DigestDerivator, EvaluationResponse, EventRequest, FactSimulation,
+    GovernanceDiagnostic, Notification, ValueWrapper,
+};
+
+#[derive(Clone)]
+pub struct EvaluatorAPI {
+    input_channel: SenderEnd<EvaluatorAPIMessage, EvaluatorAPIResponse>,
+}
+
+impl EvaluatorAPI {
+    pub fn new(input_channel: SenderEnd<EvaluatorAPIMessage, EvaluatorAPIResponse>) -> Self {
+        Self { input_channel }
+    }
+}
+
+#[async_trait]
+pub trait EvaluatorAPIInterface {
+    async fn simulate_fact(
+        &self,
+        subject_id: DigestIdentifier,
+        payload: ValueWrapper,
+    ) -> Result<FactSimulation, EvaluatorErrorResponses>;
+    async fn validate_governance(
+        &self,
+        document: ValueWrapper,
+    ) -> Result<Vec<GovernanceDiagnostic>, EvaluatorErrorResponses>;
+    async fn validate_governance_patch(
+        &self,
+        governance_id: DigestIdentifier,
+        patch: ValueWrapper,
+    ) -> Result<Vec<GovernanceDiagnostic>, EvaluatorErrorResponses>;
+}
+
+#[async_trait]
+impl EvaluatorAPIInterface for EvaluatorAPI {
+    async fn simulate_fact(
+        &self,
+        subject_id: DigestIdentifier,
+        payload: ValueWrapper,
+    ) -> Result<FactSimulation, EvaluatorErrorResponses> {
+        let response = self
+            .input_channel
+            .ask(EvaluatorAPIMessage::SimulateFact {
+                subject_id,
+                payload,
+            })
+            .await
+            .map_err(|_| EvaluatorErrorResponses::APIChannelNotAvailable)?;
+        let EvaluatorAPIResponse::SimulateFact(result) = response else {
+            return Err(EvaluatorErrorResponses::APIChannelNotAvailable);
+        };
+        result
+    }
+
+    async fn validate_governance(
+        &self,
+        document: ValueWrapper,
+    ) -> Result<Vec<GovernanceDiagnostic>, EvaluatorErrorResponses> {
+        let response = self
+            .input_channel
+            .ask(EvaluatorAPIMessage::ValidateGovernance { document })
+            .await
+            .map_err(|_| EvaluatorErrorResponses::APIChannelNotAvailable)?;
+        let EvaluatorAPIResponse::ValidateGovernance(result) = response else {
+            return Err(EvaluatorErrorResponses::APIChannelNotAvailable);
+        };
+        result
+    }
+
+    async fn validate_governance_patch(
+        &self,
+        governance_id: DigestIdentifier,
+        patch: ValueWrapper,
+    ) -> Result<Vec<GovernanceDiagnostic>, EvaluatorErrorResponses> {
+        let response = self
+            .input_channel
+            .ask(EvaluatorAPIMessage::ValidateGovernancePatch {
+                governance_id,
+                patch,
+            })
+            .await
+            .map_err(|_| EvaluatorErrorResponses::APIChannelNotAvailable)?;
+        let EvaluatorAPIResponse::ValidateGovernance(result) = response else {
+            return Err(EvaluatorErrorResponses::APIChannelNotAvailable);
+        };
+        result
+    }
+}
 
 pub struct EvaluatorManager<
     M: DatabaseManager<C>,
@@ -27,8 +119,12 @@ pub struct EvaluatorManager<
 > {
     /// Communication channel for incoming petitions
     input_channel: MpscChannel<EvaluatorMessage, EvaluatorResponse>,
+    /// Communication channel for the requests of the API of the node
+    api_channel: MpscChannel<EvaluatorAPIMessage, EvaluatorAPIResponse>,
     /// Contract executioner
     runner: TapleRunner<C, G>,
+    /// Builder of the contracts of the governance documents to validate
+    contract_builder: Arc<ContractBuilder>,
     signature_manager: SelfSignatureManager,
     token: CancellationToken,
     _notification_tx: tokio::sync::mpsc::Sender<Notification>,
@@ -46,6 +142,7 @@ impl<
 {
     pub fn new(
         input_channel: MpscChannel<EvaluatorMessage, EvaluatorResponse>,
+        api_channel: MpscChannel<EvaluatorAPIMessage, EvaluatorAPIResponse>,
         database: Arc<M>,
         signature_manager: SelfSignatureManager,
         compiler_channel: tokio::sync::broadcast::Receiver<GovernanceUpdatedMessage>,
@@ -55,14 +152,17 @@ impl<
         contracts_path: String,
         messenger_channel: SenderEnd<MessageTaskCommand<TapleMessages>, ()>,
         derivator: DigestDerivator,
+        contract_limits: ContractLimits,
     ) -> Self {
-        let engine = Engine::default();
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).expect("Engine configuration must be valid");
+        let contract_builder = Arc::new(ContractBuilder::new(engine.clone(), contracts_path));
         let compiler = TapleCompiler::new(
             compiler_channel,
             DB::new(database.clone()),
             gov_api.clone(),
-            contracts_path,
-            engine.clone(),
+            contract_builder.clone(),
             token.clone(),
             notification_tx.clone(),
         );
@@ -71,7 +171,15 @@ impl<
         });
         Self {
             input_channel,
-            runner: TapleRunner::new(DB::new(database.clone()), engine, gov_api, derivator.clone()),
+            api_channel,
+            runner: TapleRunner::new(
+                DB::new(database.clone()),
+                engine,
+                gov_api,
+                derivator.clone(),
+                contract_limits,
+            ),
+            contract_builder,
             signature_manager,
             token,
             _notification_tx: notification_tx,
@@ -99,6 +207,14 @@ impl<
                         },
                     }
                 },
+                // The API may be dropped before the node stops
+                Some(command) = self.api_channel.receive() => {
+                    let result = self.process_api_command(command).await;
+                    if result.is_err() {
+                        log::error!("{}", result.unwrap_err());
+                        break;
+                    }
+                },
                 _ = self.token.cancelled() => {
                     log::debug!("Shutdown received");
                     break;
@@ -129,13 +245,14 @@ impl<
                     evaluation_request,
                     sender,
                 } => {
-                    let EventRequest::Fact(state_data) = &evaluation_request.event_request.content else {
+                    // Creations are only evaluated with payload. Transfers, EOLs and migrations
+                    // are rejected by the runner if the contract does not declare their hook
+                    if let EventRequest::Create(StartRequest { payload: None, .. }) =
+                        &evaluation_request.event_request.content
+                    {
                         break 'response EvaluatorResponse::AskForEvaluation(Err(super::errors::EvaluatorErrorResponses::CreateRequestNotAllowed));
-                    };
-                    let result = self
-                        .runner
-                        .execute_contract(&evaluation_request, state_data)
-                        .await;
+                    }
+                    let result = self.runner.execute_contract(&evaluation_request).await;
                     match result {
                         Ok(executor_response) => {
                             let signature = self
@@ -201,6 +318,11 @@ impl<
                             log::error!("Contract not found: {}, {}", schema, gov);
                             return Ok(());
                         }
+                        Err(ExecutorErrorResponses::SubjectStateNotAvailable(subject, sn)) => {
+                            // The evaluation can not be reproduced until the subject is synced
+                            log::error!("State of subject {} at sn {} not available", subject, sn);
+                            return Ok(());
+                        }
                         Err(ExecutorErrorResponses::ContractNotUpdated) => {
                             log::error!("Contract not Updated");
                             return Ok(());
@@ -237,6 +359,110 @@ impl<
         }
         Ok(())
     }
+
+    async fn process_api_command(
+        &mut self,
+        command: ChannelData<EvaluatorAPIMessage, EvaluatorAPIResponse>,
+    ) -> Result<(), EvaluatorError> {
+        let (sender, data) = match command {
+            ChannelData::AskData(data) => {
+                let (sender, data) = data.get();
+                (Some(sender), data)
+            }
+            ChannelData::TellData(data) => {
+                let data = data.get();
+                (None, data)
+            }
+        };
+        let response = match data {
+            EvaluatorAPIMessage::SimulateFact {
+                subject_id,
+                payload,
+            } => {
+                let result = self
+                    .runner
+                    .simulate_fact(
+                        &subject_id,
+                        &payload,
+                        &self.signature_manager.get_own_identifier(),
+                    )
+                    .await;
+                match result {
+                    Err(ExecutorErrorResponses::DatabaseError(error)) => {
+                        return Err(EvaluatorError::DatabaseError(error))
+                    }
+                    result => EvaluatorAPIResponse::SimulateFact(
+                        result.map_err(EvaluatorErrorResponses::ContractExecutionError),
+                    ),
+                }
+            }
+            EvaluatorAPIMessage::ValidateGovernance { document } => {
+                let checks = self.runner.check_governance(&document);
+                return self.check_governance_contracts(checks, sender);
+            }
+            EvaluatorAPIMessage::ValidateGovernancePatch {
+                governance_id,
+                patch,
+            } => {
+                let checks = match self.runner.patch_governance(&governance_id, &patch) {
+                    Ok(Ok(document)) => self.runner.check_governance(&document),
+                    Ok(Err(diagnostic)) => Ok((vec![diagnostic], vec![])),
+                    Err(error) => Err(error),
+                };
+                return self.check_governance_contracts(checks, sender);
+            }
+        };
+        if let Some(sender) = sender {
+            sender
+                .send(response)
+                .map_err(|_| EvaluatorError::ChannelNotAvailable)?;
+        }
+        Ok(())
+    }
+
+    /// Answers the validation of a governance document once its contracts are checked. The
+    /// contracts may take long to build, so they are checked apart from the evaluations.
+    fn check_governance_contracts(
+        &self,
+        checks: Result<
+            (Vec<GovernanceDiagnostic>, Vec<(String, Contract)>),
+            ExecutorErrorResponses,
+        >,
+        sender: Option<tokio::sync::oneshot::Sender<EvaluatorAPIResponse>>,
+    ) -> Result<(), EvaluatorError> {
+        let (mut diagnostics, contracts) = match checks {
+            Ok(checks) => checks,
+            Err(ExecutorErrorResponses::DatabaseError(error)) => {
+                return Err(EvaluatorError::DatabaseError(error))
+            }
+            Err(error) => {
+                if let Some(sender) = sender {
+                    sender
+                        .send(EvaluatorAPIResponse::ValidateGovernance(Err(
+                            super::errors::EvaluatorErrorResponses::ContractExecutionError(error),
+                        )))
+                        .map_err(|_| EvaluatorError::ChannelNotAvailable)?;
+                }
+                return Ok(());
+            }
+        };
+        let contract_builder = self.contract_builder.clone();
+        tokio::spawn(async move {
+            for (pointer, contract) in contracts {
+                if let Err(error) = contract_builder.check_contract(contract).await {
+                    diagnostics.push(GovernanceDiagnostic {
+                        pointer,
+                        message: error.to_string(),
+                    });
+                }
+            }
+            if let Some(sender) = sender {
+                // The API may have stopped waiting for the answer
+                let _ = sender.send(EvaluatorAPIResponse::ValidateGovernance(Ok(diagnostics)));
+            }
+        });
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -259,7 +485,7 @@ mod test {
                 evaluation::{EvaluationRequest, SubjectContext},
                 state::Subject,
             },
-            schema_handler::gov_models::Contract,
+            schema_handler::gov_models::{Contract, ContractHook},
             self_signature_manager::{SelfSignatureInterface, SelfSignatureManager},
         },
         database::{MemoryCollection, DB},
@@ -267,7 +493,7 @@ mod test {
         event::EventCommand,
         governance::{
             error::RequestError, stage::ValidationStage, GovernanceInterface,
-            GovernanceUpdatedMessage,
+            GovernanceUpdatedMessage, SignerWeights,
         },
         identifier::{DigestIdentifier, KeyIdentifier},
         message::MessageTaskCommand,
@@ -446,18 +672,22 @@ mod test {
             _governance_id: DigestIdentifier,
             _schema_id: String,
             _governance_version: u64,
-        ) -> Result<ValueWrapper, RequestError> {
-            return Ok(ValueWrapper(serde_json::json!({
-                "one": 10,
-                "two": 11,
-                "three": 13,
-            })));
+        ) -> Result<(ValueWrapper, u64), RequestError> {
+            return Ok((
+                ValueWrapper(serde_json::json!({
+                    "one": 10,
+                    "two": 11,
+                    "three": 13,
+                })),
+                0,
+            ));
         }
 
         async fn get_schema(
             &self,
             _governance_id: DigestIdentifier,
             _schema_id: String,
+            _schema_version: u64,
             _governance_version: u64,
         ) -> Result<ValueWrapper, RequestError> {
             return Ok(ValueWrapper(serde_json::json!({
@@ -495,6 +725,14 @@ mod test {
             unimplemented!()
         }
 
+        async fn get_signer_weights(
+            &self,
+            _metadata: Metadata,
+            _stage: ValidationStage,
+        ) -> Result<SignerWeights, RequestError> {
+            unimplemented!()
+        }
+
         async fn get_invoke_info(
             &self,
             _metadata: Metadata,
@@ -508,7 +746,7 @@ mod test {
             &self,
             governance_id: DigestIdentifier,
             _governance_version: u64,
-        ) -> Result<Vec<(Contract, String)>, RequestError> {
+        ) -> Result<Vec<(Contract, String, u64)>, RequestError> {
             if governance_id
                 == DigestIdentifier::from_str("Jg2Nuv5bNs4swQGcPQ1CXs9MtcfwMVoeQDR2Ea1YNYJw")
                     .unwrap()
@@ -516,8 +754,16 @@ mod test {
                 Ok(vec![(
                     Contract {
                         raw: String::from("test"),
+                        wasm: None,
+                        wasm_hash: None,
+                        fuel: None,
+                        memory: None,
+                        mode: None,
+                        roles: vec![],
+                        hooks: vec![],
                     },
                     "test".to_owned(),
+                    0,
                 )])
             } else if governance_id
                 == DigestIdentifier::from_str("Jg2Nuc5bNs4swQGcPQ1CXs9MtcfwMVoeQDR2Ea1YNYJw")
@@ -526,8 +772,16 @@ mod test {
                 Ok(vec![(
                     Contract {
                         raw: get_file_wrong().to_string(),
+                        wasm: None,
+                        wasm_hash: None,
+                        fuel: None,
+                        memory: None,
+                        mode: None,
+                        roles: vec![],
+                        hooks: vec![],
                     },
                     "test".to_owned(),
+                    0,
                 )])
             } else if governance_id
                 == DigestIdentifier::from_str("Jg2Nuc5bNs4swQGcPQ2CXs9MtcfwMVoeQDR2Ea2YNYJw")
@@ -536,19 +790,45 @@ mod test {
                 Ok(vec![(
                     Contract {
                         raw: get_file_wrong2().to_string(),
+                        wasm: None,
+                        wasm_hash: None,
+                        fuel: None,
+                        memory: None,
+                        mode: None,
+                        roles: vec![],
+                        hooks: vec![],
                     },
                     "test".to_owned(),
+                    0,
                 )])
             } else {
                 Ok(vec![(
                     Contract {
                         raw: get_file().to_string(),
+                        wasm: None,
+                        wasm_hash: None,
+                        fuel: None,
+                        memory: None,
+                        mode: None,
+                        roles: vec![],
+                        hooks: vec![],
                     },
                     "test".to_owned(),
+                    0,
                 )])
             }
         }
 
+        async fn get_contract_hooks(
+            &self,
+            _governance_id: DigestIdentifier,
+            _schema_id: String,
+            _schema_version: u64,
+            _governance_version: u64,
+        ) -> Result<Vec<ContractHook>, RequestError> {
+            Ok(vec![])
+        }
+
         async fn get_governance_version(
             &self,
             _governance_id: DigestIdentifier,
@@ -578,6 +858,7 @@ mod test {
         MpscChannel<MessageTaskCommand<TapleMessages>, ()>,
     ) {
         let (rx, sx) = MpscChannel::new(100);
+        let (api_rx, _api_sx) = MpscChannel::new(100);
         let (msg_rx, msg_sx) = MpscChannel::new(100);
         let (sx_compiler, rx_compiler) = tokio::sync::broadcast::channel(100);
         let keypair = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[]));
@@ -601,6 +882,7 @@ mod test {
             .unwrap();
         let manager = EvaluatorManager::new(
             rx,
+            api_rx,
             collection,
             signature_manager.clone(),
             rx_compiler,
@@ -609,7 +891,8 @@ mod test {
             governance,
             SC_DIR.to_string(),
             msg_sx,
-            crate::DigestDerivator::Blake3_256
+            crate::DigestDerivator::Blake3_256,
+            crate::ContractLimits::default(),
         );
         (manager, sx, sx_compiler, signature_manager, msg_rx)
     }
@@ -630,6 +913,7 @@ mod test {
             public_key: identifier.clone(),
             namespace: "namespace1".into(),
             schema_id: "test".into(),
+            schema_version: 0,
             owner: identifier.clone(),
             creator: identifier,
             properties: ValueWrapper(initial_state_json),
@@ -705,6 +989,7 @@ mod test {
                         context: SubjectContext {
                             governance_id: governance_id.to_owned(),
                             schema_id: "test".into(),
+                            schema_version: 0,
                             namespace: "namespace1".into(),
                             is_owner: true,
                             state: ValueWrapper(initial_state_json.clone()),
@@ -2,19 +2,22 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    commons::models::evaluation::EvaluationRequest, identifier::DigestIdentifier,
-    signature::Signature, KeyIdentifier, ValueWrapper,
+    commons::models::evaluation::{EvaluationRequest, FactSimulation, GovernanceDiagnostic},
+    identifier::DigestIdentifier,
+    signature::Signature,
+    KeyIdentifier, ValueWrapper,
 };
 
 use self::errors::EvaluatorErrorResponses;
 #[cfg(feature = "evaluation")]
 pub mod compiler;
 
-mod errors;
+pub(crate) mod dependencies;
+pub(crate) mod errors;
 #[cfg(feature = "evaluation")]
 mod manager;
 #[cfg(feature = "evaluation")]
-pub use manager::EvaluatorManager;
+pub use manager::{EvaluatorAPI, EvaluatorAPIInterface, EvaluatorManager};
 #[cfg(feature = "evaluation")]
 mod runner;
 
@@ -31,6 +34,29 @@ pub enum EvaluatorMessage {
 pub enum EvaluatorResponse {
     AskForEvaluation(Result<(), EvaluatorErrorResponses>),
 }
+
+/// Requests of the API of the node to the evaluator. They have their own channel, so they
+/// can never be received from other nodes.
+#[derive(Clone, Debug)]
+pub enum EvaluatorAPIMessage {
+    SimulateFact {
+        subject_id: DigestIdentifier,
+        payload: ValueWrapper,
+    },
+    ValidateGovernance {
+        document: ValueWrapper,
+    },
+    ValidateGovernancePatch {
+        governance_id: DigestIdentifier,
+        patch: ValueWrapper,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum EvaluatorAPIResponse {
+    SimulateFact(Result<FactSimulation, EvaluatorErrorResponses>),
+    ValidateGovernance(Result<Vec<GovernanceDiagnostic>, EvaluatorErrorResponses>),
+}
 #[derive(Clone, Debug)]
 pub struct AskForEvaluationResponse {
     pub governance_version: u64,
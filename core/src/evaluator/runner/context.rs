@@ -1,28 +1,65 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
 use borsh::BorshSerialize;
+use wasmtime::ResourceLimiter;
 
-use crate::evaluator::errors::ExecutorErrorResponses;
+use crate::{
+    commons::models::evaluation::{ContractLog, ContractLogLevel, SubjectDependency},
+    evaluator::{dependencies::SubjectStateReader, errors::ExecutorErrorResponses},
+    identifier::{Derivable, DigestIdentifier},
+};
 
-#[derive(Debug)]
 pub struct MemoryManager {
     memory: Vec<u8>,
     map: HashMap<usize, usize>,
+    memory_limit: usize,
+    context_ptr: usize,
+    log_tag: String,
+    capture_logs: bool,
+    logs: Vec<ContractLog>,
+    logged_bytes: usize,
+    subject_reader: Option<Box<dyn SubjectStateReader>>,
+    dependencies: Vec<SubjectDependency>,
 }
 
 impl MemoryManager {
-    pub fn new() -> Self {
+    pub fn new(memory_limit: usize, log_tag: String, capture_logs: bool) -> Self {
         Self {
             memory: vec![],
             map: HashMap::new(),
+            memory_limit,
+            context_ptr: 0,
+            log_tag,
+            capture_logs,
+            logs: vec![],
+            logged_bytes: 0,
+            subject_reader: None,
+            dependencies: vec![],
         }
     }
 
-    pub fn alloc(&mut self, len: usize) -> usize {
+    pub fn set_context_ptr(&mut self, ptr: usize) {
+        self.context_ptr = ptr;
+    }
+
+    pub fn get_context_ptr(&self) -> usize {
+        self.context_ptr
+    }
+
+    pub fn set_subject_reader(&mut self, subject_reader: Box<dyn SubjectStateReader>) {
+        self.subject_reader = Some(subject_reader);
+    }
+
+    pub fn alloc(&mut self, len: usize) -> Result<usize, ExecutorErrorResponses> {
         let current_len = self.memory.len();
+        if current_len.saturating_add(len) > self.memory_limit {
+            return Err(ExecutorErrorResponses::ContractLimitExceeded(
+                "memory".to_owned(),
+            ));
+        }
         self.memory.resize(current_len + len, 0);
         self.map.insert(current_len, len);
-        current_len
+        Ok(current_len)
     }
 
     pub fn write_byte(&mut self, start_ptr: usize, offset: usize, data: u8) {
@@ -48,21 +85,102 @@ impl MemoryManager {
         *result as isize
     }
 
-    pub fn add_data_raw(&mut self, bytes: &[u8]) -> usize {
-        let ptr = self.alloc(bytes.len());
+    pub fn log(&mut self, level: u32, ptr: usize) -> Result<(), ExecutorErrorResponses> {
+        let message = String::from_utf8_lossy(self.read_data(ptr)?).into_owned();
+        let level = ContractLogLevel::from(level);
+        log::log!(target: "taple::contract", level.into(), "[{}] {}", self.log_tag, message);
+        if self.capture_logs {
+            // Captured messages are kept in the node, so they count towards the memory limit
+            self.logged_bytes += message.len();
+            if self.logged_bytes > self.memory_limit {
+                return Err(ExecutorErrorResponses::ContractLimitExceeded(
+                    "memory".to_owned(),
+                ));
+            }
+            self.logs.push(ContractLog { level, message });
+        }
+        Ok(())
+    }
+
+    pub fn take_logs(&mut self) -> Vec<ContractLog> {
+        std::mem::take(&mut self.logs)
+    }
+
+    pub fn get_subject_state(
+        &mut self,
+        ptr: usize,
+        sn: u64,
+    ) -> Result<usize, ExecutorErrorResponses> {
+        let subject_id = String::from_utf8_lossy(self.read_data(ptr)?).into_owned();
+        let subject_id = DigestIdentifier::from_str(&subject_id)
+            .map_err(|_| ExecutorErrorResponses::InvalidSubjectDependency(subject_id))?;
+        let Some(subject_reader) = self.subject_reader.as_ref() else {
+            return Err(ExecutorErrorResponses::InvalidSubjectDependency(
+                subject_id.to_str(),
+            ));
+        };
+        let (state, state_hash) = subject_reader.read(&subject_id, sn)?;
+        let dependency = SubjectDependency {
+            subject_id,
+            sn,
+            state_hash,
+        };
+        // The same state can be read several times, it is recorded only once
+        if !self.dependencies.contains(&dependency) {
+            self.dependencies.push(dependency);
+        }
+        let bytes = state
+            .try_to_vec()
+            .map_err(|_| ExecutorErrorResponses::BorshSerializationError)?;
+        self.add_data_raw(&bytes)
+    }
+
+    pub fn take_dependencies(&mut self) -> Vec<SubjectDependency> {
+        std::mem::take(&mut self.dependencies)
+    }
+
+    pub fn add_data_raw(&mut self, bytes: &[u8]) -> Result<usize, ExecutorErrorResponses> {
+        let ptr = self.alloc(bytes.len())?;
         for (index, byte) in bytes.iter().enumerate() {
             self.memory[ptr + index] = *byte;
         }
-        ptr
+        Ok(ptr)
     }
 
     #[allow(dead_code)]
-    pub fn add_data<S: BorshSerialize>(&mut self, data: S) -> usize {
+    pub fn add_data<S: BorshSerialize>(
+        &mut self,
+        data: S,
+    ) -> Result<usize, ExecutorErrorResponses> {
         let bytes = data.try_to_vec().unwrap();
-        let ptr = self.alloc(bytes.len());
+        let ptr = self.alloc(bytes.len())?;
         for (index, byte) in bytes.iter().enumerate() {
             self.memory[ptr + index] = *byte;
         }
-        ptr
+        Ok(ptr)
+    }
+}
+
+impl ResourceLimiter for MemoryManager {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        // An error instead of a failed growth, so the contract traps deterministically
+        if desired > self.memory_limit {
+            return Err(ExecutorErrorResponses::ContractLimitExceeded("memory".to_owned()).into());
+        }
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: u32,
+        _desired: u32,
+        _maximum: Option<u32>,
+    ) -> wasmtime::Result<bool> {
+        Ok(true)
     }
 }
@@ -1,18 +1,24 @@
 use std::collections::HashSet;
 
 use crate::{
+    commons::models::evaluation::{ContractContext, ContractLog, SubjectDependency},
     commons::schema_handler::gov_models::{
-        Governance, GovernanceEvent, Member, Policy, Role, Schema, SchemaEnum, Who,
+        ContractHook, ContractMode, Governance, GovernanceEvent, Member, Policy, Role, Schema,
+        SchemaEnum, Who,
+    },
+    evaluator::{
+        dependencies::SubjectStateReader,
+        errors::{ExecutorErrorResponses, GovernanceStateError},
     },
-    evaluator::errors::{ExecutorErrorResponses, GovernanceStateError},
     utils::patch::apply_patch,
-    ValueWrapper,
+    ContractLimits, ValueWrapper,
 };
 
 use super::context::MemoryManager;
 use borsh::{BorshDeserialize, BorshSerialize};
+use json_patch::{diff, PatchOperation};
 use serde::{Deserialize, Serialize};
-use wasmtime::{Caller, Engine, Linker, Module, Store};
+use wasmtime::{Caller, Engine, Linker, Module, Store, Trap};
 
 #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 struct WasmContractResult {
@@ -26,6 +32,8 @@ pub struct ContractResult {
     pub final_state: ValueWrapper,
     pub approval_required: bool,
     pub success: bool,
+    pub logs: Vec<ContractLog>,
+    pub dependencies: Vec<SubjectDependency>,
 }
 
 impl ContractResult {
@@ -34,6 +42,8 @@ impl ContractResult {
             final_state: ValueWrapper(serde_json::Value::Null),
             approval_required: false,
             success: false,
+            logs: vec![],
+            dependencies: vec![],
         }
     }
 }
@@ -41,6 +51,42 @@ impl ContractResult {
 pub enum Contract {
     CompiledContract(Vec<u8>),
     GovContract,
+    /// Built-in contract of a schema. If present, `pointers` are the only parts of the state
+    /// that the invoker may modify. Its events never require approval.
+    BuiltinContract {
+        mode: ContractMode,
+        pointers: Option<Vec<String>>,
+    },
+    /// Keeps the state as it is. Used to upgrade a subject to a schema version whose
+    /// contract has no migration hook.
+    Identity,
+}
+
+/// Function of a compiled contract that is called by the executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractEntryPoint {
+    /// `main_function`, that applies a fact to the state of a subject.
+    Main,
+    /// `init_function`, that builds the initial state of a subject from the creation payload.
+    Init,
+    /// `transfer_function`, that may veto a transfer of a subject or patch its state.
+    Transfer,
+    /// `eol_function`, that may veto the end of life of a subject or patch its state.
+    EOL,
+    /// `migrate_function`, that migrates the state of a subject to the current schema.
+    Migration,
+}
+
+impl ContractEntryPoint {
+    /// Hook that a contract must declare for this entry point to be called, if any.
+    pub fn hook(&self) -> Option<ContractHook> {
+        match self {
+            ContractEntryPoint::Main | ContractEntryPoint::Init => None,
+            ContractEntryPoint::Transfer => Some(ContractHook::Transfer),
+            ContractEntryPoint::EOL => Some(ContractHook::EOL),
+            ContractEntryPoint::Migration => Some(ContractHook::Migration),
+        }
+    }
 }
 
 pub struct ContractExecutor {
@@ -70,53 +116,174 @@ impl ContractExecutor {
                         final_state: ValueWrapper(serde_json::to_value(patched_state).unwrap()),
                         approval_required: true,
                         success: true,
+                        logs: vec![],
+                        dependencies: vec![],
                     })
                 } else {
                     Ok(ContractResult {
                         final_state: state.clone(),
                         approval_required: false,
                         success: false,
+                        logs: vec![],
+                        dependencies: vec![],
                     })
                 }
             }
         }
     }
 
+    /// Applies a fact with a built-in contract. Unlike compiled contracts, built-in contracts
+    /// cannot ask for approval, so their results never require it.
+    fn execute_builtin_contract(
+        &self,
+        state: &ValueWrapper,
+        event: &ValueWrapper,
+        mode: ContractMode,
+        pointers: Option<Vec<String>>,
+    ) -> ContractResult {
+        let final_state = match mode {
+            ContractMode::Patch => match apply_patch(event.0.clone(), state.0.clone()) {
+                Ok(final_state) => final_state,
+                Err(_) => return ContractResult::error(),
+            },
+            ContractMode::Replace => event.0.clone(),
+        };
+        if let Some(pointers) = pointers {
+            let changes = diff(&state.0, &final_state);
+            let allowed = changes.0.iter().all(|operation| {
+                let path = modified_path(operation);
+                pointers
+                    .iter()
+                    .any(|pointer| is_below_pointer(path, pointer))
+            });
+            if !allowed {
+                return ContractResult::error();
+            }
+        }
+        // The state is validated against the schema afterwards, as for compiled contracts
+        ContractResult {
+            final_state: ValueWrapper(final_state),
+            approval_required: false,
+            success: true,
+            logs: vec![],
+            dependencies: vec![],
+        }
+    }
+
     pub async fn execute_contract(
         &self,
         state: &ValueWrapper,
         event: &ValueWrapper,
         compiled_contract: Contract,
-        is_owner: bool,
+        contract_context: &ContractContext,
+        limits: &ContractLimits,
+        capture_logs: bool,
+        subject_reader: Option<Box<dyn SubjectStateReader>>,
+        entry_point: ContractEntryPoint,
     ) -> Result<ContractResult, ExecutorErrorResponses> {
-        let Contract::CompiledContract(contract_bytes) = compiled_contract else {
-            return self.execute_gov_contract(
-                state, event
-            ).await;
+        let contract_bytes = match compiled_contract {
+            Contract::CompiledContract(contract_bytes) => contract_bytes,
+            // Governances are always created with the initial state of their schema
+            Contract::GovContract if entry_point == ContractEntryPoint::Init => {
+                return Err(ExecutorErrorResponses::CreateRequestNotAllowed)
+            }
+            // The state is validated against the schema afterwards
+            Contract::Identity => {
+                return Ok(ContractResult {
+                    final_state: state.clone(),
+                    approval_required: false,
+                    success: true,
+                    logs: vec![],
+                    dependencies: vec![],
+                })
+            }
+            // Only compiled contracts declare hooks
+            Contract::GovContract | Contract::BuiltinContract { .. }
+                if entry_point.hook().is_some() =>
+            {
+                return Err(ExecutorErrorResponses::ContractHookNotDeclared(
+                    contract_context.schema_id.clone(),
+                    format!("{:?}", entry_point),
+                ))
+            }
+            Contract::GovContract => return self.execute_gov_contract(state, event).await,
+            Contract::BuiltinContract { mode, pointers } => {
+                return Ok(self.execute_builtin_contract(state, event, mode, pointers))
+            }
         };
         // Cargar wasm
-        let module = unsafe { Module::deserialize(&self.engine, contract_bytes).unwrap() };
+        // Modules precompiled with another engine configuration are rejected
+        let module = unsafe { Module::deserialize(&self.engine, contract_bytes) }
+            .map_err(|_| ExecutorErrorResponses::ContractNotInstantiated)?;
         // Generar contexto
-        let (context, state_ptr, event_ptr) = self.generate_context(&state, &event)?;
+        let (context, state_ptr, event_ptr) = self.generate_context(
+            &state,
+            &event,
+            contract_context,
+            limits.memory as usize,
+            capture_logs,
+            subject_reader,
+        )?;
         let mut store = Store::new(&self.engine, context);
+        store.limiter(|context| context);
+        store
+            .add_fuel(limits.fuel)
+            .map_err(|_| ExecutorErrorResponses::ContractNotInstantiated)?;
         // Generar Linker
         let linker = self.generate_linker(&self.engine)?;
         // Generar instancia contrato
-        let instance = linker
-            .instantiate(&mut store, &module)
-            .map_err(|_| ExecutorErrorResponses::ContractNotInstantiated)?;
+        let instance = linker.instantiate(&mut store, &module).map_err(|error| {
+            host_error(&error).unwrap_or(ExecutorErrorResponses::ContractNotInstantiated)
+        })?;
         // Ejecución contrato
-        let contract_entrypoint = instance
-            .get_typed_func::<(u32, u32, u32), u32>(&mut store, "main_function")
-            .map_err(|_| ExecutorErrorResponses::ContractEntryPointNotFound)?;
-        let result_ptr = contract_entrypoint
-            .call(
-                &mut store,
-                (state_ptr, event_ptr, if is_owner { 1 } else { 0 }),
-            )
-            .map_err(|_| ExecutorErrorResponses::ContractExecutionFailed)?;
+        let result = match entry_point {
+            ContractEntryPoint::Main => {
+                let contract_entrypoint = instance
+                    .get_typed_func::<(u32, u32, u32), u32>(&mut store, "main_function")
+                    .map_err(|_| ExecutorErrorResponses::ContractEntryPointNotFound)?;
+                contract_entrypoint.call(
+                    &mut store,
+                    (
+                        state_ptr,
+                        event_ptr,
+                        if contract_context.is_owner { 1 } else { 0 },
+                    ),
+                )
+            }
+            ContractEntryPoint::Init => {
+                let contract_entrypoint = instance
+                    .get_typed_func::<(u32, u32), u32>(&mut store, "init_function")
+                    .map_err(|_| ExecutorErrorResponses::ContractEntryPointNotFound)?;
+                contract_entrypoint.call(&mut store, (state_ptr, event_ptr))
+            }
+            ContractEntryPoint::Transfer
+            | ContractEntryPoint::EOL
+            | ContractEntryPoint::Migration => {
+                let name = match entry_point {
+                    ContractEntryPoint::Transfer => "transfer_function",
+                    ContractEntryPoint::EOL => "eol_function",
+                    _ => "migrate_function",
+                };
+                let contract_entrypoint = instance
+                    .get_typed_func::<(u32, u32, u32), u32>(&mut store, name)
+                    .map_err(|_| ExecutorErrorResponses::ContractEntryPointNotFound)?;
+                contract_entrypoint.call(
+                    &mut store,
+                    (
+                        state_ptr,
+                        event_ptr,
+                        if contract_context.is_owner { 1 } else { 0 },
+                    ),
+                )
+            }
+        };
+        let result_ptr = result.map_err(|error| {
+            host_error(&error).unwrap_or(ExecutorErrorResponses::ContractExecutionFailed)
+        })?;
         // Obtención "NEW STATE" almacenado en el contexto
-        let contract_result = self.get_result(&store, result_ptr)?;
+        let mut contract_result = self.get_result(&store, result_ptr)?;
+        contract_result.logs = store.data_mut().take_logs();
+        contract_result.dependencies = store.data_mut().take_dependencies();
         Ok(contract_result)
     }
 
@@ -124,18 +291,36 @@ impl ContractExecutor {
         &self,
         state: &ValueWrapper,
         event: &ValueWrapper,
+        contract_context: &ContractContext,
+        memory_limit: usize,
+        capture_logs: bool,
+        subject_reader: Option<Box<dyn SubjectStateReader>>,
     ) -> Result<(MemoryManager, u32, u32), ExecutorErrorResponses> {
-        let mut context = MemoryManager::new();
+        let log_tag = format!(
+            "{}/{}/{}",
+            contract_context.governance_id, contract_context.schema_id, contract_context.subject_id
+        );
+        let mut context = MemoryManager::new(memory_limit, log_tag, capture_logs);
+        if let Some(subject_reader) = subject_reader {
+            context.set_subject_reader(subject_reader);
+        }
         let state_ptr = context.add_data_raw(
             &state
                 .try_to_vec()
                 .map_err(|_| ExecutorErrorResponses::BorshSerializationError)?,
-        );
+        )?;
         let event_ptr = context.add_data_raw(
             &event
                 .try_to_vec()
                 .map_err(|_| ExecutorErrorResponses::BorshSerializationError)?,
-        );
+        )?;
+        // Contracts request the context through the get_context host function
+        let context_ptr = context.add_data_raw(
+            &contract_context
+                .try_to_vec()
+                .map_err(|_| ExecutorErrorResponses::BorshSerializationError)?,
+        )?;
+        context.set_context_ptr(context_ptr);
         Ok((context, state_ptr as u32, event_ptr as u32))
     }
 
@@ -151,6 +336,8 @@ impl ContractExecutor {
             final_state: contract_result.final_state,
             approval_required: contract_result.approval_required,
             success: contract_result.success,
+            logs: vec![],
+            dependencies: vec![],
         };
         Ok(result)
     }
@@ -173,8 +360,9 @@ impl ContractExecutor {
             .func_wrap(
                 "env",
                 "alloc",
-                |mut caller: Caller<'_, MemoryManager>, len: u32| {
-                    return caller.data_mut().alloc(len as usize) as u32;
+                |mut caller: Caller<'_, MemoryManager>, len: u32| -> wasmtime::Result<u32> {
+                    let ptr = caller.data_mut().alloc(len as usize)?;
+                    Ok(ptr as u32)
                 },
             )
             .map_err(|_| ExecutorErrorResponses::FunctionLinkingFailed("alloc".to_owned()))?;
@@ -198,29 +386,105 @@ impl ContractExecutor {
                 },
             )
             .map_err(|_| ExecutorErrorResponses::FunctionLinkingFailed("read_byte".to_owned()))?;
+        linker
+            .func_wrap("env", "get_context", |caller: Caller<'_, MemoryManager>| {
+                return caller.data().get_context_ptr() as u32;
+            })
+            .map_err(|_| ExecutorErrorResponses::FunctionLinkingFailed("get_context".to_owned()))?;
+        linker
+            .func_wrap(
+                "env",
+                "log",
+                |mut caller: Caller<'_, MemoryManager>,
+                 level: u32,
+                 ptr: u32|
+                 -> wasmtime::Result<()> {
+                    caller.data_mut().log(level, ptr as usize)?;
+                    Ok(())
+                },
+            )
+            .map_err(|_| ExecutorErrorResponses::FunctionLinkingFailed("log".to_owned()))?;
         linker
             .func_wrap(
                 "env",
-                "cout",
-                |_caller: Caller<'_, MemoryManager>, ptr: u32| {
-                    println!("{}", ptr);
+                "get_subject_state",
+                |mut caller: Caller<'_, MemoryManager>,
+                 ptr: u32,
+                 sn: u64|
+                 -> wasmtime::Result<u32> {
+                    let state_ptr = caller.data_mut().get_subject_state(ptr as usize, sn)?;
+                    Ok(state_ptr as u32)
                 },
             )
-            .expect("Failed write_byte link");
+            .map_err(|_| {
+                ExecutorErrorResponses::FunctionLinkingFailed("get_subject_state".to_owned())
+            })?;
         Ok(linker)
     }
 }
 
+// Errors raised by the host functions are kept instead of reporting a generic failure
+fn host_error(error: &wasmtime::Error) -> Option<ExecutorErrorResponses> {
+    if let Some(Trap::OutOfFuel) = error.downcast_ref::<Trap>() {
+        return Some(ExecutorErrorResponses::ContractLimitExceeded(
+            "fuel".to_owned(),
+        ));
+    }
+    match error.downcast_ref::<ExecutorErrorResponses>() {
+        Some(
+            error @ (ExecutorErrorResponses::ContractLimitExceeded(_)
+            | ExecutorErrorResponses::SubjectStateNotAvailable(..)
+            | ExecutorErrorResponses::InvalidSubjectDependency(_)
+            | ExecutorErrorResponses::DatabaseError(_)),
+        ) => Some(error.clone()),
+        _ => None,
+    }
+}
+
+fn modified_path(operation: &PatchOperation) -> &str {
+    match operation {
+        PatchOperation::Add(operation) => &operation.path,
+        PatchOperation::Remove(operation) => &operation.path,
+        PatchOperation::Replace(operation) => &operation.path,
+        PatchOperation::Move(operation) => &operation.path,
+        PatchOperation::Copy(operation) => &operation.path,
+        PatchOperation::Test(operation) => &operation.path,
+    }
+}
+
+fn is_below_pointer(path: &str, pointer: &str) -> bool {
+    match path.strip_prefix(pointer) {
+        Some(rest) => pointer.is_empty() || rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
 fn check_governance_state(state: &Governance) -> Result<(), GovernanceStateError> {
+    match governance_state_errors(state).into_iter().next() {
+        Some((_, error)) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Returns every inconsistency of a governance state with the JSON pointer to the part of the
+/// state where it was found.
+pub(crate) fn governance_state_errors(state: &Governance) -> Vec<(String, GovernanceStateError)> {
+    let mut errors = Vec::new();
     // Debemos comprobar varios aspectos del estado.
     // No pueden haber miembros duplicados, ya sean en name o en ID
-    let (id_set, name_set) = check_members(&state.members)?;
+    let (id_set, name_set) = check_members(&state.members, &mut errors);
     // No pueden haber policies duplicadas y la asociada a la propia gobernanza debe estar presente
-    let policies_names = check_policies(&state.policies)?;
+    let policies_names = check_policies(&state.policies, &mut errors);
     // No se pueden indicar policies de schema que no existen. Así mismo, no pueden haber
     // schemas sin policies. La correlación debe ser uno-uno
-    check_schemas(&state.schemas, policies_names.clone())?;
-    check_roles(&state.roles, policies_names, id_set, name_set)
+    check_schemas(
+        &state.schemas,
+        &state.policies,
+        policies_names.clone(),
+        &mut errors,
+    );
+    check_roles(&state.roles, policies_names, id_set, name_set, &mut errors);
+    errors
 }
 
 fn check_roles(
@@ -228,88 +492,295 @@ fn check_roles(
     mut schemas_names: HashSet<String>,
     id_set: HashSet<String>,
     name_set: HashSet<String>,
-) -> Result<(), GovernanceStateError> {
+    errors: &mut Vec<(String, GovernanceStateError)>,
+) {
     schemas_names.insert("governance".into());
-    for role in roles {
+    for (index, role) in roles.iter().enumerate() {
         if let SchemaEnum::ID { ID } = &role.schema {
             if !schemas_names.contains(ID) {
-                return Err(GovernanceStateError::InvalidRoleSchema);
+                errors.push((
+                    format!("/roles/{}/schema", index),
+                    GovernanceStateError::InvalidRoleSchema,
+                ));
             }
         }
         match &role.who {
             Who::ID { ID } => {
                 if !id_set.contains(ID) {
-                    return Err(GovernanceStateError::IdWhoRoleNoExist);
+                    errors.push((
+                        format!("/roles/{}/who", index),
+                        GovernanceStateError::IdWhoRoleNoExist,
+                    ));
                 }
             }
             Who::NAME { NAME } => {
                 if !name_set.contains(NAME) {
-                    return Err(GovernanceStateError::NameWhoRoleNoExist);
+                    errors.push((
+                        format!("/roles/{}/who", index),
+                        GovernanceStateError::NameWhoRoleNoExist,
+                    ));
                 }
             }
             _ => {}
         }
     }
-    Ok(())
 }
 
 fn check_members(
     members: &Vec<Member>,
-) -> Result<(HashSet<String>, HashSet<String>), GovernanceStateError> {
+    errors: &mut Vec<(String, GovernanceStateError)>,
+) -> (HashSet<String>, HashSet<String>) {
     let mut name_set = HashSet::new();
     let mut id_set = HashSet::new();
-    for member in members {
-        if name_set.contains(&member.name) {
-            return Err(GovernanceStateError::DuplicatedMemberName);
+    for (index, member) in members.iter().enumerate() {
+        if !name_set.insert(member.name.clone()) {
+            errors.push((
+                format!("/members/{}/name", index),
+                GovernanceStateError::DuplicatedMemberName,
+            ));
         }
-        name_set.insert(member.name.clone());
-        if id_set.contains(&member.id) {
-            return Err(GovernanceStateError::DuplicatedMemberID);
+        if !id_set.insert(member.id.clone()) {
+            errors.push((
+                format!("/members/{}/id", index),
+                GovernanceStateError::DuplicatedMemberID,
+            ));
         }
-        id_set.insert(member.id.clone());
     }
-    Ok((id_set, name_set))
+    (id_set, name_set)
 }
 
-fn check_policies(policies: &Vec<Policy>) -> Result<HashSet<String>, GovernanceStateError> {
+fn check_policies(
+    policies: &Vec<Policy>,
+    errors: &mut Vec<(String, GovernanceStateError)>,
+) -> HashSet<String> {
     // Se comprueban de que no hayan policies duplicadas y de que se incluya la de gobernanza
     let mut is_governance_present = false;
     let mut id_set = HashSet::new();
-    for policy in policies {
-        if id_set.contains(&policy.id) {
-            return Err(GovernanceStateError::DuplicatedPolicyID);
+    for (index, policy) in policies.iter().enumerate() {
+        if !id_set.insert(&policy.id) {
+            errors.push((
+                format!("/policies/{}/id", index),
+                GovernanceStateError::DuplicatedPolicyID,
+            ));
         }
-        id_set.insert(&policy.id);
         if &policy.id == "governance" {
             is_governance_present = true
         }
     }
     if !is_governance_present {
-        return Err(GovernanceStateError::NoGvernancePolicy);
+        errors.push(("/policies".into(), GovernanceStateError::NoGvernancePolicy));
     }
     id_set.remove(&String::from("governance"));
-    Ok(id_set.into_iter().cloned().collect())
+    id_set.into_iter().cloned().collect()
 }
 
 fn check_schemas(
     schemas: &Vec<Schema>,
+    policies: &Vec<Policy>,
     mut policies_names: HashSet<String>,
-) -> Result<(), GovernanceStateError> {
+    errors: &mut Vec<(String, GovernanceStateError)>,
+) {
     // Comprobamos que no hayan esquemas duplicados
     // También se tiene que comprobar que los estados iniciales sean válidos según el json_schema
     // Así mismo no puede haber un schema con id "governance"
-    for schema in schemas {
+    // Las versiones de un mismo schema comparten su policy
+    let mut ids = HashSet::new();
+    let mut versions = HashSet::new();
+    for (index, schema) in schemas.iter().enumerate() {
         if &schema.id == "governance" {
-            return Err(GovernanceStateError::GovernanceShchemaIDDetected);
+            errors.push((
+                format!("/schemas/{}/id", index),
+                GovernanceStateError::GovernanceShchemaIDDetected,
+            ));
+            continue;
+        }
+        if !versions.insert((&schema.id, schema.version)) {
+            errors.push((
+                format!("/schemas/{}/version", index),
+                GovernanceStateError::DuplicatedSchemaVersion,
+            ));
         }
-        // No pueden haber duplicados y tienen que tener correspondencia con policies_names
-        if !policies_names.remove(&schema.id) {
+        // Tienen que tener correspondencia con policies_names
+        if ids.insert(&schema.id) && !policies_names.remove(&schema.id) {
             // No tiene relación con policies_names
-            return Err(GovernanceStateError::NoCorrelationSchemaPolicy);
+            errors.push((
+                format!("/schemas/{}/id", index),
+                GovernanceStateError::NoCorrelationSchemaPolicy,
+            ));
         }
     }
-    if !policies_names.is_empty() {
-        return Err(GovernanceStateError::PoliciesWithoutSchema);
+    for (index, policy) in policies.iter().enumerate() {
+        if policies_names.remove(&policy.id) {
+            errors.push((
+                format!("/policies/{}", index),
+                GovernanceStateError::PoliciesWithoutSchema,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use json_patch::{diff, Patch};
+    use serde_json::json;
+    use tokio::runtime::Runtime;
+    use wasmtime::{Config, Engine};
+
+    use super::{
+        is_below_pointer, modified_path, Contract, ContractEntryPoint, ContractExecutor,
+        ContractResult,
+    };
+    use crate::{
+        commons::{models::evaluation::ContractContext, schema_handler::gov_models::ContractMode},
+        evaluator::errors::ExecutorErrorResponses,
+        ContractLimits, ValueWrapper,
+    };
+
+    fn context() -> ContractContext {
+        ContractContext {
+            subject_id: "subject".into(),
+            governance_id: "governance".into(),
+            schema_id: "schema".into(),
+            namespace: "".into(),
+            invoker: "invoker".into(),
+            is_owner: true,
+            sn: 1,
+            gov_version: 0,
+            timestamp: 0,
+        }
+    }
+
+    /// Runs the `main_function` of a contract written in the WebAssembly text format.
+    fn execute_wat(
+        wat: &str,
+        limits: &ContractLimits,
+    ) -> Result<ContractResult, ExecutorErrorResponses> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).unwrap();
+        let contract = engine.precompile_module(wat.as_bytes()).unwrap();
+        let executor = ContractExecutor::new(engine);
+        Runtime::new().unwrap().block_on(executor.execute_contract(
+            &ValueWrapper(json!({})),
+            &ValueWrapper(json!({})),
+            Contract::CompiledContract(contract),
+            &context(),
+            limits,
+            false,
+            None,
+            ContractEntryPoint::Main,
+        ))
+    }
+
+    #[test]
+    fn infinite_loops_run_out_of_fuel() {
+        let wat = r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "main_function") (param i32 i32 i32) (result i32)
+                    (loop $forever (br $forever))
+                    (i32.const 0)))
+        "#;
+        let limits = ContractLimits {
+            fuel: 100_000,
+            ..Default::default()
+        };
+        let result = execute_wat(wat, &limits);
+        assert!(matches!(
+            result,
+            Err(ExecutorErrorResponses::ContractLimitExceeded(limit)) if limit == "fuel"
+        ));
+    }
+
+    #[test]
+    fn unbounded_memory_growth_exceeds_the_memory_limit() {
+        let wat = r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "main_function") (param i32 i32 i32) (result i32)
+                    (loop $forever
+                        (drop (memory.grow (i32.const 1)))
+                        (br $forever))
+                    (i32.const 0)))
+        "#;
+        let limits = ContractLimits {
+            memory: 1024 * 1024,
+            ..Default::default()
+        };
+        let result = execute_wat(wat, &limits);
+        assert!(matches!(
+            result,
+            Err(ExecutorErrorResponses::ContractLimitExceeded(limit)) if limit == "memory"
+        ));
+    }
+
+    #[test]
+    fn pointers_contain_the_paths_below_them() {
+        assert!(is_below_pointer("/a", "/a"));
+        assert!(is_below_pointer("/a/b", "/a"));
+        assert!(is_below_pointer("/a/b/0", "/a/b"));
+        assert!(is_below_pointer("/a", ""));
+        assert!(is_below_pointer("", ""));
+        assert!(!is_below_pointer("/ab", "/a"));
+        assert!(!is_below_pointer("/a", "/a/b"));
+        assert!(!is_below_pointer("/b", "/a"));
+        assert!(!is_below_pointer("", "/a"));
+    }
+
+    #[test]
+    fn modified_path_of_every_operation() {
+        let patch: Patch = serde_json::from_value(json!([
+            { "op": "add", "path": "/add", "value": 1 },
+            { "op": "remove", "path": "/remove" },
+            { "op": "replace", "path": "/replace", "value": 1 },
+            { "op": "move", "from": "/from", "path": "/move" },
+            { "op": "copy", "from": "/from", "path": "/copy" },
+            { "op": "test", "path": "/test", "value": 1 }
+        ]))
+        .unwrap();
+        let paths: Vec<&str> = patch.0.iter().map(modified_path).collect();
+        assert_eq!(
+            paths,
+            vec!["/add", "/remove", "/replace", "/move", "/copy", "/test"]
+        );
+        let changes = diff(&json!({ "a": 1, "b": 2 }), &json!({ "a": 2, "c": 3 }));
+        let mut paths: Vec<&str> = changes.0.iter().map(modified_path).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/a", "/b", "/c"]);
+    }
+
+    #[test]
+    fn builtin_contracts_only_modify_their_pointers() {
+        let executor = ContractExecutor::new(Engine::default());
+        let state = ValueWrapper(json!({ "a": 1, "ab": 1 }));
+        let pointers = Some(vec!["/a".to_owned()]);
+        let event = ValueWrapper(json!([{ "op": "replace", "path": "/a", "value": 2 }]));
+        let result = executor.execute_builtin_contract(
+            &state,
+            &event,
+            ContractMode::Patch,
+            pointers.clone(),
+        );
+        assert!(result.success);
+        assert!(!result.approval_required);
+        assert_eq!(result.final_state.0, json!({ "a": 2, "ab": 1 }));
+        let event = ValueWrapper(json!([{ "op": "replace", "path": "/ab", "value": 2 }]));
+        let result = executor.execute_builtin_contract(
+            &state,
+            &event,
+            ContractMode::Patch,
+            pointers.clone(),
+        );
+        assert!(!result.success);
+        let event = ValueWrapper(json!({ "a": 3, "ab": 1 }));
+        let result =
+            executor.execute_builtin_contract(&state, &event, ContractMode::Replace, pointers);
+        assert!(result.success);
+        assert!(!result.approval_required);
+        assert_eq!(result.final_state.0, event.0);
+        let event = ValueWrapper(json!({ "b": 1 }));
+        let result = executor.execute_builtin_contract(&state, &event, ContractMode::Replace, None);
+        assert!(result.success);
+        assert!(!result.approval_required);
     }
-    Ok(())
 }
@@ -1,36 +1,63 @@
+use std::sync::Arc;
+
 use json_patch::diff;
+use serde::Serialize;
 use serde_json::Value;
 use wasmtime::Engine;
 
 use crate::{
     commons::{
-        models::{evaluation::EvaluationRequest, HashId},
-        schema_handler::Schema,
+        models::{
+            evaluation::{ContractContext, EvaluationRequest},
+            state::{generate_subject_id, Subject},
+            timestamp::TimeStamp,
+            HashId,
+        },
+        schema_handler::{
+            get_governance_schema,
+            gov_models::{Contract as GovContract, ContractRole, Governance, Member, Who},
+            Schema,
+        },
     },
     database::DB,
-    evaluator::errors::ExecutorErrorResponses,
-    governance::GovernanceInterface,
-    identifier::DigestIdentifier,
-    request::FactRequest,
-    DatabaseCollection, Derivable, EvaluationResponse, EventRequest, ValueWrapper, DigestDerivator,
+    evaluator::{
+        dependencies::{DatabaseStateReader, SubjectStateReader},
+        errors::ExecutorErrorResponses,
+    },
+    governance::{inner_governance::namespace_contiene, GovernanceInterface},
+    identifier::{DigestIdentifier, KeyIdentifier},
+    request::{MigrateRequest, StartRequest},
+    utils::patch::apply_patch,
+    ContractLimits, DatabaseCollection, Derivable, DigestDerivator, EvaluationResponse,
+    EventRequest, FactSimulation, GovernanceDiagnostic, ValueWrapper,
 };
 
-use super::executor::{Contract, ContractExecutor, ContractResult};
+use super::executor::{
+    governance_state_errors, Contract, ContractEntryPoint, ContractExecutor, ContractResult,
+};
 use crate::database::Error as DbError;
 pub struct TapleRunner<C: DatabaseCollection, G: GovernanceInterface> {
-    database: DB<C>,
+    database: Arc<DB<C>>,
     executor: ContractExecutor,
     gov_api: G,
-    derivator: DigestDerivator
+    derivator: DigestDerivator,
+    limits: ContractLimits,
 }
 
-impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
-    pub fn new(database: DB<C>, engine: Engine, gov_api: G, derivator: DigestDerivator,) -> Self {
+impl<C: DatabaseCollection + 'static, G: GovernanceInterface> TapleRunner<C, G> {
+    pub fn new(
+        database: DB<C>,
+        engine: Engine,
+        gov_api: G,
+        derivator: DigestDerivator,
+        limits: ContractLimits,
+    ) -> Self {
         Self {
-            database,
+            database: Arc::new(database),
             executor: ContractExecutor::new(engine),
             gov_api,
-            derivator
+            derivator,
+            limits,
         }
     }
 
@@ -44,15 +71,54 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
     pub async fn execute_contract(
         &self,
         execute_contract: &EvaluationRequest,
-        state_data: &FactRequest,
     ) -> Result<EvaluationResponse, ExecutorErrorResponses> {
+        // Facts go to the main entry point, creations with payload to the init entry point and
+        // transfers, EOLs and migrations to their hooks, which receive the request as payload
+        let (subject_id, payload, entry_point) = match &execute_contract.event_request.content {
+            EventRequest::Fact(data) => (
+                data.subject_id.clone(),
+                data.payload.clone(),
+                ContractEntryPoint::Main,
+            ),
+            EventRequest::Transfer(data) => (
+                data.subject_id.clone(),
+                request_payload(data)?,
+                ContractEntryPoint::Transfer,
+            ),
+            EventRequest::EOL(data) => (
+                data.subject_id.clone(),
+                request_payload(data)?,
+                ContractEntryPoint::EOL,
+            ),
+            EventRequest::Migrate(data) => (
+                data.subject_id.clone(),
+                request_payload(data)?,
+                ContractEntryPoint::Migration,
+            ),
+            EventRequest::Create(StartRequest {
+                namespace,
+                schema_id,
+                public_key,
+                governance_id,
+                payload: Some(payload),
+                ..
+            }) if schema_id != "governance" => (
+                generate_subject_id(
+                    namespace,
+                    schema_id,
+                    public_key.to_str(),
+                    governance_id.to_str(),
+                    execute_contract.gov_version,
+                    self.derivator,
+                )?,
+                payload.clone(),
+                ContractEntryPoint::Init,
+            ),
+            _ => return Err(ExecutorErrorResponses::CreateRequestNotAllowed),
+        };
         // Check governance version
         let governance_id = if &execute_contract.context.schema_id == "governance" {
-            if let EventRequest::Fact(data) = &execute_contract.event_request.content {
-                data.subject_id.clone()
-            } else {
-                return Err(ExecutorErrorResponses::CreateRequestNotAllowed);
-            }
+            subject_id.clone()
         } else {
             execute_contract.context.governance_id.clone()
         };
@@ -72,6 +138,33 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
             // Nuestra gov es menor: no podemos hacer nada. Pedimos LCE al que nos lo envió
             return Err(ExecutorErrorResponses::OurGovIsLower);
         }
+        // Upgrades are evaluated by the contract of the version that the subject moves to
+        let (schema_version, upgrade) = match &execute_contract.event_request.content {
+            EventRequest::Migrate(MigrateRequest {
+                schema_version: Some(schema_version),
+                ..
+            }) => (*schema_version, true),
+            _ => (execute_contract.context.schema_version, false),
+        };
+        let mut identity = false;
+        if let Some(hook) = entry_point.hook() {
+            let declared = schema_contract(
+                &governance,
+                &execute_contract.context.schema_id,
+                schema_version,
+            )
+            .map(|contract| contract.mode.is_none() && contract.hooks.contains(&hook))
+            .unwrap_or(false);
+            if !declared && upgrade {
+                // Without a migration hook the state is kept as it is
+                identity = true;
+            } else if !declared {
+                return Err(ExecutorErrorResponses::ContractHookNotDeclared(
+                    execute_contract.context.schema_id.clone(),
+                    format!("{:?}", hook),
+                ));
+            }
+        }
 
         // Governances can be updated without WASM because we know the contract beforehand
         let (contract, contract_gov_version): (Contract, u64) = if execute_contract
@@ -81,10 +174,21 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
             && execute_contract.context.governance_id.digest.is_empty()
         {
             (Contract::GovContract, governance.sn)
+        } else if identity {
+            (Contract::Identity, governance.sn)
+        } else if let Some(contract) = self.builtin_contract(
+            &governance,
+            &execute_contract.context.schema_id,
+            schema_version,
+            &execute_contract.context.namespace,
+            &execute_contract.event_request.signature.signer,
+        ) {
+            (contract, governance.sn)
         } else {
             match self.database.get_contract(
                 &execute_contract.context.governance_id,
                 &execute_contract.context.schema_id,
+                schema_version,
             ) {
                 Ok((contract, _, contract_gov_version)) => {
                     (Contract::CompiledContract(contract), contract_gov_version)
@@ -101,14 +205,34 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
         if contract_gov_version != execute_contract.gov_version {
             return Err(ExecutorErrorResponses::ContractNotUpdated);
         }
+        let limits = self.contract_limits(
+            &governance,
+            &execute_contract.context.schema_id,
+            schema_version,
+        );
+        let contract_context = ContractContext {
+            subject_id: subject_id.to_str(),
+            governance_id: execute_contract.context.governance_id.to_str(),
+            schema_id: execute_contract.context.schema_id.clone(),
+            namespace: execute_contract.context.namespace.clone(),
+            invoker: execute_contract.event_request.signature.signer.to_str(),
+            is_owner: execute_contract.context.is_owner,
+            sn: execute_contract.sn,
+            gov_version: execute_contract.gov_version,
+            timestamp: execute_contract.event_request.signature.timestamp.0,
+        };
         let previous_state = &execute_contract.context.state.clone();
         let mut contract_result = match self
             .executor
             .execute_contract(
                 &execute_contract.context.state,
-                &state_data.payload,
+                &payload,
                 contract,
-                execute_contract.context.is_owner,
+                &contract_context,
+                &limits,
+                false,
+                Some(self.subject_reader(&execute_contract.context.governance_id)),
+                entry_point,
             )
             .await
         {
@@ -116,6 +240,7 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
             Err(error) => {
                 match error {
                     ExecutorErrorResponses::ContractExecutionFailed
+                    | ExecutorErrorResponses::ContractLimitExceeded(_)
                     | ExecutorErrorResponses::ContractNotInstantiated
                     | ExecutorErrorResponses::ContractNotFound(_, _)
                     | ExecutorErrorResponses::ContractEntryPointNotFound
@@ -130,6 +255,7 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
                     | ExecutorErrorResponses::CreateRequestNotAllowed
                     | ExecutorErrorResponses::GovernanceError(_)
                     | ExecutorErrorResponses::SchemaCompilationFailed
+                    | ExecutorErrorResponses::InvalidSubjectDependency(_)
                     | ExecutorErrorResponses::InvalidPointerPovided => {
                         return Ok(EvaluationResponse {
                             patch: ValueWrapper(serde_json::from_str("[]").map_err(|_| {
@@ -143,6 +269,7 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
                             eval_req_hash: context_hash,
                             eval_success: false,
                             appr_required: false,
+                            dependencies: vec![],
                         })
                     }
                     _ => return Err(error),
@@ -162,6 +289,7 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
                         &contract_result,
                         &governance_id,
                         execute_contract.context.schema_id.clone(),
+                        schema_version,
                         execute_contract.gov_version,
                     )
                     .await
@@ -195,14 +323,255 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
             eval_req_hash: context_hash,
             eval_success: contract_result.success,
             appr_required: contract_result.approval_required,
+            dependencies: contract_result.dependencies,
+        })
+    }
+
+    /// Executes a fact against the current state of a subject without generating an event.
+    pub async fn simulate_fact(
+        &self,
+        subject_id: &DigestIdentifier,
+        payload: &ValueWrapper,
+        invoker: &KeyIdentifier,
+    ) -> Result<FactSimulation, ExecutorErrorResponses> {
+        let subject = match self.database.get_subject(subject_id) {
+            Ok(subject) => subject,
+            Err(DbError::EntryNotFound) => {
+                return Err(ExecutorErrorResponses::SubjectNotFound(subject_id.to_str()))
+            }
+            Err(error) => return Err(ExecutorErrorResponses::DatabaseError(error.to_string())),
+        };
+        let (governance, contract) = if subject.governance_id.digest.is_empty() {
+            (subject.clone(), Contract::GovContract)
+        } else {
+            let governance = match self.database.get_subject(&subject.governance_id) {
+                Ok(governance) => governance,
+                Err(DbError::EntryNotFound) => {
+                    return Err(ExecutorErrorResponses::GovernanceNotFound);
+                }
+                Err(error) => return Err(ExecutorErrorResponses::DatabaseError(error.to_string())),
+            };
+            let builtin_contract = self.builtin_contract(
+                &governance,
+                &subject.schema_id,
+                subject.schema_version,
+                &subject.namespace,
+                invoker,
+            );
+            let contract = match builtin_contract {
+                Some(contract) => contract,
+                None => self.compiled_contract(&subject, &governance)?,
+            };
+            (governance, contract)
+        };
+        let limits = self.contract_limits(&governance, &subject.schema_id, subject.schema_version);
+        let contract_context = ContractContext {
+            subject_id: subject.subject_id.to_str(),
+            governance_id: subject.governance_id.to_str(),
+            schema_id: subject.schema_id.clone(),
+            namespace: subject.namespace.clone(),
+            invoker: invoker.to_str(),
+            is_owner: &subject.owner == invoker,
+            sn: subject.sn + 1,
+            gov_version: governance.sn,
+            timestamp: TimeStamp::now().0,
+        };
+        let contract_result = self
+            .executor
+            .execute_contract(
+                &subject.properties,
+                payload,
+                contract,
+                &contract_context,
+                &limits,
+                true,
+                Some(self.subject_reader(&subject.governance_id)),
+                ContractEntryPoint::Main,
+            )
+            .await?;
+        if !contract_result.success {
+            return Ok(FactSimulation {
+                success: false,
+                patch: ValueWrapper(serde_json::Value::Array(vec![])),
+                state: subject.properties,
+                approval_required: false,
+                valid_state: true,
+                logs: contract_result.logs,
+            });
+        }
+        let valid_state = self
+            .validation_state(
+                &contract_result,
+                &governance.subject_id,
+                subject.schema_id.clone(),
+                subject.schema_version,
+                governance.sn,
+            )
+            .await?;
+        Ok(FactSimulation {
+            success: true,
+            patch: ValueWrapper(generate_json_patch(
+                &subject.properties.0,
+                &contract_result.final_state.0,
+            )?),
+            state: contract_result.final_state,
+            approval_required: contract_result.approval_required,
+            valid_state,
+            logs: contract_result.logs,
         })
     }
 
+    /// Checks a governance document as the governance contract does. Returns every problem
+    /// found and the contracts of its schemas, with the JSON pointers to them, whose
+    /// compilation is left to the caller.
+    pub fn check_governance(
+        &self,
+        document: &ValueWrapper,
+    ) -> Result<(Vec<GovernanceDiagnostic>, Vec<(String, GovContract)>), ExecutorErrorResponses>
+    {
+        let schema = Schema::compile(&get_governance_schema())
+            .map_err(|_| ExecutorErrorResponses::SchemaCompilationFailed)?;
+        let mut diagnostics: Vec<GovernanceDiagnostic> = schema
+            .validation_errors(&document.0)
+            .into_iter()
+            .map(|(pointer, message)| GovernanceDiagnostic { pointer, message })
+            .collect();
+        let governance: Governance = match serde_json::from_value(document.0.clone()) {
+            Ok(governance) => governance,
+            Err(error) => {
+                // The JSON Schema already points to the parts of the document that can not be read
+                if diagnostics.is_empty() {
+                    diagnostics.push(GovernanceDiagnostic {
+                        pointer: String::new(),
+                        message: error.to_string(),
+                    });
+                }
+                return Ok((diagnostics, vec![]));
+            }
+        };
+        diagnostics.extend(governance_state_errors(&governance).into_iter().map(
+            |(pointer, error)| GovernanceDiagnostic {
+                pointer,
+                message: error.to_string(),
+            },
+        ));
+        let contracts = governance
+            .schemas
+            .into_iter()
+            .enumerate()
+            .map(|(index, schema)| (format!("/schemas/{}/contract", index), schema.contract))
+            .collect();
+        Ok((diagnostics, contracts))
+    }
+
+    /// Applies a patch to the current state of a governance, as a patch event of the
+    /// governance would do. A patch that can not be applied is a problem of the document.
+    pub fn patch_governance(
+        &self,
+        governance_id: &DigestIdentifier,
+        patch: &ValueWrapper,
+    ) -> Result<Result<ValueWrapper, GovernanceDiagnostic>, ExecutorErrorResponses> {
+        let governance = match self.database.get_subject(governance_id) {
+            Ok(governance) => governance,
+            Err(DbError::EntryNotFound) => return Err(ExecutorErrorResponses::GovernanceNotFound),
+            Err(error) => return Err(ExecutorErrorResponses::DatabaseError(error.to_string())),
+        };
+        if governance.schema_id != "governance" {
+            return Err(ExecutorErrorResponses::NotGovernance(
+                governance_id.to_str(),
+            ));
+        }
+        match apply_patch::<Value>(patch.0.clone(), governance.properties.0) {
+            Ok(document) => Ok(Ok(ValueWrapper(document))),
+            Err(error) => Ok(Err(GovernanceDiagnostic {
+                pointer: String::new(),
+                message: error.to_string(),
+            })),
+        }
+    }
+
+    /// Reader of the subjects of a governance, for contracts that depend on their states.
+    fn subject_reader(&self, governance_id: &DigestIdentifier) -> Box<dyn SubjectStateReader> {
+        Box::new(DatabaseStateReader::new(
+            self.database.clone(),
+            governance_id.clone(),
+            self.derivator,
+        ))
+    }
+
+    fn compiled_contract(
+        &self,
+        subject: &Subject,
+        governance: &Subject,
+    ) -> Result<Contract, ExecutorErrorResponses> {
+        match self.database.get_contract(
+            &subject.governance_id,
+            &subject.schema_id,
+            subject.schema_version,
+        ) {
+            Ok((contract, _, contract_gov_version)) => {
+                if contract_gov_version != governance.sn {
+                    return Err(ExecutorErrorResponses::ContractNotUpdated);
+                }
+                Ok(Contract::CompiledContract(contract))
+            }
+            Err(DbError::EntryNotFound) => Err(ExecutorErrorResponses::ContractNotFound(
+                subject.schema_id.clone(),
+                subject.governance_id.to_str(),
+            )),
+            Err(error) => Err(ExecutorErrorResponses::DatabaseError(error.to_string())),
+        }
+    }
+
+    /// Built-in contract of a schema, restricted to the pointers that the invoker may modify.
+    fn builtin_contract(
+        &self,
+        governance: &Subject,
+        schema_id: &str,
+        schema_version: u64,
+        namespace: &str,
+        invoker: &KeyIdentifier,
+    ) -> Option<Contract> {
+        let contract = schema_contract(governance, schema_id, schema_version)?;
+        let mode = contract.mode?;
+        let pointers = if contract.roles.is_empty() {
+            None
+        } else {
+            Some(writable_pointers(
+                governance,
+                &contract.roles,
+                namespace,
+                invoker,
+            ))
+        };
+        Some(Contract::BuiltinContract { mode, pointers })
+    }
+
+    /// Limits of the contract of a schema, as set by the governance or else by the node.
+    fn contract_limits(
+        &self,
+        governance: &Subject,
+        schema_id: &str,
+        schema_version: u64,
+    ) -> ContractLimits {
+        let mut limits = self.limits.clone();
+        if let Some(contract) = schema_contract(governance, schema_id, schema_version) {
+            if let Some(fuel) = contract.fuel {
+                limits.fuel = fuel;
+            }
+            if let Some(memory) = contract.memory {
+                limits.memory = memory;
+            }
+        }
+        limits
+    }
+
     async fn validation_state(
         &self,
         contract_result: &ContractResult,
         governance_id: &DigestIdentifier,
         schema_id: String,
+        schema_version: u64,
         governance_version: u64,
     ) -> Result<bool, ExecutorErrorResponses> {
         if contract_result.success {
@@ -210,7 +579,12 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
             // Comprobar el estado contra el esquema definido en la gobernanza
             let schema = self
                 .gov_api
-                .get_schema(governance_id.clone(), schema_id, governance_version)
+                .get_schema(
+                    governance_id.clone(),
+                    schema_id,
+                    schema_version,
+                    governance_version,
+                )
                 .await?;
             let schema = Schema::compile(&schema.0)
                 .map_err(|_| ExecutorErrorResponses::SchemaCompilationFailed)?;
@@ -224,6 +598,61 @@ impl<C: DatabaseCollection, G: GovernanceInterface> TapleRunner<C, G> {
     }
 }
 
+/// Payload received by the hooks of a contract, which is the request that they evaluate.
+fn request_payload<T: Serialize>(request: &T) -> Result<ValueWrapper, ExecutorErrorResponses> {
+    serde_json::to_value(request)
+        .map(ValueWrapper)
+        .map_err(|_| ExecutorErrorResponses::ValueToStringConversionFailed)
+}
+
+fn schema_contract(
+    governance: &Subject,
+    schema_id: &str,
+    schema_version: u64,
+) -> Option<GovContract> {
+    governance.properties.0["schemas"]
+        .as_array()
+        .and_then(|schemas| {
+            schemas.iter().find(|schema| {
+                schema["id"] == schema_id
+                    && schema["version"].as_u64().unwrap_or(0) == schema_version
+            })
+        })
+        .and_then(|schema| serde_json::from_value::<GovContract>(schema["contract"].clone()).ok())
+}
+
+/// Pointers granted to the invoker by the roles of a built-in contract that it matches.
+fn writable_pointers(
+    governance: &Subject,
+    roles: &[ContractRole],
+    namespace: &str,
+    invoker: &KeyIdentifier,
+) -> Vec<String> {
+    let members: Vec<Member> =
+        serde_json::from_value(governance.properties.0["members"].clone()).unwrap_or_default();
+    let invoker = invoker.to_str();
+    let is_member = members.iter().any(|member| member.id == invoker);
+    let mut pointers = Vec::new();
+    for role in roles {
+        if !namespace_contiene(&role.namespace, namespace) {
+            continue;
+        }
+        let matches = match &role.who {
+            Who::ID { ID } => is_member && ID == &invoker,
+            Who::NAME { NAME } => members
+                .iter()
+                .any(|member| &member.name == NAME && member.id == invoker),
+            Who::MEMBERS => is_member,
+            Who::ALL => true,
+            Who::NOT_MEMBERS => !is_member,
+        };
+        if matches {
+            pointers.extend(role.pointers.iter().cloned());
+        }
+    }
+    pointers
+}
+
 fn generate_json_patch(
     prev_state: &Value,
     new_state: &Value,
@@ -232,3 +661,155 @@ fn generate_json_patch(
     Ok(serde_json::to_value(&patch)
         .map_err(|_| ExecutorErrorResponses::JSONPATCHDeserializationFailed)?)
 }
+
+#[cfg(test)]
+mod test {
+    use std::{str::FromStr, sync::Arc};
+
+    use serde_json::json;
+    use wasmtime::Engine;
+
+    use super::{writable_pointers, TapleRunner};
+    use crate::{
+        commons::{
+            channel::MpscChannel,
+            crypto::{Ed25519KeyPair, KeyGenerator, KeyMaterial},
+            models::state::Subject,
+            schema_handler::{
+                gov_models::{ContractRole, Who},
+                initial_state::get_governance_initial_state,
+            },
+        },
+        database::DB,
+        evaluator::errors::ExecutorErrorResponses,
+        governance::GovernanceAPI,
+        identifier::{DigestIdentifier, KeyIdentifier},
+        utils::test_utils::key,
+        ContractLimits, DigestDerivator, MemoryCollection, MemoryManager, ValueWrapper,
+    };
+
+    const GOVERNANCE_ID: &str = "JGSPR6FL-vE7iZxWMd17o09qn7NeTqlcImDVWmijXczw";
+    const SUBJECT_ID: &str = "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs";
+
+    fn subject(subject_id: &str, schema_id: &str) -> Subject {
+        let keypair = Ed25519KeyPair::from_seed(&[0]);
+        let key = KeyIdentifier::new(crate::KeyDerivator::Ed25519, &keypair.public_key_bytes());
+        Subject {
+            keys: None,
+            subject_id: DigestIdentifier::from_str(subject_id).unwrap(),
+            governance_id: DigestIdentifier::from_str("").unwrap(),
+            sn: 0,
+            genesis_gov_version: 0,
+            public_key: key.clone(),
+            namespace: "".into(),
+            name: "".into(),
+            schema_id: schema_id.into(),
+            schema_version: 0,
+            owner: key.clone(),
+            creator: key,
+            properties: get_governance_initial_state(),
+            active: true,
+        }
+    }
+
+    fn build_runner() -> TapleRunner<MemoryCollection, GovernanceAPI> {
+        let database = DB::new(Arc::new(MemoryManager::new()));
+        for (subject_id, schema_id) in [(GOVERNANCE_ID, "governance"), (SUBJECT_ID, "test")] {
+            database
+                .set_subject(
+                    &DigestIdentifier::from_str(subject_id).unwrap(),
+                    subject(subject_id, schema_id),
+                )
+                .unwrap();
+        }
+        let (_receiver, sender) = MpscChannel::new(10);
+        TapleRunner::new(
+            database,
+            Engine::default(),
+            GovernanceAPI::new(sender),
+            DigestDerivator::Blake3_256,
+            ContractLimits::default(),
+        )
+    }
+
+    #[test]
+    fn unappliable_governance_patch_is_diagnosed() {
+        let runner = build_runner();
+        let governance_id = DigestIdentifier::from_str(GOVERNANCE_ID).unwrap();
+        let patch = ValueWrapper(json!([{ "op": "remove", "path": "/missing" }]));
+        let diagnostic = runner
+            .patch_governance(&governance_id, &patch)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(diagnostic.pointer, "");
+        let patch = ValueWrapper(json!([{ "op": "add", "path": "/members/0", "value": {} }]));
+        let document = runner
+            .patch_governance(&governance_id, &patch)
+            .unwrap()
+            .unwrap();
+        assert_eq!(document.0["members"], json!([{}]));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        assert!(matches!(
+            runner.patch_governance(&subject_id, &patch),
+            Err(ExecutorErrorResponses::NotGovernance(_))
+        ));
+    }
+
+    #[test]
+    fn inconsistent_governance_is_diagnosed() {
+        let runner = build_runner();
+        let (diagnostics, contracts) = runner
+            .check_governance(&get_governance_initial_state())
+            .unwrap();
+        assert!(diagnostics.is_empty());
+        assert!(contracts.is_empty());
+        // The document follows the JSON Schema, but the role refers to an unknown member
+        let mut document = get_governance_initial_state();
+        document.0["roles"][0]["who"] = json!({ "ID": SUBJECT_ID });
+        let (diagnostics, _) = runner.check_governance(&document).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pointer, "/roles/0/who");
+        assert_eq!(
+            diagnostics[0].message,
+            "ID specified for Role::Who does not exist"
+        );
+    }
+
+    fn role(who: Who, namespace: &str, pointer: &str) -> ContractRole {
+        ContractRole {
+            who,
+            namespace: namespace.into(),
+            pointers: vec![pointer.into()],
+        }
+    }
+
+    #[test]
+    fn writable_pointers_follow_the_roles_of_the_invoker() {
+        let mut governance = subject(GOVERNANCE_ID, "governance");
+        governance.properties.0["members"] = json!([
+            { "id": key(1).to_str(), "name": "Alice" },
+            { "id": key(2).to_str(), "name": "Bob" }
+        ]);
+        let id = key(1).to_str();
+        let roles = vec![
+            role(Who::ID { ID: id }, "", "/id"),
+            role(Who::NAME { NAME: "Bob".into() }, "", "/name"),
+            role(Who::MEMBERS, "", "/members"),
+            role(Who::NOT_MEMBERS, "", "/not_members"),
+            role(Who::ALL, "", "/all"),
+            role(Who::ALL, "a", "/namespace"),
+        ];
+        let pointers = |invoker: u8, namespace: &str| {
+            writable_pointers(&governance, &roles, namespace, &key(invoker))
+        };
+        assert_eq!(pointers(1, ""), vec!["/id", "/members", "/all"]);
+        assert_eq!(pointers(2, ""), vec!["/name", "/members", "/all"]);
+        assert_eq!(pointers(3, ""), vec!["/not_members", "/all"]);
+        assert_eq!(
+            pointers(3, "a.b"),
+            vec!["/not_members", "/all", "/namespace"]
+        );
+        assert_eq!(pointers(3, "b"), vec!["/not_members", "/all"]);
+        assert!(writable_pointers(&governance, &[], "", &key(1)).is_empty());
+    }
+}
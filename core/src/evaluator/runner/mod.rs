@@ -1,3 +1,4 @@
 mod context;
+mod dependencies;
 mod executor;
 pub mod manager;
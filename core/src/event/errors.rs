@@ -70,4 +70,14 @@ pub enum EventError {
     ApprovalRequiredWhenEvalFailed,
     #[error("Invalid specify public key")]
     PublicKeyIsEmpty,
+    #[error("Request not found: {0}")]
+    RequestNotFound(String),
+    #[error("Request is not in progress: {0}")]
+    RequestNotInProgress(String),
+    #[error("The event of request {0} is being validated")]
+    RequestInValidation(String),
+    #[error("The schema of subject {0} can not be compiled")]
+    SchemaNotCompiled(String),
+    #[error("Subject {0} can not be upgraded to version {1} of its schema")]
+    InvalidSchemaVersion(String, u64),
 }
@@ -1,45 +1,55 @@
 use std::collections::{HashMap, HashSet};
 
-use json_patch::{patch, Patch};
+use json_patch::{diff, patch, Patch};
 use log::warn;
+use serde_json::json;
 
 use crate::{
+    approval::ApprovalMessages,
     commons::{
         channel::SenderEnd,
         models::{
             approval::UniqueApproval,
-            evaluation::{EvaluationRequest, SubjectContext},
+            evaluation::{EvaluationRequest, SubjectContext, SubjectDependency},
             event::Event,
             event::Metadata,
+            request::MigrateRequest,
             state::{generate_subject_id, Subject},
             validation::ValidationProof,
             HashId,
         },
+        schema_handler::{gov_models::ContractHook, Schema},
         self_signature_manager::SelfSignatureManager,
     },
     crypto::KeyPair,
-    governance::{stage::ValidationStage, GovernanceAPI, GovernanceInterface},
+    evaluator::EvaluatorMessage,
+    governance::{
+        error::RequestError, stage::ValidationStage, votes_weight, GovernanceAPI,
+        GovernanceInterface, SignerWeights,
+    },
     identifier::{Derivable, DigestIdentifier, KeyIdentifier},
     ledger::{LedgerCommand, LedgerResponse},
-    message::{MessageConfig, MessageTaskCommand},
+    message::{MessageConfig, MessageTaskCommand, TaskExhausted},
     protocol::protocol_message_manager::TapleMessages,
     request::StartRequest,
-    request::TapleRequest,
+    request::{RequestFailure, RequestStage, RequestState, TapleRequest},
     signature::{Signature, Signed, UniqueSignature},
     utils::message::{
-        approval::create_approval_request, evaluator::create_evaluator_request,
-        ledger::request_gov_event, validation::create_validator_request,
+        approval::create_approval_request,
+        evaluator::create_evaluator_request,
+        ledger::{lce_task_id, request_gov_event},
+        validation::create_validator_request,
     },
     validation::ValidationEvent,
     ApprovalRequest, ApprovalResponse, DatabaseCollection, DigestDerivator, EvaluationResponse,
-    EventRequest, Notification, ValueWrapper,
+    EventRequest, Notification, RetryPolicies, RetryPolicy, ValueWrapper,
 };
 use std::hash::Hash;
+use std::str::FromStr;
 
 use super::errors::EventError;
 use crate::database::DB;
 
-const TIMEOUT: u32 = 2000;
 // const GET_ALL: isize = 200;
 const QUORUM_PORCENTAGE_AMPLIFICATION: f64 = 0.2;
 
@@ -54,6 +64,8 @@ pub struct EventCompleter<C: DatabaseCollection> {
     subjects_by_governance: HashMap<DigestIdentifier, HashSet<DigestIdentifier>>,
     subjects_completing_event:
         HashMap<DigestIdentifier, (ValidationStage, HashSet<KeyIdentifier>, (u32, u32))>,
+    // Weight of the votes of the signers of the current stage of each subject
+    signer_weights: HashMap<DigestIdentifier, SignerWeights>,
     // actual_sn: HashMap<DigestIdentifier, u64>,
     // virtual_state: HashMap<DigestIdentifier, Value>,
     // Evaluation HashMaps
@@ -61,15 +73,20 @@ pub struct EventCompleter<C: DatabaseCollection> {
     event_evaluations:
         HashMap<DigestIdentifier, HashSet<(UniqueSignature, bool, DigestIdentifier)>>,
     // Approval HashMaps
-    approval_eval_signatures: HashMap<DigestIdentifier, HashSet<Signature>>,
+    approval_eval_signatures:
+        HashMap<DigestIdentifier, (HashSet<Signature>, Vec<SubjectDependency>)>,
     approval_requests: HashMap<DigestIdentifier, Signed<ApprovalRequest>>,
     event_approvations: HashMap<DigestIdentifier, HashSet<UniqueApproval>>,
     // Validation HashMaps
     events_to_validate: HashMap<DigestIdentifier, Signed<Event>>,
     event_validations: HashMap<DigestIdentifier, HashSet<UniqueSignature>>,
     event_validation_events: HashMap<DigestIdentifier, ValidationEvent>,
+    // Requests waiting for the migration of the state of their subject
+    requests_awaiting_migration: HashMap<DigestIdentifier, Signed<EventRequest>>,
     // SignatureManager
     signature_manager: SelfSignatureManager,
+    retry_policies: RetryPolicies,
+    all_at_once_signers: usize,
     derivator: DigestDerivator,
 }
 
@@ -83,6 +100,8 @@ impl<C: DatabaseCollection> EventCompleter<C> {
         ledger_sender: SenderEnd<LedgerCommand, LedgerResponse>,
         own_identifier: KeyIdentifier,
         signature_manager: SelfSignatureManager,
+        retry_policies: RetryPolicies,
+        all_at_once_signers: usize,
         derivator: DigestDerivator,
     ) -> Self {
         Self {
@@ -92,6 +111,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             notification_tx,
             ledger_sender,
             subjects_completing_event: HashMap::new(),
+            signer_weights: HashMap::new(),
             // actual_sn: HashMap::new(),
             // virtual_state: HashMap::new(),
             event_pre_evaluations: HashMap::new(),
@@ -103,8 +123,11 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             event_validations: HashMap::new(),
             subjects_by_governance: HashMap::new(),
             event_validation_events: HashMap::new(),
+            requests_awaiting_migration: HashMap::new(),
             own_identifier,
             signature_manager,
+            retry_policies,
+            all_at_once_signers,
             derivator,
         }
     }
@@ -116,12 +139,14 @@ impl<C: DatabaseCollection> EventCompleter<C> {
         governance_version: u64,
         subject_id: DigestIdentifier,
         subject_keys: &KeyPair,
+        dependencies: Vec<SubjectDependency>,
     ) -> Result<ValidationEvent, EventError> {
         let validation_proof = ValidationProof::new_from_genesis_event(
             create_request,
             event_hash,
             governance_version,
             subject_id,
+            dependencies,
         );
         let subject_signature = Signature::new(&validation_proof, subject_keys, self.derivator)?;
         Ok(ValidationEvent {
@@ -138,16 +163,31 @@ impl<C: DatabaseCollection> EventCompleter<C> {
         event: &Signed<Event>,
         gov_version: u64,
     ) -> Result<ValidationEvent, EventError> {
+        if let EventRequest::Create(create_request) = &event.content.event_request.content {
+            let Some(keys) = &subject.keys else {
+                return Err(EventError::SubjectNotOwned(subject.subject_id.to_str()));
+            };
+            return self.create_validation_event_from_genesis(
+                create_request.clone(),
+                event.content.hash_id(self.derivator)?,
+                event.content.gov_version,
+                subject.subject_id.clone(),
+                keys,
+                event.content.dependencies.clone(),
+            );
+        }
         let proof = match &event.content.event_request.content {
-            EventRequest::Create(_) | EventRequest::Fact(_) | EventRequest::EOL(_) => {
-                ValidationProof::new(
-                    subject,
-                    event.content.sn,
-                    event.content.hash_prev_event.clone(),
-                    event.content.hash_id(self.derivator)?,
-                    gov_version,
-                )
-            }
+            EventRequest::Create(_)
+            | EventRequest::Fact(_)
+            | EventRequest::EOL(_)
+            | EventRequest::Migrate(_) => ValidationProof::new(
+                subject,
+                event.content.sn,
+                event.content.hash_prev_event.clone(),
+                event.content.hash_id(self.derivator)?,
+                gov_version,
+                event.content.dependencies.clone(),
+            ),
             EventRequest::Transfer(transfer_request) => ValidationProof::new_from_transfer_event(
                 subject,
                 event.content.sn,
@@ -155,6 +195,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 event.content.hash_id(self.derivator)?,
                 gov_version,
                 transfer_request.public_key.clone(),
+                event.content.dependencies.clone(),
             ),
         };
         let (prev_event_validation_signatures, previous_proof) = {
@@ -277,6 +318,35 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             // self.actual_sn
             //     .insert(subject.subject_id.to_owned(), last_event_sn);
         }
+        self.restore_requests_awaiting_migration().await
+    }
+
+    /// Restores the requests that were waiting for the migration of the state of their
+    /// subject. They wait again for the migration if it is still in progress and are processed
+    /// otherwise, since the migration finished before the restart.
+    async fn restore_requests_awaiting_migration(&mut self) -> Result<(), EventError> {
+        for request in self.database.get_taple_all_request() {
+            if request.state != RequestState::Processing
+                || request.current_stage() != Some(&RequestStage::Migration)
+            {
+                continue;
+            }
+            let Some(subject_id) = request.subject_id else {
+                continue;
+            };
+            let migrating = matches!(
+                self.pending_event_request(&subject_id)?,
+                Some(Signed {
+                    content: EventRequest::Migrate(_),
+                    ..
+                })
+            );
+            self.requests_awaiting_migration
+                .insert(subject_id.clone(), request.event_request);
+            if !migrating {
+                self.resume_after_migration(&subject_id).await;
+            }
+        }
         Ok(())
     }
 
@@ -291,9 +361,15 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 for subject_id in subjects_affected.iter() {
                     match self.database.get_request(subject_id) {
                         Ok(event_request) => {
-                            let EventRequest::Fact(_) = &event_request.content else {
-                                return Err(EventError::GenesisInGovUpdate);
-                            };
+                            if let EventRequest::Create(_) = &event_request.content {
+                                // The id of the subject depends on the governance version, so
+                                // the creation is started again
+                                self.discard_event(subject_id, &event_request).await?;
+                                self.new_event(event_request).await?;
+                                continue;
+                            }
+                            // Facts, migrations and the transfers and EOLs evaluated by the
+                            // hooks of their schema are evaluated again
                             self.new_event(event_request).await?;
                         }
                         Err(error) => match error {
@@ -317,6 +393,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                                     .await
                                     .map_err(EventError::ChannelError)?;
                                 self.subjects_completing_event.remove(&subject_id);
+                                self.signer_weights.remove(&subject_id);
                                 self.subjects_by_governance.remove(&subject_id);
                                 self.database.del_prevalidated_event(&subject_id).map_err(
                                     |error| EventError::DatabaseError(error.to_string()),
@@ -401,6 +478,31 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             governance_version: gov_version,
             schema_id: subject.schema_id.clone(),
         };
+        self.check_transfer_or_eol(&event_request, &subject)?;
+        // Add to the hashmap to be able to access it when the validator signatures arrive.
+        let event =
+            &self.create_event_prevalidated_no_eval(event_request, &subject, gov_version)?;
+        let event_hash = DigestIdentifier::from_serializable_borsh(&event.content, self.derivator)
+            .map_err(|_| EventError::CryptoError("Error generating event hash".to_owned()))?;
+        let validation_event = self.create_validation_event(&subject, &event, gov_version)?;
+        let event_message = create_validator_request(validation_event.clone());
+        self.event_validation_events
+            .insert(event_hash, validation_event);
+        let stage = ValidationStage::Validate;
+        let (signers, quorum_size) = self.get_signers_and_quorum(metadata, stage.clone()).await?;
+        self.ask_signatures(&subject_id, event_message, signers.clone(), quorum_size)
+            .await?;
+        // Make update of the phase the event is going through
+        self.subjects_completing_event
+            .insert(subject_id.clone(), (stage, signers, (quorum_size, 0)));
+        Ok(())
+    }
+
+    fn check_transfer_or_eol(
+        &self,
+        event_request: &Signed<EventRequest>,
+        subject: &Subject,
+    ) -> Result<(), EventError> {
         match &event_request.content {
             EventRequest::Transfer(tr) => {
                 if event_request.signature.signer == self.own_identifier {
@@ -418,25 +520,32 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             }
             _ => unreachable!(),
         }
-        // Add to the hashmap to be able to access it when the validator signatures arrive.
-        let event =
-            &self.create_event_prevalidated_no_eval(event_request, &subject, gov_version)?;
-        let event_hash = DigestIdentifier::from_serializable_borsh(&event.content, self.derivator)
-            .map_err(|_| EventError::CryptoError("Error generating event hash".to_owned()))?;
-        let validation_event = self.create_validation_event(&subject, &event, gov_version)?;
-        let event_message = create_validator_request(validation_event.clone());
-        self.event_validation_events
-            .insert(event_hash, validation_event);
-        let stage = ValidationStage::Validate;
-        let (signers, quorum_size) = self.get_signers_and_quorum(metadata, stage.clone()).await?;
-        self.ask_signatures(&subject_id, event_message, signers.clone(), quorum_size)
-            .await?;
-        // Make update of the phase the event is going through
-        self.subjects_completing_event
-            .insert(subject_id.clone(), (stage, signers, (quorum_size, 0)));
         Ok(())
     }
 
+    /// Whether the contract of the schema of the subject evaluates the request through a hook.
+    async fn contract_hook_declared(
+        &self,
+        event_request: &EventRequest,
+        subject: &Subject,
+        gov_version: u64,
+    ) -> Result<bool, EventError> {
+        let Some(hook) = event_request.contract_hook() else {
+            return Ok(false);
+        };
+        let hooks = self
+            .gov_api
+            .get_contract_hooks(
+                subject.governance_id.clone(),
+                subject.schema_id.clone(),
+                subject.schema_version,
+                gov_version,
+            )
+            .await
+            .map_err(EventError::GovernanceError)?;
+        Ok(hooks.contains(&hook))
+    }
+
     async fn generate_event_proposal(
         &self,
         event_request: &Signed<EventRequest>,
@@ -516,6 +625,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             EventRequest::Fact(fact_req) => &fact_req.subject_id,
             EventRequest::Transfer(trans_req) => &trans_req.subject_id,
             EventRequest::EOL(eol_req) => &eol_req.subject_id,
+            EventRequest::Migrate(migrate_req) => &migrate_req.subject_id,
         };
         // Check if we already have an event for that subject
         let None = self.subjects_completing_event.get(subject_id) else {
@@ -539,6 +649,10 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             if create_request.public_key.public_key.is_empty() {
                 return Err(EventError::PublicKeyIsEmpty);
             }
+            // Governances are created with the initial state of their schema
+            if create_request.payload.is_some() && create_request.schema_id == "governance" {
+                return Err(EventError::EvaluationOrApprovationInCreationEvent);
+            }
             // Check if i have the keys
             let subject_keys = match self.database.get_keys(&create_request.public_key) {
                 Ok(keys) => keys,
@@ -596,6 +710,8 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                     .await?;
                 (0, initial_state)
             };
+            // Subjects are created with the latest version of their schema
+            let (initial_state, schema_version) = initial_state;
             let subject_id = generate_subject_id(
                 &create_request.namespace,
                 &create_request.schema_id,
@@ -604,6 +720,70 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 governance_version,
                 self.derivator,
             )?;
+            if create_request.payload.is_some() {
+                // The initial state is built by the init entry point of the schema contract, so
+                // the genesis event is evaluated like a fact
+                let metadata = Metadata {
+                    namespace: create_request.namespace.clone(),
+                    subject_id: subject_id.clone(),
+                    governance_id: create_request.governance_id.clone(),
+                    governance_version,
+                    schema_id: create_request.schema_id.clone(),
+                };
+                let event_preevaluation = EvaluationRequest {
+                    event_request: event_request.clone(),
+                    context: SubjectContext {
+                        governance_id: create_request.governance_id.clone(),
+                        schema_id: create_request.schema_id.clone(),
+                        schema_version,
+                        is_owner: true,
+                        state: initial_state,
+                        namespace: create_request.namespace.clone(),
+                    },
+                    gov_version: governance_version,
+                    sn: 0,
+                };
+                let stage = ValidationStage::Evaluate;
+                let (signers, quorum_size) =
+                    self.get_signers_and_quorum(metadata, stage.clone()).await?;
+                let event_preevaluation_hash =
+                    DigestIdentifier::generate_with_blake3(&event_preevaluation).map_err(|_| {
+                        EventError::CryptoError(String::from(
+                            "Error calculating the hash of the event pre-evaluation",
+                        ))
+                    })?;
+                self.event_pre_evaluations
+                    .insert(event_preevaluation_hash, event_preevaluation.clone());
+                let negative_quorum_size =
+                    negative_quorum(self.signer_weights.get(&subject_id), &signers, quorum_size);
+                self.subjects_completing_event.insert(
+                    subject_id.clone(),
+                    (stage, signers.clone(), (quorum_size, negative_quorum_size)),
+                );
+                self.ask_signatures(
+                    &subject_id,
+                    create_evaluator_request(event_preevaluation),
+                    signers,
+                    quorum_size,
+                )
+                .await?;
+                self.subjects_by_governance
+                    .entry(create_request.governance_id.clone())
+                    .or_insert_with(HashSet::new)
+                    .insert(subject_id.clone());
+                let mut request_data: TapleRequest = event_request.clone().try_into()?;
+                request_data.sn = Some(0);
+                request_data.subject_id = Some(subject_id.clone());
+                request_data.start_stage(RequestStage::Evaluation);
+                self.database
+                    .set_taple_request(&request_id, &request_data)
+                    .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+                self.notify_request_update(&request_data).await;
+                self.database
+                    .set_request(&subject_id, event_request)
+                    .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+                return Ok(request_id);
+            }
             // Once everything goes well, we create the pre-validated event and send it to validation.
             let event = Signed::<Event>::from_genesis_request(
                 event_request.clone(),
@@ -622,6 +802,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 governance_version,
                 subject_id.clone(),
                 &subject_keys,
+                vec![],
             )?;
             let metadata = validation_event.proof.get_metadata();
             let event_message = create_validator_request(validation_event.clone());
@@ -639,9 +820,14 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 .insert(event_hash.clone(), validation_event);
             // Make update of the phase the event is going through
             self.events_to_validate.insert(event_hash, event.clone());
+            let mut request_data: TapleRequest = event_request.clone().try_into()?;
+            request_data.sn = Some(0);
+            request_data.subject_id = Some(subject_id.clone());
+            request_data.start_stage(RequestStage::Validation);
             self.database
-                .set_taple_request(&request_id, &event_request.clone().try_into()?)
+                .set_taple_request(&request_id, &request_data)
                 .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+            self.notify_request_update(&request_data).await;
             self.database
                 .set_prevalidated_event(&subject_id, event.clone())
                 .map_err(|error| EventError::DatabaseError(error.to_string()))?;
@@ -668,6 +854,10 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 log::info!("Processing state event");
                 sr.subject_id.clone()
             }
+            EventRequest::Migrate(mr) => {
+                log::info!("Processing migration event");
+                mr.subject_id.clone()
+            }
             _ => unreachable!(),
         };
         // Comprobamos si tenemos el sujeto
@@ -694,34 +884,50 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
             .await
             .map_err(EventError::GovernanceError)?;
-        match &event_request.content {
+        let metadata = Metadata {
+            namespace: subject.namespace.clone(),
+            subject_id: subject_id.clone(),
+            governance_id: subject.governance_id.clone(),
+            governance_version: gov_version,
+            schema_id: subject.schema_id.clone(),
+        };
+        // The state of the subject is migrated before anything else is done with it
+        if self
+            .subject_needs_migration(&event_request.content, &subject, gov_version)
+            .await?
+        {
+            return self
+                .start_migration(event_request, &subject, metadata)
+                .await;
+        }
+        let evaluated = match &event_request.content {
             EventRequest::Transfer(_) | EventRequest::EOL(_) => {
                 // TRANSFER
                 // We must remove the cryptographic material of the current subject and change its public key.
                 // However, the event must be signed with the current key, so it cannot be deleted
                 // immediately. It must therefore be deleted after validation.
-                // These events are neither evaluated nor approved.
+                // These events are only evaluated, and approved if the contract asks for it, when
+                // the contract of the schema declares a hook for them.
                 // It is not necessary to check the governance, as no permissions are required for the transfer.
-                self.process_transfer_or_eol_event(
-                    event_request.clone(),
-                    subject.clone(),
-                    gov_version,
-                )
-                .await?;
+                if self
+                    .contract_hook_declared(&event_request.content, &subject, gov_version)
+                    .await?
+                {
+                    self.check_transfer_or_eol(&event_request, &subject)?;
+                    self.ask_for_evaluation(&event_request, &subject, metadata)
+                        .await?;
+                    true
+                } else {
+                    self.process_transfer_or_eol_event(
+                        event_request.clone(),
+                        subject.clone(),
+                        gov_version,
+                    )
+                    .await?;
+                    false
+                }
             }
-            EventRequest::Fact(_) => {
-                // Request evaluation signatures, sending request, sn and signature of everything about the subject
-                // Get the list of evaluators
-                let (metadata, stage) = (
-                    Metadata {
-                        namespace: subject.namespace,
-                        subject_id: subject_id.clone(),
-                        governance_id: subject.governance_id.clone(),
-                        governance_version: gov_version,
-                        schema_id: subject.schema_id,
-                    },
-                    ValidationStage::Evaluate,
-                );
+            EventRequest::Fact(_) | EventRequest::Migrate(_) => {
                 // Check the invoker can Invoke for this subject
                 if event_request.signature.signer != self.own_identifier
                     && !self
@@ -739,152 +945,337 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                         subject_id.to_str(),
                     ));
                 };
-                let event_preevaluation = EvaluationRequest {
-                    event_request: event_request.clone(),
-                    context: SubjectContext {
-                        governance_id: metadata.governance_id.clone(),
-                        schema_id: metadata.schema_id.clone(),
-                        is_owner: subject.owner == event_request.signature.signer,
-                        state: subject.properties,
-                        // serde_json::to_string(self.virtual_state.get(&subject_id).unwrap())
-                        //     .map_err(|_| EventError::ErrorParsingValue)?, // Must be Some, filled in init function
-                        namespace: metadata.namespace.clone(),
-                    },
-                    gov_version,
-                    sn: subject.sn + 1,
-                    // self.actual_sn.get(&subject_id).unwrap().to_owned() + 1, // Must be Some, filled in init function
-                };
-                let (signers, quorum_size) =
-                    self.get_signers_and_quorum(metadata, stage.clone()).await?;
-                // log::info!(
-                //     "{} PIDIENDO FIRMAS DE EVALUACIÓN {} PARA: {}",
-                //     subject.sn + 1,
-                //     quorum_size,
-                //     subject.subject_id.to_str()
-                // );
-                // log::info!("SIGNERS::::");
-                // for signer in signers.iter() {
-                //     log::warn!("{}", signer.to_str());
-                // }
-                let event_preevaluation_hash =
-                    DigestIdentifier::generate_with_blake3(&event_preevaluation).map_err(|_| {
-                        EventError::CryptoError(String::from(
-                            "Error calculating the hash of the event pre-evaluation",
-                        ))
-                    })?;
-                self.event_pre_evaluations
-                    .insert(event_preevaluation_hash, event_preevaluation.clone());
-                // Add the event to the hashset to not complete two at the same time for the same subject
-                let negative_quorum_size = (signers.len() as u32 - quorum_size) + 1;
-                self.subjects_completing_event.insert(
-                    subject_id.clone(),
-                    (stage, signers.clone(), (quorum_size, negative_quorum_size)),
-                );
-                self.ask_signatures(
-                    &subject_id,
-                    create_evaluator_request(event_preevaluation.clone()),
-                    signers.clone(),
-                    quorum_size,
-                )
-                .await?;
+                if let EventRequest::Migrate(MigrateRequest {
+                    schema_version: Some(schema_version),
+                    ..
+                }) = &event_request.content
+                {
+                    self.check_schema_upgrade(&subject, *schema_version, gov_version)
+                        .await?;
+                }
+                self.ask_for_evaluation(&event_request, &subject, metadata)
+                    .await?;
+                true
             }
             EventRequest::Create(_) => unreachable!(),
-        }
+        };
         self.subjects_by_governance
             .entry(subject.governance_id.clone())
             .or_insert_with(HashSet::new)
-            .insert(subject.governance_id);
-        let mut request_data: TapleRequest = event_request.clone().try_into()?;
-        request_data.sn = Some(subject.sn + 1);
-        request_data.subject_id = Some(subject.subject_id.clone());
-        self.database
-            .set_taple_request(&request_id, &request_data)
-            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+            .insert(subject.governance_id.clone());
+        self.register_request(
+            &event_request,
+            &subject,
+            if evaluated {
+                RequestStage::Evaluation
+            } else {
+                RequestStage::Validation
+            },
+        )
+        .await?;
         self.database
             .set_request(&subject.subject_id, event_request)
             .map_err(|error| EventError::DatabaseError(error.to_string()))?;
         Ok(request_id)
     }
 
-    pub async fn evaluator_signatures(
-        &mut self,
-        evaluator_response: Signed<EvaluationResponse>,
+    /// Stores the tracking data of a request that starts a stage for the next event of a
+    /// subject. The data of a request that was waiting for a migration is kept.
+    async fn register_request(
+        &self,
+        event_request: &Signed<EventRequest>,
+        subject: &Subject,
+        stage: RequestStage,
     ) -> Result<(), EventError> {
-        // Check that the returned hash matches the hash from the pre-evaluation
-        let evaluation_request = match self
-            .event_pre_evaluations
-            .get(&evaluator_response.content.eval_req_hash)
-        {
-            Some(preevaluation_event) => preevaluation_event,
-            None => return Err(EventError::CryptoError(String::from(
-                "The hash of the event pre-evaluation does not match any of the pre-evaluations",
-            ))),
+        let request_id = DigestIdentifier::generate_with_blake3(event_request)
+            .map_err(|_| EventError::HashGenerationFailed)?;
+        let mut request_data: TapleRequest = match self.database.get_taple_request(&request_id) {
+            Ok(request_data) => request_data,
+            Err(crate::DbError::EntryNotFound) => event_request.clone().try_into()?,
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
         };
+        request_data.sn = Some(subject.sn + 1);
+        request_data.subject_id = Some(subject.subject_id.clone());
+        request_data.start_stage(stage);
+        self.database
+            .set_taple_request(&request_id, &request_data)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        self.notify_request_update(&request_data).await;
+        Ok(())
+    }
 
-        let subject_id = match &evaluation_request.event_request.content {
-            // The transfer is not evaluated
-            EventRequest::Transfer(_) => return Err(EventError::NoEvaluationForTransferEvents),
-            EventRequest::EOL(_) => return Err(EventError::NoEvaluationForEOLEvents),
-            EventRequest::Create(_) => {
-                return Err(EventError::EvaluationOrApprovationInCreationEvent)
-            }
-            EventRequest::Fact(state_request) => state_request.subject_id.clone(),
-        };
-        // Look at the status of the event, whether it is under evaluation or not.
-        let Some((ValidationStage::Evaluate, signers, quorum_size)) =
-            self.subjects_completing_event.get(&subject_id)
-        else {
-            return Err(EventError::WrongEventPhase);
-        };
-        let signer = evaluator_response.signature.signer.clone();
-        // Comprobar si el evaluador está en la lista de evaluadores
-        if !signers.contains(&signer) {
-            return Err(EventError::CryptoError(String::from(
-                "The signer is not in the list of evaluators or we already have the signature",
-            )));
+    /// Whether the state of a subject must be migrated before a request is processed. That is
+    /// the case when the state does not match the schema, the contract declares the migration
+    /// hook and the last event of the subject is not already a migration for the current
+    /// governance version.
+    async fn subject_needs_migration(
+        &self,
+        event_request: &EventRequest,
+        subject: &Subject,
+        gov_version: u64,
+    ) -> Result<bool, EventError> {
+        if let EventRequest::Migrate(_) = event_request {
+            return Ok(false);
         }
-        // Check that everything is cryptographically correct
-        evaluator_response
-            .verify()
-            .map_err(|error| EventError::CryptoError(error.to_string()))?;
-        let evaluation_hash = evaluator_response.content.hash_id(self.derivator)?;
-        // Get subject to know if we have it and the subject metadata
-        let subject = self
-            .database
-            .get_subject(&subject_id)
-            .map_err(|error| match error {
-                crate::DbError::EntryNotFound => EventError::SubjectNotFound(subject_id.to_str()),
-                _ => EventError::DatabaseError(error.to_string()),
-            })?;
-        // Check if the governance version matches ours, if not we do not accept it.
-        let governance_version = self
+        if subject.schema_id == "governance" {
+            return Ok(false);
+        }
+        let hooks = self
             .gov_api
-            .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
+            .get_contract_hooks(
+                subject.governance_id.clone(),
+                subject.schema_id.clone(),
+                subject.schema_version,
+                gov_version,
+            )
             .await
             .map_err(EventError::GovernanceError)?;
-        // Check that the json patch is valid
-        if !hash_match_after_patch(
-            &evaluator_response.content,
-            evaluator_response.content.patch.clone(),
-            subject.properties.clone(),
-        )? {
-            return Err(EventError::CryptoError(
-                "Json patch applied to state hash does not match the new state hash".to_string(),
-            ));
+        if !hooks.contains(&ContractHook::Migration) {
+            return Ok(false);
         }
-        // Save evaluation
-        let signatures_set = match self
-            .event_evaluations
-            .get_mut(&evaluator_response.content.eval_req_hash)
-        {
-            Some(signatures_set) => {
-                insert_or_replace_and_check(
-                    signatures_set,
-                    (
-                        UniqueSignature {
-                            signature: evaluator_response.signature.clone(),
-                        },
-                        evaluator_response.content.eval_success.clone(),
+        let last_event = self
+            .database
+            .get_event(&subject.subject_id, subject.sn)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        if let EventRequest::Migrate(_) = last_event.content.event_request.content {
+            if last_event.content.gov_version == gov_version {
+                return Ok(false);
+            }
+        }
+        let schema = self
+            .gov_api
+            .get_schema(
+                subject.governance_id.clone(),
+                subject.schema_id.clone(),
+                subject.schema_version,
+                gov_version,
+            )
+            .await
+            .map_err(EventError::GovernanceError)?;
+        let schema = Schema::compile(&schema.0)
+            .map_err(|_| EventError::SchemaNotCompiled(subject.subject_id.to_str()))?;
+        Ok(!schema.validate(&subject.properties.0))
+    }
+
+    /// Checks that a subject can be upgraded to a version of its schema, which must be newer
+    /// than its current one and be declared by the governance.
+    async fn check_schema_upgrade(
+        &self,
+        subject: &Subject,
+        schema_version: u64,
+        gov_version: u64,
+    ) -> Result<(), EventError> {
+        let invalid_version =
+            || EventError::InvalidSchemaVersion(subject.subject_id.to_str(), schema_version);
+        if schema_version <= subject.schema_version {
+            return Err(invalid_version());
+        }
+        match self
+            .gov_api
+            .get_schema(
+                subject.governance_id.clone(),
+                subject.schema_id.clone(),
+                schema_version,
+                gov_version,
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(RequestError::SchemaNotFound(_)) => Err(invalid_version()),
+            Err(error) => Err(EventError::GovernanceError(error)),
+        }
+    }
+
+    /// Asks for the evaluation of a migration request signed by the node. The request that
+    /// triggered it is processed once the migration event has been validated.
+    async fn start_migration(
+        &mut self,
+        event_request: Signed<EventRequest>,
+        subject: &Subject,
+        metadata: Metadata,
+    ) -> Result<DigestIdentifier, EventError> {
+        log::info!(
+            "Migrating the state of subject {}",
+            subject.subject_id.to_str()
+        );
+        let request_id = DigestIdentifier::generate_with_blake3(&event_request)
+            .map_err(|_| EventError::HashGenerationFailed)?;
+        let migrate_request = EventRequest::Migrate(MigrateRequest {
+            subject_id: subject.subject_id.clone(),
+            schema_version: None,
+        });
+        let signature = Signature::new(
+            &migrate_request,
+            &self.signature_manager.keys,
+            self.derivator,
+        )
+        .map_err(|_| {
+            EventError::CryptoError(String::from("Error signing the migration request"))
+        })?;
+        let migrate_request = Signed::<EventRequest>::new(migrate_request, signature);
+        self.ask_for_evaluation(&migrate_request, subject, metadata)
+            .await?;
+        self.register_request(&migrate_request, subject, RequestStage::Evaluation)
+            .await?;
+        self.database
+            .set_request(&subject.subject_id, migrate_request)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        self.register_request(&event_request, subject, RequestStage::Migration)
+            .await?;
+        self.requests_awaiting_migration
+            .insert(subject.subject_id.clone(), event_request);
+        Ok(request_id)
+    }
+
+    /// Processes the request that was waiting for the migration of the state of a subject.
+    async fn resume_after_migration(&mut self, subject_id: &DigestIdentifier) {
+        let Some(event_request) = self.requests_awaiting_migration.remove(subject_id) else {
+            return;
+        };
+        if let Err(error) = self.new_event(event_request.clone()).await {
+            self.request_failed(&event_request, &error).await;
+        }
+    }
+
+    /// Asks the evaluators for the evaluation of a request against the current state of a subject.
+    async fn ask_for_evaluation(
+        &mut self,
+        event_request: &Signed<EventRequest>,
+        subject: &Subject,
+        metadata: Metadata,
+    ) -> Result<(), EventError> {
+        let stage = ValidationStage::Evaluate;
+        let event_preevaluation = EvaluationRequest {
+            event_request: event_request.clone(),
+            context: SubjectContext {
+                governance_id: metadata.governance_id.clone(),
+                schema_id: metadata.schema_id.clone(),
+                schema_version: subject.schema_version,
+                is_owner: subject.owner == event_request.signature.signer,
+                state: subject.properties.clone(),
+                namespace: metadata.namespace.clone(),
+            },
+            gov_version: metadata.governance_version,
+            sn: subject.sn + 1,
+        };
+        // Get the list of evaluators
+        let (signers, quorum_size) = self.get_signers_and_quorum(metadata, stage.clone()).await?;
+        let event_preevaluation_hash = DigestIdentifier::generate_with_blake3(&event_preevaluation)
+            .map_err(|_| {
+                EventError::CryptoError(String::from(
+                    "Error calculating the hash of the event pre-evaluation",
+                ))
+            })?;
+        self.event_pre_evaluations
+            .insert(event_preevaluation_hash, event_preevaluation.clone());
+        // Add the event to the hashset to not complete two at the same time for the same subject
+        let negative_quorum_size = negative_quorum(
+            self.signer_weights.get(&subject.subject_id),
+            &signers,
+            quorum_size,
+        );
+        self.subjects_completing_event.insert(
+            subject.subject_id.clone(),
+            (stage, signers.clone(), (quorum_size, negative_quorum_size)),
+        );
+        self.ask_signatures(
+            &subject.subject_id,
+            create_evaluator_request(event_preevaluation),
+            signers,
+            quorum_size,
+        )
+        .await
+    }
+
+    pub async fn evaluator_signatures(
+        &mut self,
+        evaluator_response: Signed<EvaluationResponse>,
+    ) -> Result<(), EventError> {
+        // Check that the returned hash matches the hash from the pre-evaluation
+        let evaluation_request = match self
+            .event_pre_evaluations
+            .get(&evaluator_response.content.eval_req_hash)
+        {
+            Some(preevaluation_event) => preevaluation_event,
+            None => return Err(EventError::CryptoError(String::from(
+                "The hash of the event pre-evaluation does not match any of the pre-evaluations",
+            ))),
+        };
+
+        let subject_id = match &evaluation_request.event_request.content {
+            // Only evaluated if the contract declares their hooks
+            EventRequest::Transfer(transfer_request) => transfer_request.subject_id.clone(),
+            EventRequest::EOL(eol_request) => eol_request.subject_id.clone(),
+            EventRequest::Migrate(migrate_request) => migrate_request.subject_id.clone(),
+            EventRequest::Create(create_request) => generate_subject_id(
+                &create_request.namespace,
+                &create_request.schema_id,
+                create_request.public_key.to_str(),
+                create_request.governance_id.to_str(),
+                evaluation_request.gov_version,
+                self.derivator,
+            )?,
+            EventRequest::Fact(state_request) => state_request.subject_id.clone(),
+        };
+        // Look at the status of the event, whether it is under evaluation or not.
+        let Some((ValidationStage::Evaluate, signers, quorum_size)) =
+            self.subjects_completing_event.get(&subject_id)
+        else {
+            return Err(EventError::WrongEventPhase);
+        };
+        let signer = evaluator_response.signature.signer.clone();
+        // Comprobar si el evaluador está en la lista de evaluadores
+        if !signers.contains(&signer) {
+            return Err(EventError::CryptoError(String::from(
+                "The signer is not in the list of evaluators or we already have the signature",
+            )));
+        }
+        // Check that everything is cryptographically correct
+        evaluator_response
+            .verify()
+            .map_err(|error| EventError::CryptoError(error.to_string()))?;
+        let evaluation_hash = evaluator_response.content.hash_id(self.derivator)?;
+        // Get subject to know if we have it and the subject metadata
+        let subject = self
+            .event_subject(
+                &subject_id,
+                &evaluation_request.event_request,
+                evaluation_request.gov_version,
+            )
+            .await?;
+        // Check if the governance version matches ours, if not we do not accept it.
+        let governance_version = self
+            .gov_api
+            .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
+            .await
+            .map_err(EventError::GovernanceError)?;
+        // Check that the json patch is valid
+        if !hash_match_after_patch(
+            &evaluator_response.content,
+            evaluator_response.content.patch.clone(),
+            subject.properties.clone(),
+        )? {
+            return Err(EventError::CryptoError(
+                "Json patch applied to state hash does not match the new state hash".to_string(),
+            ));
+        }
+        self.update_request(&evaluation_request.event_request, |request| {
+            request.add_signer(signer.clone())
+        })
+        .await?;
+        // Save evaluation
+        let signatures_set = match self
+            .event_evaluations
+            .get_mut(&evaluator_response.content.eval_req_hash)
+        {
+            Some(signatures_set) => {
+                insert_or_replace_and_check(
+                    signatures_set,
+                    (
+                        UniqueSignature {
+                            signature: evaluator_response.signature.clone(),
+                        },
+                        evaluator_response.content.eval_success.clone(),
                         evaluation_hash.clone(),
                     ),
                 );
@@ -909,7 +1300,11 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             }
         };
         let (num_signatures_hash_ok, num_signatures_hash_ko) =
-            count_signatures_with_event_content_hash(&signatures_set, &evaluation_hash);
+            count_signatures_with_event_content_hash(
+                &signatures_set,
+                &evaluation_hash,
+                self.signer_weights.get(&subject_id),
+            );
         let (quorum_size, negative_quorum_size) = quorum_size.to_owned();
         // Check if we reach Quorum
         let quorum_reached = {
@@ -929,7 +1324,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 &subject_id,
                 create_evaluator_request(evaluation_request.clone()),
                 new_signers.clone(),
-                quorum_size,
+                quorum_size - num_signatures_hash_ok,
             )
             .await?;
             self.subjects_completing_event.insert(
@@ -951,6 +1346,57 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 })
                 .map(|(signature, _, _)| signature.signature.clone())
                 .collect();
+            let evaluation_request = evaluation_request.clone();
+            let result = self
+                .evaluation_completed(
+                    evaluator_response,
+                    evaluation_request.clone(),
+                    evaluator_signatures,
+                    subject,
+                    governance_version,
+                )
+                .await;
+            if let Err(error) = &result {
+                self.request_failed(&evaluation_request.event_request, error)
+                    .await;
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Continues with the approval or the validation of an event once its evaluation has
+    /// reached quorum.
+    async fn evaluation_completed(
+        &mut self,
+        evaluator_response: Signed<EvaluationResponse>,
+        evaluation_request: EvaluationRequest,
+        evaluator_signatures: HashSet<Signature>,
+        subject: Subject,
+        governance_version: u64,
+    ) -> Result<(), EventError> {
+        let subject_id = subject.subject_id.clone();
+        let eval_success = evaluator_response.content.eval_success;
+        let is_genesis = evaluation_request.sn == 0;
+        let is_fact = matches!(
+            evaluation_request.event_request.content,
+            EventRequest::Fact(_) | EventRequest::Migrate(_)
+        );
+        if !eval_success && !is_fact {
+            // The subject is not created if its initial state can not be built, and transfers
+            // and EOLs vetoed by the contract do not happen. Failed migrations are stored like
+            // failed facts, so they are not attempted again for the same governance version
+            self.discard_event(&subject_id, &evaluation_request.event_request)
+                .await?;
+            return self
+                .update_request(&evaluation_request.event_request, |request| {
+                    request.fail_with(RequestFailure::EvaluationFailed)
+                })
+                .await;
+        }
+        let hash_prev_event = if is_genesis {
+            DigestIdentifier::default()
+        } else {
             // We need to get the derivator from the previous validation proof if any
             let derivator = match self
                 .database
@@ -960,7 +1406,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 Err(crate::database::Error::EntryNotFound) => self.derivator,
                 Err(e) => return Err(EventError::DatabaseError(e.to_string())),
             };
-            let hash_prev_event = DigestIdentifier::from_serializable_borsh(
+            DigestIdentifier::from_serializable_borsh(
                 &self
                     .database
                     .get_event(&subject.subject_id, subject.sn)
@@ -972,127 +1418,140 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 EventError::CryptoError(
                     "Error calculating the hash of the previous event".to_owned(),
                 )
-            })?;
-            let metadata = Metadata {
-                namespace: subject.namespace.clone(),
-                subject_id: subject_id.clone(),
-                governance_id: subject.governance_id.clone(),
-                governance_version,
-                schema_id: subject.schema_id.clone(),
+            })?
+        };
+        // Genesis events carry the whole initial state of the subject as their patch
+        let patch = if is_genesis {
+            genesis_patch(&subject.properties, evaluator_response.content.patch)?
+        } else {
+            evaluator_response.content.patch
+        };
+        let metadata = Metadata {
+            namespace: subject.namespace.clone(),
+            subject_id: subject_id.clone(),
+            governance_id: subject.governance_id.clone(),
+            governance_version,
+            schema_id: subject.schema_id.clone(),
+        };
+        if evaluator_response.content.appr_required && !evaluator_response.content.eval_success {
+            return Err(EventError::ApprovalRequiredWhenEvalFailed);
+        }
+        // Ask for Approves if necessary, otherwise ask for validations.
+        let (stage, event_message) = if evaluator_response.content.appr_required {
+            let approval_request = ApprovalRequest {
+                event_request: evaluation_request.event_request.clone(),
+                sn: evaluation_request.sn,
+                gov_version: governance_version,
+                patch,
+                state_hash: evaluator_response.content.state_hash,
+                hash_prev_event,
+                gov_id: subject.governance_id.clone(),
             };
-            if evaluator_response.content.appr_required && !evaluator_response.content.eval_success
-            {
-                return Err(EventError::ApprovalRequiredWhenEvalFailed);
-            }
-            // Ask for Approves if necessary, otherwise ask for validations.
-            let (stage, event_message) = if evaluator_response.content.appr_required {
-                let approval_request = ApprovalRequest {
-                    event_request: evaluation_request.event_request.clone(),
-                    sn: evaluation_request.sn,
-                    gov_version: governance_version,
-                    patch: evaluator_response.content.patch,
-                    state_hash: evaluator_response.content.state_hash,
-                    hash_prev_event,
-                    gov_id: subject.governance_id.clone(),
-                };
-                let approval_request_hash = approval_request
-                    .hash_id(DigestDerivator::Blake3_256)
-                    .map_err(|_| {
+            let approval_request_hash = approval_request
+                .hash_id(DigestDerivator::Blake3_256)
+                .map_err(|_| {
                     EventError::CryptoError(String::from(
                         "Error calculating the hash of the proposal",
                     ))
                 })?;
-                let subject_keys = subject
-                    .keys
-                    .as_ref()
-                    .expect("Llegados a aquí tenemos que ser owner");
-                let subject_signature = Signature::new(
-                    &approval_request,
-                    &subject_keys,
-                    self.derivator,
-                )
-                .map_err(|_| {
+            let subject_keys = subject
+                .keys
+                .as_ref()
+                .expect("Llegados a aquí tenemos que ser owner");
+            let subject_signature =
+                Signature::new(&approval_request, &subject_keys, self.derivator).map_err(|_| {
                     EventError::CryptoError(String::from("Error signing the Approval Request"))
                 })?;
-                let approval_request = Signed::<ApprovalRequest> {
-                    content: approval_request,
-                    signature: subject_signature,
-                };
-                // Add to the hashmap to be able to access it when the signatures of the evaluators arrive.
-                self.approval_eval_signatures
-                    .insert(approval_request_hash.clone(), evaluator_signatures.clone());
-                self.approval_requests
-                    .insert(approval_request_hash, approval_request.clone());
-                let msg = create_approval_request(approval_request);
-                // Return TapleMessage directly
-                (ValidationStage::Approve, msg)
-            } else {
-                // No approval required
-                let gov_version = self
-                    .gov_api
-                    .get_governance_version(
-                        subject.governance_id.clone(),
-                        subject.subject_id.clone(),
-                    )
-                    .await?;
-                let event = Event {
-                    subject_id: subject_id.clone(),
-                    event_request: evaluation_request.event_request.clone(),
-                    sn: evaluation_request.sn,
-                    gov_version: governance_version,
-                    patch: evaluator_response.content.patch,
-                    state_hash: evaluator_response.content.state_hash,
-                    eval_success: evaluator_response.content.eval_success,
-                    appr_required: evaluator_response.content.appr_required,
-                    approved: true,
-                    hash_prev_event,
-                    evaluators: evaluator_signatures,
-                    approvers: HashSet::new(),
-                };
-                let event_hash = event.hash_id(self.derivator)?;
-                let subject_keys = subject
-                    .keys
-                    .as_ref()
-                    .expect("Llegados a aquí tenemos que ser owner");
-                let subject_signature = Signature::new(&event, &subject_keys, self.derivator)
-                    .map_err(|_| {
-                        EventError::CryptoError(String::from("Error signing the Event"))
-                    })?;
-                let signed_event = Signed::<Event> {
-                    content: event,
-                    signature: subject_signature,
-                };
-                let validation_event =
-                    self.create_validation_event(&subject, &signed_event, gov_version)?;
-                let event_message = create_validator_request(validation_event.clone());
-                self.event_validation_events
-                    .insert(event_hash.clone(), validation_event);
-                self.events_to_validate
-                    .insert(event_hash, signed_event.clone());
-                self.database
-                    .set_prevalidated_event(&subject.subject_id, signed_event)
-                    .map_err(|error| EventError::DatabaseError(error.to_string()))?;
-                self.database
-                    .del_request(&subject.subject_id)
-                    .map_err(|error| EventError::DatabaseError(error.to_string()))?;
-                (ValidationStage::Validate, event_message)
+            let approval_request = Signed::<ApprovalRequest> {
+                content: approval_request,
+                signature: subject_signature,
             };
-            // Clean HashMaps
-            self.event_evaluations
-                .remove(&evaluator_response.content.eval_req_hash);
-            self.event_pre_evaluations
-                .remove(&evaluator_response.content.eval_req_hash);
-            let (signers, quorum_size) =
-                self.get_signers_and_quorum(metadata, stage.clone()).await?;
-            self.ask_signatures(&subject_id, event_message, signers.clone(), quorum_size)
-                .await?;
-            // Make update of the phase the event is going through
-            let negative_quorum_size = (signers.len() as u32 - quorum_size) + 1;
-            self.subjects_completing_event.insert(
-                subject_id.clone(),
-                (stage, signers, (quorum_size, negative_quorum_size)),
+            // Add to the hashmap to be able to access it when the signatures of the evaluators arrive.
+            self.approval_eval_signatures.insert(
+                approval_request_hash.clone(),
+                (
+                    evaluator_signatures.clone(),
+                    evaluator_response.content.dependencies,
+                ),
             );
-        }
+            self.approval_requests
+                .insert(approval_request_hash, approval_request.clone());
+            let msg = create_approval_request(approval_request);
+            // Return TapleMessage directly
+            (ValidationStage::Approve, msg)
+        } else {
+            // No approval required
+            let gov_version = self
+                .gov_api
+                .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
+                .await?;
+            let event = Event {
+                subject_id: subject_id.clone(),
+                event_request: evaluation_request.event_request.clone(),
+                sn: evaluation_request.sn,
+                gov_version: governance_version,
+                patch,
+                state_hash: evaluator_response.content.state_hash,
+                eval_success: evaluator_response.content.eval_success,
+                appr_required: evaluator_response.content.appr_required,
+                approved: true,
+                hash_prev_event,
+                evaluators: evaluator_signatures,
+                approvers: HashSet::new(),
+                dependencies: evaluator_response.content.dependencies,
+            };
+            let event_hash = event.hash_id(self.derivator)?;
+            let subject_keys = subject
+                .keys
+                .as_ref()
+                .expect("Llegados a aquí tenemos que ser owner");
+            let subject_signature = Signature::new(&event, &subject_keys, self.derivator)
+                .map_err(|_| EventError::CryptoError(String::from("Error signing the Event")))?;
+            let signed_event = Signed::<Event> {
+                content: event,
+                signature: subject_signature,
+            };
+            let validation_event =
+                self.create_validation_event(&subject, &signed_event, gov_version)?;
+            let event_message = create_validator_request(validation_event.clone());
+            self.event_validation_events
+                .insert(event_hash.clone(), validation_event);
+            self.events_to_validate
+                .insert(event_hash, signed_event.clone());
+            self.database
+                .set_prevalidated_event(&subject.subject_id, signed_event)
+                .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+            self.database
+                .del_request(&subject.subject_id)
+                .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+            (ValidationStage::Validate, event_message)
+        };
+        // Clean HashMaps
+        self.event_evaluations
+            .remove(&evaluator_response.content.eval_req_hash);
+        self.event_pre_evaluations
+            .remove(&evaluator_response.content.eval_req_hash);
+        let (signers, quorum_size) = self.get_signers_and_quorum(metadata, stage.clone()).await?;
+        self.ask_signatures(&subject_id, event_message, signers.clone(), quorum_size)
+            .await?;
+        let request_stage = match stage {
+            ValidationStage::Approve => RequestStage::Approval,
+            _ => RequestStage::Validation,
+        };
+        // Make update of the phase the event is going through
+        let negative_quorum_size =
+            negative_quorum(self.signer_weights.get(&subject_id), &signers, quorum_size);
+        self.subjects_completing_event.insert(
+            subject_id.clone(),
+            (stage, signers, (quorum_size, negative_quorum_size)),
+        );
+        self.update_request(&evaluation_request.event_request, |request| {
+            if !eval_success {
+                request.failure = Some(RequestFailure::EvaluationFailed);
+            }
+            request.start_stage(request_stage);
+        })
+        .await?;
         Ok(())
     }
 
@@ -1110,13 +1569,18 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             }
         };
         let subject_id = match &approval_request.content.event_request.content {
-            // The transfer is not approved
-            EventRequest::Transfer(_) => return Err(EventError::NoApprovalForTransferEvents),
-            // EOL is not approved
-            EventRequest::EOL(_) => return Err(EventError::NoApprovalForEOLEvents),
-            EventRequest::Create(_) => {
-                return Err(EventError::EvaluationOrApprovationInCreationEvent)
-            }
+            // Only approved if the contract declares their hooks
+            EventRequest::Transfer(transfer_request) => transfer_request.subject_id.clone(),
+            EventRequest::EOL(eol_request) => eol_request.subject_id.clone(),
+            EventRequest::Migrate(migrate_request) => migrate_request.subject_id.clone(),
+            EventRequest::Create(create_request) => generate_subject_id(
+                &create_request.namespace,
+                &create_request.schema_id,
+                create_request.public_key.to_str(),
+                create_request.governance_id.to_str(),
+                approval_request.content.gov_version,
+                self.derivator,
+            )?,
             EventRequest::Fact(state_request) => state_request.subject_id.clone(),
         };
         let Some((ValidationStage::Approve, signers, quorum_size)) =
@@ -1137,12 +1601,16 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             .map_err(|error| EventError::CryptoError(error.to_string()))?;
         // Get subject to know if we have it and the subject metadata
         let subject = self
-            .database
-            .get_subject(&subject_id)
-            .map_err(|error| match error {
-                crate::DbError::EntryNotFound => EventError::SubjectNotFound(subject_id.to_str()),
-                _ => EventError::DatabaseError(error.to_string()),
-            })?;
+            .event_subject(
+                &subject_id,
+                &approval_request.content.event_request,
+                approval_request.content.gov_version,
+            )
+            .await?;
+        self.update_request(&approval_request.content.event_request, |request| {
+            request.add_signer(signer.clone())
+        })
+        .await?;
         // Save approval
         let approval_set = match self
             .event_approvations
@@ -1170,12 +1638,15 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             }
         };
         // Check if we reach positive or negative Quorum
-        let num_approvals_with_same_acceptance = approval_set
-            .iter()
-            .filter(|unique_approval| {
-                unique_approval.approval.content.approved == approval.content.approved
-            })
-            .count() as u32;
+        let num_approvals_with_same_acceptance = weigh_votes(
+            self.signer_weights.get(&subject_id),
+            approval_set
+                .iter()
+                .filter(|unique_approval| {
+                    unique_approval.approval.content.approved == approval.content.approved
+                })
+                .map(|unique_approval| &unique_approval.approval.signature.signer),
+        );
         let (quorum_size_now, _) = match approval.content.approved {
             true => (quorum_size.0, true),
             false => (quorum_size.1, false),
@@ -1188,8 +1659,8 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             self.ask_signatures(
                 &subject_id,
                 create_approval_request(approval_request.to_owned()),
-                signers.clone(),
-                quorum_size_now,
+                new_signers.clone(),
+                quorum_size_now - num_approvals_with_same_acceptance,
             )
             .await?;
             // Make update of the phase the event is going through
@@ -1203,20 +1674,6 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             );
             Ok(()) // We don't reach quorum, we do nothing
         } else {
-            let governance_version = self
-                .gov_api
-                .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
-                .await
-                .map_err(EventError::GovernanceError)?;
-            // If Quorum is reached, we stop asking for approves and start asking for validations with the complete event including the new approves.
-            let metadata = Metadata {
-                namespace: subject.namespace.clone(),
-                subject_id: subject_id.clone(),
-                governance_id: subject.governance_id.clone(),
-                governance_version,
-                schema_id: subject.schema_id.clone(),
-            };
-            // We create the final event
             let approvals: HashSet<Signature> = approval_set
                 .iter()
                 .filter(|unique_approval| {
@@ -1224,78 +1681,129 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 })
                 .map(|approval| approval.approval.signature.clone())
                 .collect();
-            let event_proposal = self
-                .approval_requests
-                .get(&approval.content.appr_req_hash)
-                .unwrap();
-            let gov_version = self
-                .gov_api
-                .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
-                .await?;
-            let evaluators = self
-                .approval_eval_signatures
-                .get(&approval.content.appr_req_hash)
-                .unwrap()
-                .to_owned();
-            let event = Event {
-                subject_id: subject_id.clone(),
-                event_request: event_proposal.content.event_request.clone(),
-                sn: event_proposal.content.sn,
-                gov_version,
-                patch: event_proposal.content.patch.clone(),
-                state_hash: event_proposal.content.state_hash.clone(),
-                eval_success: true,
-                appr_required: true,
-                approved: approval.content.approved,
-                hash_prev_event: event_proposal.content.hash_prev_event.clone(),
-                evaluators,
-                approvers: approvals,
-            };
-            let event_hash = event.hash_id(self.derivator)?;
-            let subject_keys = subject
-                .keys
-                .as_ref()
-                .expect("Llegados a aquí tenemos que ser owner");
-            let subject_signature =
-                Signature::new(&event, &subject_keys, self.derivator).map_err(|_| {
-                    EventError::CryptoError(String::from(
-                        "Error signing the Event (Approval stage)",
-                    ))
-                })?;
-            let signed_event = Signed::<Event> {
-                content: event,
-                signature: subject_signature,
-            };
-            let validation_event =
-                self.create_validation_event(&subject, &signed_event, gov_version)?;
-            let event_message = create_validator_request(validation_event.clone());
-            // Clean HashMaps
-            self.approval_eval_signatures
-                .remove(&approval.content.appr_req_hash);
-            self.approval_requests
-                .remove(&approval.content.appr_req_hash);
-            self.event_approvations
-                .remove(&approval.content.appr_req_hash);
-            let stage = ValidationStage::Validate;
-            let (signers, quorum_size) =
-                self.get_signers_and_quorum(metadata, stage.clone()).await?;
-            self.ask_signatures(&subject_id, event_message, signers.clone(), quorum_size)
-                .await?;
-            self.event_validation_events
-                .insert(event_hash.clone(), validation_event);
-            self.events_to_validate
-                .insert(event_hash, signed_event.clone());
-            self.database
-                .set_prevalidated_event(&subject.subject_id, signed_event)
-                .map_err(|error| EventError::DatabaseError(error.to_string()))?;
-            self.database
-                .del_request(&subject.subject_id)
-                .map_err(|error| EventError::DatabaseError(error.to_string()))?;
-            // Make update of the phase the event is going through
-            self.subjects_completing_event
-                .insert(subject_id, (stage, signers, (quorum_size, 0)));
-            Ok(())
+            let event_request = approval_request.content.event_request.clone();
+            let result = self.approval_completed(approval, approvals, subject).await;
+            if let Err(error) = &result {
+                self.request_failed(&event_request, error).await;
+            }
+            result
+        }
+    }
+
+    /// Continues with the validation of an event once its approval has reached quorum.
+    async fn approval_completed(
+        &mut self,
+        approval: Signed<ApprovalResponse>,
+        approvals: HashSet<Signature>,
+        subject: Subject,
+    ) -> Result<(), EventError> {
+        let subject_id = subject.subject_id.clone();
+        let event_proposal = self
+            .approval_requests
+            .get(&approval.content.appr_req_hash)
+            .unwrap();
+        let is_fact = matches!(
+            event_proposal.content.event_request.content,
+            EventRequest::Fact(_) | EventRequest::Migrate(_)
+        );
+        if !approval.content.approved && !is_fact {
+            // The subject is not created if its initial state is rejected, and rejected
+            // transfers and EOLs do not happen
+            let event_request = event_proposal.content.event_request.clone();
+            self.discard_event(&subject_id, &event_request).await?;
+            return self
+                .update_request(&event_request, |request| {
+                    request.fail_with(RequestFailure::ApprovalRejected)
+                })
+                .await;
         }
+        let governance_version = self
+            .gov_api
+            .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
+            .await
+            .map_err(EventError::GovernanceError)?;
+        // If Quorum is reached, we stop asking for approves and start asking for validations with the complete event including the new approves.
+        let metadata = Metadata {
+            namespace: subject.namespace.clone(),
+            subject_id: subject_id.clone(),
+            governance_id: subject.governance_id.clone(),
+            governance_version,
+            schema_id: subject.schema_id.clone(),
+        };
+        // We create the final event
+        let gov_version = self
+            .gov_api
+            .get_governance_version(subject.governance_id.clone(), subject.subject_id.clone())
+            .await?;
+        let (evaluators, dependencies) = self
+            .approval_eval_signatures
+            .get(&approval.content.appr_req_hash)
+            .unwrap()
+            .to_owned();
+        let event = Event {
+            subject_id: subject_id.clone(),
+            event_request: event_proposal.content.event_request.clone(),
+            sn: event_proposal.content.sn,
+            gov_version,
+            patch: event_proposal.content.patch.clone(),
+            state_hash: event_proposal.content.state_hash.clone(),
+            eval_success: true,
+            appr_required: true,
+            approved: approval.content.approved,
+            hash_prev_event: event_proposal.content.hash_prev_event.clone(),
+            evaluators,
+            approvers: approvals,
+            dependencies,
+        };
+        let event_hash = event.hash_id(self.derivator)?;
+        let subject_keys = subject
+            .keys
+            .as_ref()
+            .expect("Llegados a aquí tenemos que ser owner");
+        let subject_signature =
+            Signature::new(&event, &subject_keys, self.derivator).map_err(|_| {
+                EventError::CryptoError(String::from("Error signing the Event (Approval stage)"))
+            })?;
+        let signed_event = Signed::<Event> {
+            content: event,
+            signature: subject_signature,
+        };
+        let event_request = signed_event.content.event_request.clone();
+        let validation_event =
+            self.create_validation_event(&subject, &signed_event, gov_version)?;
+        let event_message = create_validator_request(validation_event.clone());
+        // Clean HashMaps
+        self.approval_eval_signatures
+            .remove(&approval.content.appr_req_hash);
+        self.approval_requests
+            .remove(&approval.content.appr_req_hash);
+        self.event_approvations
+            .remove(&approval.content.appr_req_hash);
+        let stage = ValidationStage::Validate;
+        let (signers, quorum_size) = self.get_signers_and_quorum(metadata, stage.clone()).await?;
+        self.ask_signatures(&subject_id, event_message, signers.clone(), quorum_size)
+            .await?;
+        self.event_validation_events
+            .insert(event_hash.clone(), validation_event);
+        self.events_to_validate
+            .insert(event_hash, signed_event.clone());
+        self.database
+            .set_prevalidated_event(&subject.subject_id, signed_event)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        self.database
+            .del_request(&subject.subject_id)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        // Make update of the phase the event is going through
+        self.subjects_completing_event
+            .insert(subject_id, (stage, signers, (quorum_size, 0)));
+        self.update_request(&event_request, |request| {
+            if !approval.content.approved {
+                request.failure = Some(RequestFailure::ApprovalRejected);
+            }
+            request.start_stage(RequestStage::Validation);
+        })
+        .await?;
+        Ok(())
     }
 
     pub async fn validation_signatures(
@@ -1320,6 +1828,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
         let subject_id = match &event.content.event_request.content {
             EventRequest::Transfer(transfer_request) => transfer_request.subject_id.clone(),
             EventRequest::EOL(eol_request) => eol_request.subject_id.clone(),
+            EventRequest::Migrate(migrate_request) => migrate_request.subject_id.clone(),
             EventRequest::Create(create_request) => generate_subject_id(
                 &create_request.namespace,
                 &create_request.schema_id,
@@ -1392,6 +1901,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                     MessageConfig {
                         timeout: 2000,
                         replication_factor: 1.0,
+                        retry: None,
                     },
                 ))
                 .await?;
@@ -1417,6 +1927,10 @@ impl<C: DatabaseCollection> EventCompleter<C> {
         signature
             .verify(&validation_event.proof)
             .map_err(|error| EventError::CryptoError(error.to_string()))?;
+        self.update_request(&event.content.event_request, |request| {
+            request.add_signer(signer.clone())
+        })
+        .await?;
         // Save validation
         let validation_set = match self.event_validations.get_mut(&event_hash) {
             Some(validation_set) => {
@@ -1434,8 +1948,15 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             }
         };
         let quorum_size = quorum_size.to_owned();
+        let num_validations = weigh_votes(
+            self.signer_weights.get(&subject_id),
+            validation_set
+                .iter()
+                .map(|unique_signature| &unique_signature.signature.signer),
+        );
         // Check if we reach Quorum and if so stop asking for signatures.
-        if (validation_set.len() as u32) < quorum_size.0 {
+        if num_validations < quorum_size.0 {
+            let signatures_needed = quorum_size.0 - num_validations;
             let event_message = create_validator_request(validation_event.to_owned());
             let mut new_signers: HashSet<KeyIdentifier> =
                 signers.into_iter().map(|s| s.clone()).collect();
@@ -1444,7 +1965,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 &subject_id,
                 event_message,
                 new_signers.clone(),
-                quorum_size.0,
+                signatures_needed,
             )
             .await?;
             // Make update of the phase the event is going through
@@ -1458,6 +1979,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
                 .iter()
                 .map(|unique_signature| unique_signature.signature.clone())
                 .collect();
+            let event_request = event.content.event_request.clone();
             // If quorum is reached we send it to the ledger.
             // Message to Ledger Are in ask because is we use tell we can accept anotheer event with same sn if the ledger function has not finished and block the subject for validation
             if event.content.sn == 0 {
@@ -1498,7 +2020,12 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             self.event_validation_events.remove(&event_hash);
             self.event_validations.remove(&event_hash);
             self.subjects_completing_event.remove(&subject_id);
-            Ok(())
+            self.signer_weights.remove(&subject_id);
+            let result = self.request_finished(&event_request).await;
+            if let EventRequest::Migrate(_) = &event_request.content {
+                self.resume_after_migration(&subject_id).await;
+            }
+            result
         }
     }
 
@@ -1509,23 +2036,323 @@ impl<C: DatabaseCollection> EventCompleter<C> {
     ) -> Result<(), EventError> {
         self.message_channel
             .tell(MessageTaskCommand::Request(
-                None,
+                Some(lce_task_id(&governance_id)),
                 TapleMessages::LedgerMessages(LedgerCommand::GetLCE {
                     who_asked: self.own_identifier.clone(),
                     subject_id: governance_id,
                 }),
                 vec![who_asked],
-                MessageConfig {
-                    timeout: TIMEOUT,
-                    replication_factor: 1.0,
-                },
+                MessageConfig::with_retry(self.retry_policies.lce.clone(), 1.0),
             ))
             .await
             .map_err(EventError::ChannelError)
     }
 
-    async fn get_signers_and_quorum(
+    /// Aborts a request whose event is being completed. The message tasks of the subject are
+    /// cancelled and the pending event is discarded, so a new event request can be sent for
+    /// the subject.
+    pub async fn cancel_request(&mut self, request_id: DigestIdentifier) -> Result<(), EventError> {
+        let mut request = match self.database.get_taple_request(&request_id) {
+            Ok(request) => request,
+            Err(crate::DbError::EntryNotFound) => {
+                return Err(EventError::RequestNotFound(request_id.to_str()))
+            }
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        };
+        let Some(subject_id) = request.subject_id.clone() else {
+            return Err(EventError::RequestNotInProgress(request_id.to_str()));
+        };
+        if request.state != RequestState::Processing {
+            return Err(EventError::RequestNotInProgress(request_id.to_str()));
+        }
+        // The request must be the one whose event is being completed for the subject, unless
+        // it is waiting for a migration
+        if self.requests_awaiting_migration.get(&subject_id) == Some(&request.event_request) {
+            self.requests_awaiting_migration.remove(&subject_id);
+        } else if self.pending_event_request(&subject_id)?.as_ref() == Some(&request.event_request)
+        {
+            // Validators may have already signed the proof of the event, and they would not
+            // sign another event with the same sn, so its validation can not be stopped
+            if self.is_prevalidated(&subject_id)? {
+                return Err(EventError::RequestInValidation(request_id.to_str()));
+            }
+            self.discard_event(&subject_id, &request.event_request)
+                .await?;
+        } else {
+            return Err(EventError::RequestNotInProgress(request_id.to_str()));
+        }
+        request.cancel();
+        self.database
+            .set_taple_request(&request_id, &request)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        self.notify_request_update(&request).await;
+        Ok(())
+    }
+
+    /// Called when the retry policy of the messages sent for an event is exhausted. The event
+    /// is discarded and its request fails. Only the evaluation and approval of an event can
+    /// exhaust their policies.
+    pub async fn retries_exhausted(
+        &mut self,
+        task: TaskExhausted<TapleMessages>,
+    ) -> Result<(), EventError> {
+        // The tasks of the subjects are identified by the subject id
+        let Ok(subject_id) = DigestIdentifier::from_str(&task.id) else {
+            return Ok(());
+        };
+        let Some(event_request) = self.pending_event_request(&subject_id)? else {
+            return Ok(());
+        };
+        // The task may belong to a stage that has already been completed
+        let stage = match (
+            self.subjects_completing_event.get(&subject_id),
+            &task.content,
+        ) {
+            (
+                Some((ValidationStage::Evaluate, _, _)),
+                TapleMessages::EvaluationMessage(EvaluatorMessage::AskForEvaluation(evaluation)),
+            ) if evaluation.event_request == event_request => RequestStage::Evaluation,
+            (
+                Some((ValidationStage::Approve, _, _)),
+                TapleMessages::ApprovalMessages(ApprovalMessages::RequestApproval(approval)),
+            ) if approval.content.event_request == event_request => RequestStage::Approval,
+            _ => return Ok(()),
+        };
+        log::warn!(
+            "Retries exhausted during the {:?} of the event of subject {}",
+            stage,
+            subject_id.to_str()
+        );
+        self.discard_event(&subject_id, &event_request).await?;
+        self.update_request(&event_request, |request| {
+            request.fail_with(RequestFailure::RetriesExhausted { stage })
+        })
+        .await
+    }
+
+    /// Returns the subject an evaluated or approved request refers to. The subject of a
+    /// creation request does not exist yet, so it is built with the initial state of its schema.
+    async fn event_subject(
+        &self,
+        subject_id: &DigestIdentifier,
+        event_request: &Signed<EventRequest>,
+        gov_version: u64,
+    ) -> Result<Subject, EventError> {
+        let EventRequest::Create(create_request) = &event_request.content else {
+            return self
+                .database
+                .get_subject(subject_id)
+                .map_err(|error| match error {
+                    crate::DbError::EntryNotFound => {
+                        EventError::SubjectNotFound(subject_id.to_str())
+                    }
+                    _ => EventError::DatabaseError(error.to_string()),
+                });
+        };
+        let subject_keys = match self.database.get_keys(&create_request.public_key) {
+            Ok(keys) => keys,
+            Err(crate::DbError::EntryNotFound) => {
+                return Err(EventError::SubjectKeysNotFound(
+                    create_request.public_key.to_str(),
+                ));
+            }
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        };
+        let (initial_state, schema_version) = self
+            .gov_api
+            .get_init_state(
+                create_request.governance_id.clone(),
+                create_request.schema_id.clone(),
+                gov_version,
+            )
+            .await?;
+        Ok(Subject::from_start_request(
+            event_request,
+            initial_state,
+            schema_version,
+            Some(subject_keys),
+            gov_version,
+            self.derivator,
+        )?)
+    }
+
+    /// Returns the request whose event is being completed for the subject, if any.
+    fn pending_event_request(
+        &self,
+        subject_id: &DigestIdentifier,
+    ) -> Result<Option<Signed<EventRequest>>, EventError> {
+        if !self.subjects_completing_event.contains_key(subject_id) {
+            return Ok(None);
+        }
+        match self.database.get_prevalidated_event(subject_id) {
+            Ok(event) => return Ok(Some(event.content.event_request)),
+            Err(crate::DbError::EntryNotFound) => {}
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        }
+        match self.database.get_request(subject_id) {
+            Ok(event_request) => Ok(Some(event_request)),
+            Err(crate::DbError::EntryNotFound) => Ok(None),
+            Err(error) => Err(EventError::DatabaseError(error.to_string())),
+        }
+    }
+
+    /// Whether the event being completed for a subject has already been sent to validation.
+    fn is_prevalidated(&self, subject_id: &DigestIdentifier) -> Result<bool, EventError> {
+        match self.database.get_prevalidated_event(subject_id) {
+            Ok(_) => Ok(true),
+            Err(crate::DbError::EntryNotFound) => Ok(false),
+            Err(error) => Err(EventError::DatabaseError(error.to_string())),
+        }
+    }
+
+    /// Stops the completion of the event of a request, removing all its pending data.
+    async fn discard_event(
+        &mut self,
+        subject_id: &DigestIdentifier,
+        event_request: &Signed<EventRequest>,
+    ) -> Result<(), EventError> {
+        self.message_channel
+            .tell(MessageTaskCommand::Cancel(subject_id.to_str()))
+            .await
+            .map_err(EventError::ChannelError)?;
+        // Clean HashMaps
+        let pre_evaluations: Vec<DigestIdentifier> = self
+            .event_pre_evaluations
+            .iter()
+            .filter(|(_, evaluation)| &evaluation.event_request == event_request)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for hash in pre_evaluations {
+            self.event_pre_evaluations.remove(&hash);
+            self.event_evaluations.remove(&hash);
+        }
+        let approval_requests: Vec<DigestIdentifier> = self
+            .approval_requests
+            .iter()
+            .filter(|(_, approval)| &approval.content.event_request == event_request)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for hash in approval_requests {
+            self.approval_requests.remove(&hash);
+            self.approval_eval_signatures.remove(&hash);
+            self.event_approvations.remove(&hash);
+        }
+        let events: Vec<DigestIdentifier> = self
+            .events_to_validate
+            .iter()
+            .filter(|(_, event)| &event.content.event_request == event_request)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for hash in events {
+            self.events_to_validate.remove(&hash);
+            self.event_validation_events.remove(&hash);
+            self.event_validations.remove(&hash);
+        }
+        self.subjects_completing_event.remove(subject_id);
+        self.signer_weights.remove(subject_id);
+        if let EventRequest::Create(create_request) = &event_request.content {
+            // The subject will not exist, so it is not affected by governance updates
+            if let Some(subjects) = self
+                .subjects_by_governance
+                .get_mut(&create_request.governance_id)
+            {
+                subjects.remove(subject_id);
+            }
+        }
+        match self.database.del_prevalidated_event(subject_id) {
+            Ok(_) | Err(crate::DbError::EntryNotFound) => {}
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        }
+        match self.database.del_request(subject_id) {
+            Ok(_) | Err(crate::DbError::EntryNotFound) => {}
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        }
+        if let EventRequest::Migrate(_) = &event_request.content {
+            // The request that was waiting for the migration can not be processed
+            if let Some(waiting) = self.requests_awaiting_migration.remove(subject_id) {
+                self.update_request(&waiting, |request| {
+                    request.fail("The migration of the state of the subject failed".to_owned())
+                })
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a change to the tracking data of a request. A notification is sent if the
+    /// request reaches a new stage or changes its state.
+    async fn update_request<F: FnOnce(&mut TapleRequest)>(
+        &self,
+        event_request: &Signed<EventRequest>,
+        update: F,
+    ) -> Result<(), EventError> {
+        let request_id = DigestIdentifier::generate_with_blake3(event_request)
+            .map_err(|_| EventError::HashGenerationFailed)?;
+        let mut request = match self.database.get_taple_request(&request_id) {
+            Ok(request) => request,
+            Err(crate::DbError::EntryNotFound) => return Ok(()),
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        };
+        let previous = (request.current_stage().cloned(), request.state.clone());
+        update(&mut request);
+        self.database
+            .set_taple_request(&request_id, &request)
+            .map_err(|error| EventError::DatabaseError(error.to_string()))?;
+        if previous != (request.current_stage().cloned(), request.state.clone()) {
+            self.notify_request_update(&request).await;
+        }
+        Ok(())
+    }
+
+    /// Notifies the end of a request once the ledger has processed its event. The request is
+    /// marked as failed if the ledger has not stored the event.
+    async fn request_finished(
         &self,
+        event_request: &Signed<EventRequest>,
+    ) -> Result<(), EventError> {
+        let request_id = DigestIdentifier::generate_with_blake3(event_request)
+            .map_err(|_| EventError::HashGenerationFailed)?;
+        let request = match self.database.get_taple_request(&request_id) {
+            Ok(request) => request,
+            Err(crate::DbError::EntryNotFound) => return Ok(()),
+            Err(error) => return Err(EventError::DatabaseError(error.to_string())),
+        };
+        if request.state == RequestState::Finished {
+            self.notify_request_update(&request).await;
+            return Ok(());
+        }
+        self.update_request(event_request, |request| {
+            request.fail("The event has not been stored in the ledger".to_owned())
+        })
+        .await
+    }
+
+    /// Marks a request as failed after an error that prevents its event from being completed.
+    async fn request_failed(&self, event_request: &Signed<EventRequest>, error: &EventError) {
+        let result = self
+            .update_request(event_request, |request| request.fail(error.to_string()))
+            .await;
+        if let Err(error) = result {
+            log::error!("Error updating the failed request: {}", error);
+        }
+    }
+
+    async fn notify_request_update(&self, request: &TapleRequest) {
+        let _ = self
+            .notification_tx
+            .send(Notification::RequestUpdated {
+                id: request.id.to_str(),
+                subject_id: request.subject_id.as_ref().map(|id| id.to_str()),
+                state: request.state.clone(),
+                stage: request.current_stage().cloned(),
+            })
+            .await;
+    }
+
+    /// Gets the signers and the quorum of a stage of the event of a subject. The weights of
+    /// the votes of the signers are kept until the subject leaves the stage.
+    async fn get_signers_and_quorum(
+        &mut self,
         metadata: Metadata,
         stage: ValidationStage,
     ) -> Result<(HashSet<KeyIdentifier>, u32), EventError> {
@@ -1534,31 +2361,58 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             .get_signers(metadata.clone(), stage.clone())
             .await
             .map_err(EventError::GovernanceError)?;
+        let weights = self
+            .gov_api
+            .get_signer_weights(metadata.clone(), stage.clone())
+            .await
+            .map_err(EventError::GovernanceError)?;
         let quorum_size = self
             .gov_api
-            .get_quorum(metadata, stage)
+            .get_quorum(metadata.clone(), stage)
             .await
             .map_err(EventError::GovernanceError)?;
+        self.signer_weights.insert(metadata.subject_id, weights);
         Ok((signers, quorum_size))
     }
 
+    /// Sends the message of the current stage of the event of a subject to the signers that
+    /// have not answered yet. Each attempt reaches enough of them to obtain the signatures
+    /// still needed, or all of them if there are few.
     async fn ask_signatures(
         &self,
         subject_id: &DigestIdentifier,
         event_message: TapleMessages,
         signers: HashSet<KeyIdentifier>,
-        quorum_size: u32,
+        signatures_needed: u32,
     ) -> Result<(), EventError> {
-        let replication_factor = extend_quorum(quorum_size, signers.len());
+        let weighted = self
+            .signer_weights
+            .get(subject_id)
+            .map_or(false, |weights| weights.values().any(|weight| *weight != 1));
+        // The signatures needed of weighted quorums are not a number of signers
+        let replication_factor = if signers.len() <= self.all_at_once_signers || weighted {
+            1.0
+        } else {
+            extend_quorum(signatures_needed, signers.len())
+        };
+        let retry = match &event_message {
+            TapleMessages::EvaluationMessage(_) => self.retry_policies.evaluation.clone(),
+            TapleMessages::ApprovalMessages(_) => self.retry_policies.approval.clone(),
+            // Validators may have already signed the proof of the event and would not sign
+            // another one with the same sn, so the validation is never given up
+            _ => RetryPolicy {
+                max_attempts: None,
+                deadline: None,
+                ..self.retry_policies.validation.clone()
+            },
+        };
+        let config = MessageConfig::with_retry(retry, replication_factor);
         self.message_channel
             .tell(MessageTaskCommand::Request(
                 Some(String::from(format!("{}", subject_id.to_str()))),
                 event_message,
                 signers.into_iter().collect(),
-                MessageConfig {
-                    timeout: TIMEOUT,
-                    replication_factor,
-                },
+                config,
             ))
             .await
             .map_err(EventError::ChannelError)?;
@@ -1602,6 +2456,7 @@ impl<C: DatabaseCollection> EventCompleter<C> {
             hash_prev_event,
             evaluators: HashSet::new(),
             approvers: HashSet::new(),
+            dependencies: vec![],
         };
         let event_content_hash = event.hash_id(self.derivator)?;
         let subject_keys = subject.keys.as_ref().expect("Somos propietario");
@@ -1625,23 +2480,51 @@ impl<C: DatabaseCollection> EventCompleter<C> {
     }
 }
 
+/// Fraction of the signers that is asked to reach a quorum. Quorums of more than two thirds of
+/// the signers, as BFT ones, are asked to all of them, because they must be reached even if the
+/// faulty signers that they tolerate never answer.
 pub fn extend_quorum(quorum_size: u32, signers_len: usize) -> f64 {
+    if quorum_size as usize * 3 > signers_len * 2 {
+        return 1.0;
+    }
     let quorum_extended =
         quorum_size + (signers_len as f64 * QUORUM_PORCENTAGE_AMPLIFICATION).ceil() as u32;
     quorum_extended as f64 / signers_len as f64
 }
 
+/// Weight of the votes of some signers. Every vote counts as one if the weights are unknown.
+fn weigh_votes<'a>(
+    weights: Option<&SignerWeights>,
+    voters: impl IntoIterator<Item = &'a KeyIdentifier>,
+) -> u32 {
+    match weights {
+        Some(weights) => votes_weight(weights, voters),
+        None => voters.into_iter().count() as u32,
+    }
+}
+
+/// Weight of the votes against an event that makes it impossible to reach the quorum
+fn negative_quorum(
+    weights: Option<&SignerWeights>,
+    signers: &HashSet<KeyIdentifier>,
+    quorum_size: u32,
+) -> u32 {
+    weigh_votes(weights, signers).saturating_sub(quorum_size) + 1
+}
+
 fn count_signatures_with_event_content_hash(
     signatures: &HashSet<(UniqueSignature, bool, DigestIdentifier)>,
     target_event_content_hash: &DigestIdentifier,
+    weights: Option<&SignerWeights>,
 ) -> (u32, u32) {
     let mut ok: u32 = 0;
     let mut ko: u32 = 0;
-    for (_, acceptance, hash) in signatures.iter() {
+    for (signature, acceptance, hash) in signatures.iter() {
         if hash == target_event_content_hash {
+            let weight = weigh_votes(weights, [&signature.signature.signer]);
             match acceptance {
-                true => ok += 1,
-                false => ko += 1,
+                true => ok = ok.saturating_add(weight),
+                false => ko = ko.saturating_add(weight),
             }
         }
     }
@@ -1657,6 +2540,28 @@ fn insert_or_replace_and_check<T: PartialEq + Eq + Hash>(
     replaced
 }
 
+/// Builds the patch of a genesis event, which goes from an empty state to the initial state of
+/// the subject, from the patch returned by the evaluators over the initial state of the schema.
+fn genesis_patch(
+    init_state: &ValueWrapper,
+    json_patch: ValueWrapper,
+) -> Result<ValueWrapper, EventError> {
+    let mut state = init_state.clone();
+    let Ok(patch_json) = serde_json::from_value::<Patch>(json_patch.0) else {
+        return Err(EventError::ErrorParsingJsonString(
+            "Error Parsing Patch".to_owned(),
+        ));
+    };
+    let Ok(()) = patch(&mut state.0, &patch_json) else {
+        return Err(EventError::ErrorApplyingPatch(
+            "Error applying patch".to_owned(),
+        ));
+    };
+    let genesis_patch = serde_json::to_value(diff(&json!({}), &state.0))
+        .map_err(|_| EventError::ErrorParsingValue)?;
+    Ok(ValueWrapper(genesis_patch))
+}
+
 fn hash_match_after_patch(
     evaluation: &EvaluationResponse,
     json_patch: ValueWrapper,
@@ -1692,3 +2597,509 @@ fn hash_match_after_patch(
         Ok(state_hash_calculated == evaluation.state_hash)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+    use serde_json::json;
+
+    use super::{extend_quorum, negative_quorum, weigh_votes, EventCompleter};
+    use crate::commons::channel::{ChannelData, MpscChannel};
+    use crate::commons::crypto::{Ed25519KeyPair, KeyGenerator, KeyMaterial, KeyPair};
+    use crate::commons::models::state::Subject;
+    use crate::commons::self_signature_manager::SelfSignatureManager;
+    use crate::database::{MemoryCollection, DB};
+    use crate::event::errors::EventError;
+    use crate::governance::{stage::ValidationStage, GovernanceAPI, SignerWeights};
+    use crate::identifier::{DigestIdentifier, KeyIdentifier};
+    use crate::ledger::{LedgerCommand, LedgerResponse};
+    use crate::message::{MessageConfig, MessageTaskCommand, TaskExhausted};
+    use crate::protocol::protocol_message_manager::TapleMessages;
+    use crate::request::{
+        EventRequest, FactRequest, MigrateRequest, RequestStage, RequestState, TapleRequest,
+        TransferRequest,
+    };
+    use crate::signature::{Signature, Signed};
+    use crate::utils::test_utils::key;
+    use crate::validation::{ValidationCommand, ValidationEvent};
+    use crate::{
+        DigestDerivator, Event, MemoryManager, RetryPolicies, ValidationProof, ValueWrapper,
+    };
+
+    const SUBJECT_ID: &str = "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs";
+
+    struct TestCompleter {
+        completer: EventCompleter<MemoryCollection>,
+        database: DB<MemoryCollection>,
+        // Kept alive so that the messages of the completer can be sent
+        message_rx: MpscChannel<MessageTaskCommand<TapleMessages>, ()>,
+        _ledger_rx: MpscChannel<LedgerCommand, LedgerResponse>,
+        _notification_rx: tokio::sync::mpsc::Receiver<crate::Notification>,
+    }
+
+    fn build_completer() -> TestCompleter {
+        let manager = Arc::new(MemoryManager::new());
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[1]));
+        let signature_manager = SelfSignatureManager {
+            identifier: KeyIdentifier::new(keys.get_key_derivator(), &keys.public_key_bytes()),
+            keys,
+            digest_derivator: DigestDerivator::Blake3_256,
+        };
+        let (_gov_rx, gov_tx) = MpscChannel::new(10);
+        let (message_rx, message_tx) = MpscChannel::new(10);
+        let (ledger_rx, ledger_tx) = MpscChannel::new(10);
+        let (notification_tx, notification_rx) = tokio::sync::mpsc::channel(10);
+        let completer = EventCompleter::new(
+            GovernanceAPI::new(gov_tx),
+            DB::new(manager.clone()),
+            message_tx,
+            notification_tx,
+            ledger_tx,
+            signature_manager.identifier.clone(),
+            signature_manager,
+            RetryPolicies::default(),
+            0,
+            DigestDerivator::Blake3_256,
+        );
+        TestCompleter {
+            completer,
+            database: DB::new(manager),
+            message_rx: message_rx,
+            _ledger_rx: ledger_rx,
+            _notification_rx: notification_rx,
+        }
+    }
+
+    /// Stores a fact request whose event is in the given stage of its completion.
+    fn start_request(test: &mut TestCompleter, stage: ValidationStage) -> DigestIdentifier {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[2]));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let request = EventRequest::Fact(FactRequest {
+            subject_id: subject_id.clone(),
+            payload: ValueWrapper(json!({})),
+        });
+        let event_request = Signed::<EventRequest> {
+            signature: Signature::new(&request, &keys, DigestDerivator::Blake3_256).unwrap(),
+            content: request,
+        };
+        let taple_request: TapleRequest = event_request.clone().try_into().unwrap();
+        test.database
+            .set_taple_request(&taple_request.id, &taple_request)
+            .unwrap();
+        if stage == ValidationStage::Validate {
+            let event = Event {
+                subject_id: subject_id.clone(),
+                event_request,
+                sn: 1,
+                gov_version: 0,
+                patch: ValueWrapper(json!([])),
+                state_hash: DigestIdentifier::default(),
+                eval_success: true,
+                appr_required: false,
+                approved: true,
+                hash_prev_event: DigestIdentifier::default(),
+                evaluators: HashSet::new(),
+                approvers: HashSet::new(),
+                dependencies: vec![],
+            };
+            let event = Signed::<Event> {
+                signature: Signature::new(&event, &keys, DigestDerivator::Blake3_256).unwrap(),
+                content: event,
+            };
+            test.database
+                .set_prevalidated_event(&subject_id, event)
+                .unwrap();
+        } else {
+            test.database
+                .set_request(&subject_id, event_request)
+                .unwrap();
+        }
+        test.completer
+            .subjects_completing_event
+            .insert(subject_id, (stage, HashSet::from([key(3)]), (1, 1)));
+        taple_request.id
+    }
+
+    #[tokio::test]
+    async fn cancel_request_during_evaluation() {
+        let mut test = build_completer();
+        let request_id = start_request(&mut test, ValidationStage::Evaluate);
+        test.completer
+            .cancel_request(request_id.clone())
+            .await
+            .unwrap();
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        assert!(!test
+            .completer
+            .subjects_completing_event
+            .contains_key(&subject_id));
+        let request = test.database.get_taple_request(&request_id).unwrap();
+        assert_eq!(request.state, RequestState::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn cancel_request_refused_during_validation() {
+        let mut test = build_completer();
+        let request_id = start_request(&mut test, ValidationStage::Validate);
+        let result = test.completer.cancel_request(request_id.clone()).await;
+        assert!(matches!(result, Err(EventError::RequestInValidation(_))));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        assert!(test
+            .completer
+            .subjects_completing_event
+            .contains_key(&subject_id));
+        assert!(test.database.get_prevalidated_event(&subject_id).is_ok());
+        let request = test.database.get_taple_request(&request_id).unwrap();
+        assert_eq!(request.state, RequestState::Processing);
+    }
+
+    #[test]
+    fn votes_are_weighed_when_weights_are_known() {
+        let weights = SignerWeights::from([(key(1), 3), (key(2), 1)]);
+        assert_eq!(weigh_votes(Some(&weights), &[key(1), key(2)]), 4);
+        assert_eq!(weigh_votes(Some(&weights), &[key(2), key(3)]), 1);
+        assert_eq!(weigh_votes(None, &[key(1), key(2)]), 2);
+    }
+
+    #[test]
+    fn negative_quorum_does_not_underflow() {
+        let signers = HashSet::from([key(1), key(2)]);
+        let weights = SignerWeights::from([(key(1), 3), (key(2), 1)]);
+        assert_eq!(negative_quorum(Some(&weights), &signers, 3), 2);
+        assert_eq!(negative_quorum(None, &signers, 2), 1);
+        // Quorums above the weight of the signers can not be reached
+        assert_eq!(negative_quorum(Some(&weights), &signers, 10), 1);
+    }
+
+    #[tokio::test]
+    async fn validation_is_retried_until_it_completes() {
+        let mut test = build_completer();
+        test.completer.retry_policies.validation.max_attempts = Some(1);
+        test.completer.retry_policies.validation.deadline = Some(1000);
+        let request_id = start_request(&mut test, ValidationStage::Validate);
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let proof = ValidationProof {
+            subject_id: subject_id.clone(),
+            schema_id: "test".to_owned(),
+            namespace: "".to_owned(),
+            name: "test".to_owned(),
+            subject_public_key: key(4),
+            governance_id: DigestIdentifier::default(),
+            genesis_governance_version: 0,
+            sn: 1,
+            prev_event_hash: DigestIdentifier::default(),
+            event_hash: DigestIdentifier::default(),
+            governance_version: 0,
+            dependencies: vec![],
+        };
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[4]));
+        let message = TapleMessages::ValidationMessage(ValidationCommand::AskForValidation(
+            ValidationEvent {
+                subject_signature: Signature::new(&proof, &keys, DigestDerivator::Blake3_256)
+                    .unwrap(),
+                proof,
+                previous_proof: None,
+                prev_event_validation_signatures: HashSet::new(),
+            },
+        ));
+        test.completer
+            .ask_signatures(&subject_id, message.clone(), HashSet::from([key(3)]), 1)
+            .await
+            .unwrap();
+        let Some(ChannelData::TellData(data)) = test.message_rx.receive().await else {
+            panic!("Validation not requested");
+        };
+        let MessageTaskCommand::Request(_, _, _, config) = data.get() else {
+            panic!("Validation not requested");
+        };
+        let retry = config.retry().unwrap();
+        assert!(!retry.is_bounded());
+        // The validation is not given up even if the policy was exhausted
+        test.completer
+            .retries_exhausted(TaskExhausted {
+                id: subject_id.to_str(),
+                content: message,
+            })
+            .await
+            .unwrap();
+        assert!(test
+            .completer
+            .subjects_completing_event
+            .contains_key(&subject_id));
+        let request = test.database.get_taple_request(&request_id).unwrap();
+        assert_eq!(request.state, RequestState::Processing);
+    }
+
+    /// Stores a fact request that waits for the migration of its subject, as it is found after
+    /// a restart. The migration is in progress if `migrating` is set.
+    fn await_migration(test: &mut TestCompleter, migrating: bool) -> DigestIdentifier {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[2]));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let request = EventRequest::Fact(FactRequest {
+            subject_id: subject_id.clone(),
+            payload: ValueWrapper(json!({})),
+        });
+        let event_request = Signed::<EventRequest> {
+            signature: Signature::new(&request, &keys, DigestDerivator::Blake3_256).unwrap(),
+            content: request,
+        };
+        let mut taple_request: TapleRequest = event_request.try_into().unwrap();
+        taple_request.start_stage(RequestStage::Migration);
+        test.database
+            .set_taple_request(&taple_request.id, &taple_request)
+            .unwrap();
+        if migrating {
+            let migrate_request = EventRequest::Migrate(MigrateRequest {
+                subject_id: subject_id.clone(),
+                schema_version: None,
+            });
+            let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[1]));
+            let migrate_request = Signed::<EventRequest> {
+                signature: Signature::new(&migrate_request, &keys, DigestDerivator::Blake3_256)
+                    .unwrap(),
+                content: migrate_request,
+            };
+            test.database
+                .set_request(&subject_id, migrate_request)
+                .unwrap();
+            test.completer.subjects_completing_event.insert(
+                subject_id,
+                (ValidationStage::Evaluate, HashSet::from([key(3)]), (1, 0)),
+            );
+        }
+        taple_request.id
+    }
+
+    #[tokio::test]
+    async fn requests_wait_again_for_migrations_in_progress() {
+        let mut test = build_completer();
+        let request_id = await_migration(&mut test, true);
+        test.completer
+            .restore_requests_awaiting_migration()
+            .await
+            .unwrap();
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let request = test.database.get_taple_request(&request_id).unwrap();
+        assert_eq!(
+            test.completer.requests_awaiting_migration.get(&subject_id),
+            Some(&request.event_request)
+        );
+        assert_eq!(request.state, RequestState::Processing);
+    }
+
+    #[tokio::test]
+    async fn requests_are_processed_after_finished_migrations() {
+        let mut test = build_completer();
+        let request_id = await_migration(&mut test, false);
+        test.completer
+            .restore_requests_awaiting_migration()
+            .await
+            .unwrap();
+        assert!(test.completer.requests_awaiting_migration.is_empty());
+        // The subject is unknown, so processing the request fails instead of leaving it waiting
+        let request = test.database.get_taple_request(&request_id).unwrap();
+        assert_eq!(request.state, RequestState::Error);
+    }
+
+    #[tokio::test]
+    async fn transfers_are_evaluated_again_on_governance_updates() {
+        let mut test = build_completer();
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[2]));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let governance_id = DigestIdentifier::generate_with_blake3(&1u8).unwrap();
+        let request = EventRequest::Transfer(TransferRequest {
+            subject_id: subject_id.clone(),
+            public_key: key(4),
+        });
+        let event_request = Signed::<EventRequest> {
+            signature: Signature::new(&request, &keys, DigestDerivator::Blake3_256).unwrap(),
+            content: request,
+        };
+        test.database
+            .set_request(&subject_id, event_request)
+            .unwrap();
+        test.completer
+            .subjects_by_governance
+            .insert(governance_id.clone(), HashSet::from([subject_id.clone()]));
+        let result = test
+            .completer
+            .new_governance_version(governance_id, 1)
+            .await;
+        // The subject is unknown, so the transfer fails when it is evaluated again
+        assert!(
+            matches!(result, Err(EventError::SubjectNotFound(id)) if id == subject_id.to_str())
+        );
+    }
+
+    /// Starts the validation of an event of a governance, whose version is the sn of the
+    /// governance, by the validators with seeds from 10. Returns the hash of the event and the
+    /// proof signed by the validators.
+    fn start_validation(
+        test: &mut TestCompleter,
+        validators: u8,
+        quorum_size: u32,
+        weights: Option<SignerWeights>,
+    ) -> (DigestIdentifier, ValidationProof) {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[2]));
+        let subject_id = DigestIdentifier::from_str(SUBJECT_ID).unwrap();
+        let subject = Subject {
+            keys: Some(keys.clone()),
+            subject_id: subject_id.clone(),
+            governance_id: DigestIdentifier::default(),
+            sn: 1,
+            genesis_gov_version: 0,
+            public_key: key(2),
+            namespace: "".to_owned(),
+            name: "governance".to_owned(),
+            schema_id: "governance".to_owned(),
+            schema_version: 0,
+            owner: key(2),
+            creator: key(2),
+            properties: ValueWrapper(json!({})),
+            active: true,
+        };
+        test.database.set_subject(&subject_id, subject).unwrap();
+        let request = EventRequest::Fact(FactRequest {
+            subject_id: subject_id.clone(),
+            payload: ValueWrapper(json!({})),
+        });
+        let event_request = Signed::<EventRequest> {
+            signature: Signature::new(&request, &keys, DigestDerivator::Blake3_256).unwrap(),
+            content: request,
+        };
+        let event = Event {
+            subject_id: subject_id.clone(),
+            event_request,
+            sn: 2,
+            gov_version: 1,
+            patch: ValueWrapper(json!([])),
+            state_hash: DigestIdentifier::default(),
+            eval_success: true,
+            appr_required: false,
+            approved: true,
+            hash_prev_event: DigestIdentifier::default(),
+            evaluators: HashSet::new(),
+            approvers: HashSet::new(),
+            dependencies: vec![],
+        };
+        let event_hash =
+            DigestIdentifier::from_serializable_borsh(&event, DigestDerivator::Blake3_256).unwrap();
+        let proof = ValidationProof {
+            subject_id: subject_id.clone(),
+            schema_id: "governance".to_owned(),
+            namespace: "".to_owned(),
+            name: "governance".to_owned(),
+            subject_public_key: key(2),
+            governance_id: DigestIdentifier::default(),
+            genesis_governance_version: 0,
+            sn: 2,
+            prev_event_hash: DigestIdentifier::default(),
+            event_hash: event_hash.clone(),
+            governance_version: 1,
+            dependencies: vec![],
+        };
+        let validation_event = ValidationEvent {
+            subject_signature: Signature::new(&proof, &keys, DigestDerivator::Blake3_256).unwrap(),
+            proof: proof.clone(),
+            previous_proof: None,
+            prev_event_validation_signatures: HashSet::new(),
+        };
+        let event = Signed::<Event> {
+            signature: Signature::new(&event, &keys, DigestDerivator::Blake3_256).unwrap(),
+            content: event,
+        };
+        test.completer
+            .events_to_validate
+            .insert(event_hash.clone(), event);
+        test.completer
+            .event_validation_events
+            .insert(event_hash.clone(), validation_event);
+        let signers = (10..10 + validators).map(key).collect();
+        test.completer.subjects_completing_event.insert(
+            subject_id.clone(),
+            (ValidationStage::Validate, signers, (quorum_size, 0)),
+        );
+        if let Some(weights) = weights {
+            test.completer.signer_weights.insert(subject_id, weights);
+        }
+        (event_hash, proof)
+    }
+
+    /// Gives the answer of a validator and returns the validators asked again and the
+    /// configuration of the request.
+    async fn answer_validation(
+        test: &mut TestCompleter,
+        event_hash: &DigestIdentifier,
+        proof: &ValidationProof,
+        validator: u8,
+    ) -> (HashSet<KeyIdentifier>, MessageConfig) {
+        let keys = KeyPair::Ed25519(Ed25519KeyPair::from_seed(&[validator]));
+        let signature = Signature::new(proof, &keys, DigestDerivator::Blake3_256).unwrap();
+        test.completer
+            .validation_signatures(event_hash.clone(), signature, 1)
+            .await
+            .unwrap();
+        let Some(ChannelData::TellData(data)) = test.message_rx.receive().await else {
+            panic!("Validation not requested");
+        };
+        let MessageTaskCommand::Request(_, _, targets, config) = data.get() else {
+            panic!("Validation not requested");
+        };
+        (targets.into_iter().collect(), config)
+    }
+
+    #[tokio::test]
+    async fn validations_are_asked_again_to_the_missing_validators() {
+        let mut test = build_completer();
+        let (event_hash, proof) = start_validation(&mut test, 10, 3, None);
+        let (targets, config) = answer_validation(&mut test, &event_hash, &proof, 10).await;
+        assert_eq!(targets, (11..20).map(key).collect::<HashSet<_>>());
+        // Only a fraction of the missing validators is asked in each attempt
+        assert_eq!(config.replication_factor(), extend_quorum(2, 9));
+        assert!(config.replication_factor() < 1.0);
+        let (targets, config) = answer_validation(&mut test, &event_hash, &proof, 15).await;
+        assert_eq!(
+            targets,
+            [11, 12, 13, 14, 16, 17, 18, 19]
+                .into_iter()
+                .map(key)
+                .collect::<HashSet<_>>()
+        );
+        assert_eq!(config.replication_factor(), extend_quorum(1, 8));
+    }
+
+    #[tokio::test]
+    async fn few_missing_validators_are_asked_at_once() {
+        let mut test = build_completer();
+        test.completer.all_at_once_signers = 9;
+        let (event_hash, proof) = start_validation(&mut test, 11, 3, None);
+        // Ten validators are missing, more than the threshold
+        let (targets, config) = answer_validation(&mut test, &event_hash, &proof, 10).await;
+        assert_eq!(targets.len(), 10);
+        assert!(config.replication_factor() < 1.0);
+        let (targets, config) = answer_validation(&mut test, &event_hash, &proof, 11).await;
+        assert_eq!(targets, (12..21).map(key).collect::<HashSet<_>>());
+        assert_eq!(config.replication_factor(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn weighted_validations_are_asked_to_every_missing_validator() {
+        let mut test = build_completer();
+        let mut weights: SignerWeights = (10..20).map(|seed| (key(seed), 1)).collect();
+        weights.insert(key(10), 3);
+        let (event_hash, proof) = start_validation(&mut test, 10, 4, Some(weights));
+        let (targets, config) = answer_validation(&mut test, &event_hash, &proof, 11).await;
+        assert_eq!(
+            targets,
+            [10, 12, 13, 14, 15, 16, 17, 18, 19]
+                .into_iter()
+                .map(key)
+                .collect::<HashSet<_>>()
+        );
+        // The missing weight would be asked to a fraction of the validators if the votes
+        // were not weighted
+        assert!(extend_quorum(3, 9) < 1.0);
+        assert_eq!(config.replication_factor(), 1.0);
+    }
+}
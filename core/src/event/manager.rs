@@ -1,12 +1,15 @@
 use async_trait::async_trait;
 use tokio_util::sync::CancellationToken;
 
-use super::{errors::EventError, event_completer::EventCompleter, EventCommand, EventResponse};
+use super::{
+    errors::EventError, event_completer::EventCompleter, EventAPICommand, EventCommand,
+    EventResponse,
+};
 use crate::commons::self_signature_manager::SelfSignatureManager;
 use crate::database::{DatabaseCollection, DB};
 use crate::governance::error::RequestError;
 use crate::governance::GovernanceUpdatedMessage;
-use crate::identifier::KeyIdentifier;
+use crate::identifier::{DigestIdentifier, KeyIdentifier};
 use crate::ledger::{LedgerCommand, LedgerResponse};
 use crate::protocol::protocol_message_manager::TapleMessages;
 use crate::signature::Signed;
@@ -14,24 +17,29 @@ use crate::{EventRequest, DigestDerivator};
 use crate::{
     commons::channel::{ChannelData, MpscChannel, SenderEnd},
     governance::GovernanceAPI,
-    message::MessageTaskCommand,
-    Notification,
+    message::{MessageTaskCommand, TaskExhausted},
+    Notification, RetryPolicies,
 };
 
 #[derive(Clone, Debug)]
 pub struct EventAPI {
     sender: SenderEnd<EventCommand, EventResponse>,
+    api_sender: SenderEnd<EventAPICommand, EventResponse>,
 }
 
 impl EventAPI {
-    pub fn new(sender: SenderEnd<EventCommand, EventResponse>) -> Self {
-        Self { sender }
+    pub fn new(
+        sender: SenderEnd<EventCommand, EventResponse>,
+        api_sender: SenderEnd<EventAPICommand, EventResponse>,
+    ) -> Self {
+        Self { sender, api_sender }
     }
 }
 
 #[async_trait]
 pub trait EventAPIInterface {
     async fn send_event_request(&self, event_request: Signed<EventRequest>) -> EventResponse;
+    async fn cancel_request(&self, request_id: DigestIdentifier) -> EventResponse;
 }
 
 #[async_trait]
@@ -42,12 +50,26 @@ impl EventAPIInterface for EventAPI {
             Err(_) => EventResponse::Event(Err(EventError::EventApiChannelNotAvailable)),
         }
     }
+
+    async fn cancel_request(&self, request_id: DigestIdentifier) -> EventResponse {
+        match self
+            .api_sender
+            .ask(EventAPICommand::CancelRequest { request_id })
+            .await
+        {
+            Ok(response) => response,
+            Err(_) => EventResponse::CancelRequest(Err(EventError::EventApiChannelNotAvailable)),
+        }
+    }
 }
 
 pub struct EventManager<C: DatabaseCollection> {
     /// Communication channel for incoming petitions
     input_channel: MpscChannel<EventCommand, EventResponse>,
+    /// Communication channel for the commands of the API of the node
+    api_channel: MpscChannel<EventAPICommand, EventResponse>,
     input_channel_updated_gov: tokio::sync::broadcast::Receiver<GovernanceUpdatedMessage>,
+    input_channel_task_exhausted: tokio::sync::broadcast::Receiver<TaskExhausted<TapleMessages>>,
     event_completer: EventCompleter<C>,
     token: CancellationToken,
     notification_tx: tokio::sync::mpsc::Sender<Notification>,
@@ -56,7 +78,11 @@ pub struct EventManager<C: DatabaseCollection> {
 impl<C: DatabaseCollection> EventManager<C> {
     pub fn new(
         input_channel: MpscChannel<EventCommand, EventResponse>,
+        api_channel: MpscChannel<EventAPICommand, EventResponse>,
         input_channel_updated_gov: tokio::sync::broadcast::Receiver<GovernanceUpdatedMessage>,
+        input_channel_task_exhausted: tokio::sync::broadcast::Receiver<
+            TaskExhausted<TapleMessages>,
+        >,
         gov_api: GovernanceAPI,
         database: DB<C>,
         token: CancellationToken,
@@ -65,11 +91,15 @@ impl<C: DatabaseCollection> EventManager<C> {
         ledger_sender: SenderEnd<LedgerCommand, LedgerResponse>,
         own_identifier: KeyIdentifier,
         signature_manager: SelfSignatureManager,
+        retry_policies: RetryPolicies,
+        all_at_once_signers: usize,
         derivator: DigestDerivator,
     ) -> Self {
         Self {
             input_channel,
+            api_channel,
             input_channel_updated_gov,
+            input_channel_task_exhausted,
             event_completer: EventCompleter::new(
                 gov_api,
                 database,
@@ -78,6 +108,8 @@ impl<C: DatabaseCollection> EventManager<C> {
                 ledger_sender,
                 own_identifier,
                 signature_manager,
+                retry_policies,
+                all_at_once_signers,
                 derivator
             ),
             token,
@@ -110,6 +142,14 @@ impl<C: DatabaseCollection> EventManager<C> {
                         },
                     }
                 },
+                // The API may be dropped before the node stops
+                Some(command) = self.api_channel.receive() => {
+                    let result = self.process_api_command(command).await;
+                    if result.is_err() {
+                        log::error!("{}", result.unwrap_err());
+                        break;
+                    }
+                },
                 gov_updated = self.input_channel_updated_gov.recv() => {
                     match gov_updated {
                         Ok(gov_updated) => {
@@ -127,6 +167,22 @@ impl<C: DatabaseCollection> EventManager<C> {
                         },
                     }
                 },
+                task_exhausted = self.input_channel_task_exhausted.recv() => {
+                    match task_exhausted {
+                        Ok(task) => {
+                            let result = self.event_completer.retries_exhausted(task).await;
+                            if let Err(error) = result {
+                                log::error!("{}", error);
+                            }
+                        },
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            log::warn!("{} exhausted message tasks have been missed", skipped);
+                        },
+                        Err(_) => {
+                            break;
+                        },
+                    }
+                },
                 _ = self.token.cancelled() => {
                     log::debug!("Shutdown received");
                     break;
@@ -291,4 +347,35 @@ impl<C: DatabaseCollection> EventManager<C> {
         }
         Ok(())
     }
+
+    async fn process_api_command(
+        &mut self,
+        command: ChannelData<EventAPICommand, EventResponse>,
+    ) -> Result<(), EventError> {
+        let (sender, data) = match command {
+            ChannelData::AskData(data) => {
+                let (sender, data) = data.get();
+                (Some(sender), data)
+            }
+            ChannelData::TellData(data) => {
+                let data = data.get();
+                (None, data)
+            }
+        };
+        let response = match data {
+            EventAPICommand::CancelRequest { request_id } => {
+                let response = self.event_completer.cancel_request(request_id).await;
+                if let Err(EventError::ChannelClosed) = response {
+                    log::error!("Channel Closed");
+                    self.token.cancel();
+                    return Err(EventError::ChannelClosed);
+                }
+                EventResponse::CancelRequest(response)
+            }
+        };
+        if let Some(sender) = sender {
+            sender.send(response).expect("Sender Dropped");
+        }
+        Ok(())
+    }
 }
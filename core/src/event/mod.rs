@@ -36,8 +36,16 @@ pub enum EventCommand {
     },
 }
 
+/// Commands that only the API of the node may send. They are never part of a message of
+/// the network.
+#[derive(Debug, Clone)]
+pub enum EventAPICommand {
+    CancelRequest { request_id: DigestIdentifier },
+}
+
 #[derive(Debug, Clone)]
 pub enum EventResponse {
     Event(Result<DigestIdentifier, EventError>),
+    CancelRequest(Result<(), EventError>),
     NoResponse,
 }
@@ -8,7 +8,10 @@ use crate::{
         channel::{ChannelData, MpscChannel, SenderEnd},
         identifier::{DigestIdentifier, KeyIdentifier},
         models::event::Metadata,
-        schema_handler::{get_governance_schema, gov_models::Contract},
+        schema_handler::{
+            get_governance_schema,
+            gov_models::{Contract, ContractHook},
+        },
     },
     DatabaseCollection, DatabaseManager, Notification, ValueWrapper, DB,
 };
@@ -17,7 +20,7 @@ use super::{
     error::{InternalError, RequestError},
     inner_governance::InnerGovernance,
     stage::ValidationStage,
-    GovernanceMessage, GovernanceResponse, GovernanceUpdatedMessage,
+    GovernanceMessage, GovernanceResponse, GovernanceUpdatedMessage, SignerWeights,
 };
 
 pub struct Governance<M: DatabaseManager<C>, C: DatabaseCollection> {
@@ -89,11 +92,13 @@ impl<M: DatabaseManager<C>, C: DatabaseCollection> Governance<M, C> {
                     GovernanceMessage::GetSchema {
                         governance_id,
                         schema_id,
+                        schema_version,
                         governance_version,
                     } => {
                         let to_send = self.inner_governance.get_schema(
                             governance_id,
                             schema_id,
+                            schema_version,
                             governance_version,
                         )?;
                         Ok(sender
@@ -110,6 +115,12 @@ impl<M: DatabaseManager<C>, C: DatabaseCollection> Governance<M, C> {
                         let to_send = self.inner_governance.get_quorum(metadata, stage)?;
                         Ok(sender.send(GovernanceResponse::GetQuorum(to_send)).unwrap())
                     }
+                    GovernanceMessage::GetSignerWeights { metadata, stage } => {
+                        let to_send = self.inner_governance.get_signer_weights(metadata, stage)?;
+                        Ok(sender
+                            .send(GovernanceResponse::GetSignerWeights(to_send))
+                            .map_err(|_| InternalError::OneshotClosed)?)
+                    }
                     GovernanceMessage::GetGovernanceVersion {
                         governance_id,
                         subject_id,
@@ -150,6 +161,22 @@ impl<M: DatabaseManager<C>, C: DatabaseCollection> Governance<M, C> {
                             .send(GovernanceResponse::GetContracts(to_send))
                             .map_err(|_| InternalError::OneshotClosed)?)
                     }
+                    GovernanceMessage::GetContractHooks {
+                        governance_id,
+                        schema_id,
+                        schema_version,
+                        governance_version,
+                    } => {
+                        let to_send = self.inner_governance.get_contract_hooks(
+                            governance_id,
+                            schema_id,
+                            schema_version,
+                            governance_version,
+                        )?;
+                        Ok(sender
+                            .send(GovernanceResponse::GetContractHooks(to_send))
+                            .map_err(|_| InternalError::OneshotClosed)?)
+                    }
                     GovernanceMessage::GetInitState {
                         governance_id,
                         schema_id,
@@ -203,11 +230,12 @@ pub trait GovernanceInterface: Sync + Send {
         governance_id: DigestIdentifier,
         schema_id: String,
         governance_version: u64,
-    ) -> Result<ValueWrapper, RequestError>;
+    ) -> Result<(ValueWrapper, u64), RequestError>;
     async fn get_schema(
         &self,
         governance_id: DigestIdentifier,
         schema_id: String,
+        schema_version: u64,
         governance_version: u64,
     ) -> Result<ValueWrapper, RequestError>;
 
@@ -223,6 +251,12 @@ pub trait GovernanceInterface: Sync + Send {
         stage: ValidationStage,
     ) -> Result<u32, RequestError>;
 
+    async fn get_signer_weights(
+        &self,
+        metadata: Metadata,
+        stage: ValidationStage,
+    ) -> Result<SignerWeights, RequestError>;
+
     async fn get_invoke_info(
         &self,
         metadata: Metadata,
@@ -234,7 +268,15 @@ pub trait GovernanceInterface: Sync + Send {
         &self,
         governance_id: DigestIdentifier,
         governance_version: u64,
-    ) -> Result<Vec<(Contract, String)>, RequestError>;
+    ) -> Result<Vec<(Contract, String, u64)>, RequestError>;
+
+    async fn get_contract_hooks(
+        &self,
+        governance_id: DigestIdentifier,
+        schema_id: String,
+        schema_version: u64,
+        governance_version: u64,
+    ) -> Result<Vec<ContractHook>, RequestError>;
 
     async fn get_governance_version(
         &self,
@@ -269,7 +311,7 @@ impl GovernanceInterface for GovernanceAPI {
         governance_id: DigestIdentifier,
         schema_id: String,
         governance_version: u64,
-    ) -> Result<ValueWrapper, RequestError> {
+    ) -> Result<(ValueWrapper, u64), RequestError> {
         let response = self
             .sender
             .ask(GovernanceMessage::GetInitState {
@@ -290,6 +332,7 @@ impl GovernanceInterface for GovernanceAPI {
         &self,
         governance_id: DigestIdentifier,
         schema_id: String,
+        schema_version: u64,
         governance_version: u64,
     ) -> Result<ValueWrapper, RequestError> {
         let response = self
@@ -297,6 +340,7 @@ impl GovernanceInterface for GovernanceAPI {
             .ask(GovernanceMessage::GetSchema {
                 governance_id: governance_id.clone(),
                 schema_id,
+                schema_version,
                 governance_version,
             })
             .await
@@ -348,6 +392,23 @@ impl GovernanceInterface for GovernanceAPI {
         }
     }
 
+    async fn get_signer_weights(
+        &self,
+        metadata: Metadata,
+        stage: ValidationStage,
+    ) -> Result<SignerWeights, RequestError> {
+        let response = self
+            .sender
+            .ask(GovernanceMessage::GetSignerWeights { metadata, stage })
+            .await
+            .map_err(|_| RequestError::ChannelClosed)?;
+        if let GovernanceResponse::GetSignerWeights(weights) = response {
+            weights
+        } else {
+            Err(RequestError::UnexpectedResponse)
+        }
+    }
+
     async fn get_invoke_info(
         &self,
         metadata: Metadata,
@@ -374,7 +435,7 @@ impl GovernanceInterface for GovernanceAPI {
         &self,
         governance_id: DigestIdentifier,
         governance_version: u64,
-    ) -> Result<Vec<(Contract, String)>, RequestError> {
+    ) -> Result<Vec<(Contract, String, u64)>, RequestError> {
         let response = self
             .sender
             .ask(GovernanceMessage::GetContracts {
@@ -390,6 +451,30 @@ impl GovernanceInterface for GovernanceAPI {
         }
     }
 
+    async fn get_contract_hooks(
+        &self,
+        governance_id: DigestIdentifier,
+        schema_id: String,
+        schema_version: u64,
+        governance_version: u64,
+    ) -> Result<Vec<ContractHook>, RequestError> {
+        let response = self
+            .sender
+            .ask(GovernanceMessage::GetContractHooks {
+                governance_id,
+                schema_id,
+                schema_version,
+                governance_version,
+            })
+            .await
+            .map_err(|_| RequestError::ChannelClosed)?;
+        if let GovernanceResponse::GetContractHooks(hooks) = response {
+            hooks
+        } else {
+            Err(RequestError::UnexpectedResponse)
+        }
+    }
+
     async fn get_governance_version(
         &self,
         governance_id: DigestIdentifier,
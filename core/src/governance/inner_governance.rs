@@ -9,7 +9,7 @@ use crate::{
         identifier::{Derivable, DigestIdentifier, KeyIdentifier},
         models::{event::Metadata, state::Subject},
         schema_handler::{
-            gov_models::{Contract, Quorum, Role, SchemaEnum, Who},
+            gov_models::{Contract, ContractHook, Quorum, Role, SchemaEnum, Who},
             initial_state::get_governance_initial_state,
         },
     },
@@ -21,7 +21,7 @@ use serde_json::Value;
 use super::{
     error::{InternalError, RequestError},
     stage::ValidationStage,
-    GovernanceUpdatedMessage,
+    total_weight, GovernanceUpdatedMessage, SignerWeights,
 };
 
 use crate::database::{DatabaseCollection, DB};
@@ -51,9 +51,9 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
         governance_id: DigestIdentifier,
         schema_id: String,
         governance_version: u64,
-    ) -> Result<Result<ValueWrapper, RequestError>, InternalError> {
+    ) -> Result<Result<(ValueWrapper, u64), RequestError>, InternalError> {
         if governance_id.digest.is_empty() {
-            return Ok(Ok(get_governance_initial_state()));
+            return Ok(Ok((get_governance_initial_state(), 0)));
         }
         let governance = match self.governance_event_sourcing(&governance_id, governance_version) {
             Ok(subject) => subject,
@@ -65,13 +65,11 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
             },
         };
         let schemas = get_as_array(&governance.properties.0, "schemas")?;
-        for schema in schemas {
-            let tmp = get_as_str(schema, "id")?;
-            if tmp == &schema_id {
-                return Ok(Ok(ValueWrapper(
-                    schema.get("initial_value").unwrap().to_owned(),
-                )));
-            }
+        if let Some(schema) = find_schema(schemas, &schema_id, None)? {
+            return Ok(Ok((
+                ValueWrapper(schema.get("initial_value").unwrap().to_owned()),
+                get_schema_version(schema),
+            )));
         }
         return Ok(Err(RequestError::SchemaNotFound(schema_id)));
     }
@@ -81,6 +79,7 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
         &self,
         governance_id: DigestIdentifier,
         schema_id: String,
+        schema_version: u64,
         governance_version: u64,
     ) -> Result<Result<ValueWrapper, RequestError>, InternalError> {
         if schema_id == "governance" {
@@ -96,11 +95,8 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
             },
         };
         let schemas = get_as_array(&governance.properties.0, "schemas")?;
-        for schema in schemas {
-            let tmp = get_as_str(schema, "id")?;
-            if tmp == &schema_id {
-                return Ok(Ok(ValueWrapper(schema.get("schema").unwrap().to_owned())));
-            }
+        if let Some(schema) = find_schema(schemas, &schema_id, Some(schema_version))? {
+            return Ok(Ok(ValueWrapper(schema.get("schema").unwrap().to_owned())));
         }
         return Ok(Err(RequestError::SchemaNotFound(schema_id)));
     }
@@ -237,7 +233,58 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
         metadata: Metadata,
         stage: ValidationStage,
     ) -> Result<Result<u32, RequestError>, InternalError> {
-        if ValidationStage::Witness == stage {
+        let (quorum, properties) = match self.get_stage_quorum(&metadata, &stage)? {
+            Ok(data) => data,
+            Err(error) => return Ok(Err(error)),
+        };
+        let signers = self.get_signers(metadata, stage)?;
+        let Ok(signers) = signers else {
+            return Ok(Err(signers.unwrap_err()));
+        };
+        match quorum {
+            Quorum::MAJORITY => Ok(Ok((signers.len() as u32 / 2) + 1)),
+            Quorum::FIXED { fixed } => Ok(Ok(fixed)),
+            Quorum::PERCENTAGE { percentage } => {
+                let result = (signers.len() as f64 * percentage).ceil() as u32;
+                Ok(Ok(result))
+            }
+            // More than two thirds of the signers
+            Quorum::BFT => Ok(Ok((signers.len() as u32 * 2 / 3) + 1)),
+            Quorum::WEIGHTED { weighted } => {
+                let weights = get_member_weights(&properties.0, signers)?;
+                let quorum = (total_weight(&weights) as f64 * weighted).ceil() as u32;
+                // At least one vote, even if the fraction rounds down to nothing
+                Ok(Ok(quorum.max(1)))
+            }
+        }
+    }
+
+    // Devuelve el peso del voto de cada firmante. Todos valen 1 salvo en los quórums ponderados
+    pub fn get_signer_weights(
+        &self,
+        metadata: Metadata,
+        stage: ValidationStage,
+    ) -> Result<Result<SignerWeights, RequestError>, InternalError> {
+        let (quorum, properties) = match self.get_stage_quorum(&metadata, &stage)? {
+            Ok(data) => data,
+            Err(error) => return Ok(Err(error)),
+        };
+        let signers = self.get_signers(metadata, stage)?;
+        let Ok(signers) = signers else {
+            return Ok(Err(signers.unwrap_err()));
+        };
+        match quorum {
+            Quorum::WEIGHTED { .. } => Ok(Ok(get_member_weights(&properties.0, signers)?)),
+            _ => Ok(Ok(signers.into_iter().map(|signer| (signer, 1)).collect())),
+        }
+    }
+
+    fn get_stage_quorum(
+        &self,
+        metadata: &Metadata,
+        stage: &ValidationStage,
+    ) -> Result<Result<(Quorum, ValueWrapper), RequestError>, InternalError> {
+        if ValidationStage::Witness == *stage {
             return Ok(Err(RequestError::SearchingSignersQuorumInWrongStage(
                 stage.to_str().to_owned(),
             )));
@@ -263,18 +310,7 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
             return Ok(Err(schema_policy.unwrap_err()));
         }; // El return dentro de otro return es una **** que obliga a hacer cosas como esta
         let quorum = get_quorum(&schema_policy, stage.to_str())?;
-        let signers = self.get_signers(metadata, stage)?;
-        let Ok(signers) = signers else {
-            return Ok(Err(signers.unwrap_err()));
-        };
-        match quorum {
-            Quorum::MAJORITY => Ok(Ok((signers.len() as u32 / 2) + 1)),
-            Quorum::FIXED { fixed } => Ok(Ok(fixed)),
-            Quorum::PERCENTAGE { percentage } => {
-                let result = (signers.len() as f64 * percentage).ceil() as u32;
-                Ok(Ok(result))
-            } // Quorum::BFT { .. } => todo!(),
-        }
+        Ok(Ok((quorum, governance.properties)))
     }
 
     // NEW
@@ -392,7 +428,7 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
         &self,
         governance_id: DigestIdentifier,
         governance_version: u64,
-    ) -> Result<Result<Vec<(Contract, String)>, RequestError>, InternalError> {
+    ) -> Result<Result<Vec<(Contract, String, u64)>, RequestError>, InternalError> {
         let governance = match self.governance_event_sourcing(&governance_id, governance_version) {
             Ok(subject) => subject,
             Err(error) => match error {
@@ -409,16 +445,49 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
             let mut contract: Contract = serde_json::from_value(schema["contract"].clone())
                 .map_err(|_| InternalError::InvalidGovernancePayload("5".into()))?;
 
-            let decoded_bytes =
-                base64::decode(contract.raw).map_err(|_| InternalError::Base64DecodingError)?;
-            contract.raw =
-                String::from_utf8(decoded_bytes).map_err(|_| InternalError::Base64DecodingError)?;
+            if contract.wasm.is_none() && contract.mode.is_none() {
+                let decoded_bytes =
+                    base64::decode(contract.raw).map_err(|_| InternalError::Base64DecodingError)?;
+                contract.raw = String::from_utf8(decoded_bytes)
+                    .map_err(|_| InternalError::Base64DecodingError)?;
+            }
 
-            result.push((contract, schema_id.to_owned()));
+            result.push((contract, schema_id.to_owned(), get_schema_version(schema)));
         }
         Ok(Ok(result))
     }
 
+    pub fn get_contract_hooks(
+        &self,
+        governance_id: DigestIdentifier,
+        schema_id: String,
+        schema_version: u64,
+        governance_version: u64,
+    ) -> Result<Result<Vec<ContractHook>, RequestError>, InternalError> {
+        if governance_id.digest.is_empty() || schema_id == "governance" {
+            return Ok(Ok(vec![]));
+        }
+        let governance = match self.governance_event_sourcing(&governance_id, governance_version) {
+            Ok(subject) => subject,
+            Err(error) => match error {
+                RequestError::DatabaseError(err) => {
+                    return Err(InternalError::DatabaseError { source: err })
+                }
+                err => return Ok(Err(err)),
+            },
+        };
+        let schemas = get_as_array(&governance.properties.0, "schemas")?;
+        if let Some(schema) = find_schema(schemas, &schema_id, Some(schema_version))? {
+            let contract: Contract = serde_json::from_value(schema["contract"].clone())
+                .map_err(|_| InternalError::InvalidGovernancePayload("5".into()))?;
+            if contract.mode.is_some() {
+                return Ok(Ok(vec![]));
+            }
+            return Ok(Ok(contract.hooks));
+        }
+        Ok(Err(RequestError::SchemaNotFound(schema_id)))
+    }
+
     // OLD BUT OK
     pub fn get_governance_version(
         &self,
@@ -494,6 +563,7 @@ impl<C: DatabaseCollection> InnerGovernance<C> {
             let mut gov_subject = Subject::from_genesis_event(
                 gov_genesis,
                 init_state,
+                0,
                 None,
                 governance_id.derivator.clone(),
             )?;
@@ -518,6 +588,35 @@ fn get_as_str<'a>(data: &'a Value, key: &str) -> Result<&'a str, InternalError>
         .ok_or(InternalError::InvalidGovernancePayload("7".into()))
 }
 
+fn get_schema_version(schema: &Value) -> u64 {
+    schema.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Looks up a schema entry by id. Without a version the latest one declared is returned.
+fn find_schema<'a>(
+    schemas: &'a Vec<Value>,
+    schema_id: &str,
+    schema_version: Option<u64>,
+) -> Result<Option<&'a Value>, InternalError> {
+    let mut found: Option<&'a Value> = None;
+    for schema in schemas {
+        if get_as_str(schema, "id")? != schema_id {
+            continue;
+        }
+        let version = get_schema_version(schema);
+        match schema_version {
+            Some(target) if target == version => return Ok(Some(schema)),
+            Some(_) => {}
+            None => {
+                if found.map_or(true, |current| get_schema_version(current) < version) {
+                    found = Some(schema);
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
 fn get_as_array<'a>(data: &'a Value, key: &str) -> Result<&'a Vec<Value>, InternalError> {
     data.get(key)
         .ok_or(InternalError::InvalidGovernancePayload("8".into()))?
@@ -583,12 +682,47 @@ fn get_members_from_governance(
     Ok(member_ids_names)
 }
 
+fn get_member_weights(
+    properties: &Value,
+    signers: HashSet<KeyIdentifier>,
+) -> Result<SignerWeights, InternalError> {
+    let mut weights = SignerWeights::new();
+    let members = get_as_array(properties, "members")?;
+    for member in members {
+        let Some(id) = member.get("id").and_then(|id| id.as_str()) else {
+            continue;
+        };
+        let Ok(id) = KeyIdentifier::from_str(id) else {
+            continue;
+        };
+        if signers.contains(&id) {
+            let weight = match member.get("weight") {
+                Some(weight) => weight
+                    .as_u64()
+                    .and_then(|weight| u32::try_from(weight).ok())
+                    .ok_or_else(|| {
+                        InternalError::InvalidGovernancePayload(
+                            "15: Invalid weight in member".into(),
+                        )
+                    })?,
+                None => 1,
+            };
+            weights.insert(id, weight);
+        }
+    }
+    // Firmantes que no son miembros, como el propietario de la gobernanza
+    for signer in signers {
+        weights.entry(signer).or_insert(1);
+    }
+    Ok(weights)
+}
+
 #[allow(dead_code)]
 fn contains_common_element(set1: &HashSet<String>, vec2: &[String]) -> bool {
     vec2.iter().any(|s| set1.contains(s))
 }
 
-fn namespace_contiene(namespace_padre: &str, namespace_hijo: &str) -> bool {
+pub(crate) fn namespace_contiene(namespace_padre: &str, namespace_hijo: &str) -> bool {
     // Si el namespace padre es vacío, contiene a todos
     if namespace_padre.is_empty() {
         return true;
@@ -612,3 +746,131 @@ fn namespace_contiene(namespace_padre: &str, namespace_hijo: &str) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod test {
+    use std::{str::FromStr, sync::Arc};
+
+    use serde_json::json;
+
+    use super::InnerGovernance;
+    use crate::commons::models::state::Subject;
+    use crate::database::{MemoryCollection, DB};
+    use crate::governance::{stage::ValidationStage, total_weight};
+    use crate::identifier::{Derivable, DigestIdentifier};
+    use crate::utils::test_utils::key;
+    use crate::{MemoryManager, Metadata, ValueWrapper};
+
+    const GOVERNANCE_ID: &str = "JGSPR6FL-vE7iZxWMd17o09qn7NeTqlcImDVWmijXczw";
+
+    fn build_governance(weights: &[u32], quorum: f64) -> InnerGovernance<MemoryCollection> {
+        let members: Vec<_> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| {
+                json!({
+                    "id": key(i as u8 + 1).to_str(),
+                    "name": format!("Member{}", i),
+                    "weight": weight
+                })
+            })
+            .collect();
+        let properties = json!({
+            "members": members,
+            "roles": [{
+                "namespace": "",
+                "role": "APPROVER",
+                "schema": { "ID": "Test" },
+                "who": "MEMBERS"
+            }],
+            "schemas": [],
+            "policies": [{
+                "id": "Test",
+                "approve": { "quorum": { "WEIGHTED": quorum } },
+                "evaluate": { "quorum": "MAJORITY" },
+                "validate": { "quorum": "MAJORITY" }
+            }]
+        });
+        let database = DB::new(Arc::new(MemoryManager::new()));
+        let governance_id = DigestIdentifier::from_str(GOVERNANCE_ID).unwrap();
+        database
+            .set_subject(
+                &governance_id,
+                Subject {
+                    keys: None,
+                    subject_id: governance_id.clone(),
+                    governance_id: DigestIdentifier::from_str("").unwrap(),
+                    sn: 0,
+                    genesis_gov_version: 0,
+                    public_key: key(0),
+                    namespace: "".into(),
+                    name: "".into(),
+                    schema_id: "governance".into(),
+                    schema_version: 0,
+                    owner: key(0),
+                    creator: key(0),
+                    properties: ValueWrapper(properties),
+                    active: true,
+                },
+            )
+            .unwrap();
+        let (update_channel, _) = tokio::sync::broadcast::channel(10);
+        InnerGovernance::new(database, json!({}), update_channel)
+    }
+
+    fn metadata() -> Metadata {
+        Metadata {
+            namespace: "".into(),
+            subject_id: DigestIdentifier::from_str("JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs")
+                .unwrap(),
+            governance_id: DigestIdentifier::from_str(GOVERNANCE_ID).unwrap(),
+            governance_version: 0,
+            schema_id: "Test".into(),
+        }
+    }
+
+    #[test]
+    fn weighted_quorum_uses_member_weights() {
+        let governance = build_governance(&[3, 1, 1], 0.5);
+        let quorum = governance
+            .get_quorum(metadata(), ValidationStage::Approve)
+            .unwrap()
+            .unwrap();
+        assert_eq!(quorum, 3);
+        let weights = governance
+            .get_signer_weights(metadata(), ValidationStage::Approve)
+            .unwrap()
+            .unwrap();
+        assert_eq!(weights.get(&key(1)), Some(&3));
+        assert_eq!(weights.get(&key(2)), Some(&1));
+        // Negative quorum: the votes against that make the quorum unreachable
+        assert_eq!(total_weight(&weights) - quorum + 1, 3);
+        // The other stages count every signer as one
+        let weights = governance
+            .get_signer_weights(metadata(), ValidationStage::Evaluate)
+            .unwrap()
+            .unwrap();
+        assert_eq!(weights.len(), 1);
+        assert_eq!(weights.get(&key(0)), Some(&1));
+    }
+
+    #[test]
+    fn weighted_quorum_needs_at_least_one_vote() {
+        let governance = build_governance(&[1], 0.0001);
+        let quorum = governance
+            .get_quorum(metadata(), ValidationStage::Approve)
+            .unwrap()
+            .unwrap();
+        assert_eq!(quorum, 1);
+    }
+
+    #[test]
+    fn weighted_quorum_saturates_summed_weights() {
+        let governance = build_governance(&[u32::MAX, u32::MAX], 1.0);
+        let quorum = governance
+            .get_quorum(metadata(), ValidationStage::Approve)
+            .unwrap()
+            .unwrap();
+        assert_eq!(quorum, u32::MAX);
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     commons::{
         identifier::{DigestIdentifier, KeyIdentifier},
         models::event::Metadata,
-        schema_handler::gov_models::Contract,
+        schema_handler::gov_models::{Contract, ContractHook},
     },
     ValueWrapper,
 };
@@ -19,6 +19,26 @@ use error::RequestError;
 
 use self::stage::ValidationStage;
 
+/// Weight of the votes of every signer of a stage
+pub type SignerWeights = HashMap<KeyIdentifier, u32>;
+
+/// Sums the weights of the voters that are signers of the stage. The sum saturates at
+/// `u32::MAX` instead of overflowing.
+pub fn votes_weight<'a>(
+    weights: &SignerWeights,
+    voters: impl IntoIterator<Item = &'a KeyIdentifier>,
+) -> u32 {
+    voters
+        .into_iter()
+        .filter_map(|voter| weights.get(voter))
+        .fold(0, |total, weight| total.saturating_add(*weight))
+}
+
+/// Sums the weights of all the signers of the stage
+pub fn total_weight(weights: &SignerWeights) -> u32 {
+    votes_weight(weights, weights.keys())
+}
+
 #[derive(Debug, Clone)]
 pub struct GovernanceMember {
     pub id: String,
@@ -44,6 +64,7 @@ pub enum GovernanceMessage {
     GetSchema {
         governance_id: DigestIdentifier,
         schema_id: String,
+        schema_version: u64,
         governance_version: u64,
     },
     GetSigners {
@@ -54,6 +75,10 @@ pub enum GovernanceMessage {
         metadata: Metadata,
         stage: ValidationStage,
     },
+    GetSignerWeights {
+        metadata: Metadata,
+        stage: ValidationStage,
+    },
     GetInvokeInfo {
         metadata: Metadata,
         stage: ValidationStage,
@@ -63,6 +88,12 @@ pub enum GovernanceMessage {
         governance_id: DigestIdentifier,
         governance_version: u64,
     },
+    GetContractHooks {
+        governance_id: DigestIdentifier,
+        schema_id: String,
+        schema_version: u64,
+        governance_version: u64,
+    },
     GetGovernanceVersion {
         governance_id: DigestIdentifier,
         subject_id: DigestIdentifier,
@@ -78,12 +109,14 @@ pub enum GovernanceMessage {
 
 #[derive(Debug, Clone)]
 pub enum GovernanceResponse {
-    GetInitState(Result<ValueWrapper, RequestError>),
+    GetInitState(Result<(ValueWrapper, u64), RequestError>),
     GetSchema(Result<ValueWrapper, RequestError>),
     GetSigners(Result<HashSet<KeyIdentifier>, RequestError>),
     GetQuorum(Result<u32, RequestError>),
+    GetSignerWeights(Result<SignerWeights, RequestError>),
     GetInvokeInfo(Result<bool, RequestError>),
-    GetContracts(Result<Vec<(Contract, String)>, RequestError>),
+    GetContracts(Result<Vec<(Contract, String, u64)>, RequestError>),
+    GetContractHooks(Result<Vec<ContractHook>, RequestError>),
     GetGovernanceVersion(Result<u64, RequestError>),
     IsGovernance(Result<bool, RequestError>),
     NoResponse,
@@ -96,3 +129,24 @@ pub enum GovernanceUpdatedMessage {
         governance_version: u64,
     },
 }
+
+#[cfg(test)]
+mod test {
+    use super::{total_weight, votes_weight, SignerWeights};
+    use crate::utils::test_utils::key;
+
+    #[test]
+    fn votes_weight_sums_only_signers() {
+        let weights = SignerWeights::from([(key(1), 3), (key(2), 1)]);
+        assert_eq!(votes_weight(&weights, &[key(1), key(2)]), 4);
+        assert_eq!(votes_weight(&weights, &[key(2)]), 1);
+        assert_eq!(votes_weight(&weights, &[key(3)]), 0);
+        assert_eq!(total_weight(&weights), 4);
+    }
+
+    #[test]
+    fn votes_weight_saturates() {
+        let weights = SignerWeights::from([(key(1), u32::MAX), (key(2), 2)]);
+        assert_eq!(total_weight(&weights), u32::MAX);
+    }
+}
@@ -62,6 +62,10 @@ pub enum LedgerError {
     EvaluationInTransferEvent,
     #[error("Approval found in transfer event")]
     ApprovalInTransferEvent,
+    #[error("Transfer or EOL event vetoed by the contract")]
+    RejectedByContractHook,
+    #[error("Invalid state of other subject read by the contract: {0}")]
+    InvalidSubjectDependency(String),
     #[error("State event with an SN of 0 detected")]
     StateEventWithZeroSNDetected,
     #[error("Unexpected create event")]
@@ -2,28 +2,40 @@ use crate::commons::crypto::KeyGenerator;
 use crate::commons::models::approval::ApprovalState;
 use crate::commons::models::state::generate_subject_id;
 use crate::crypto::Secp256k1KeyPair;
-use crate::request::{RequestState, TapleRequest};
+use crate::request::{RequestStage, RequestState, TapleRequest};
 use crate::signature::Signed;
 use crate::{
     commons::{
         channel::SenderEnd,
-        models::{evaluation::SubjectContext, state::Subject, validation::ValidationProof},
+        models::{
+            evaluation::{SubjectContext, SubjectDependency},
+            state::Subject,
+            validation::ValidationProof,
+        },
     },
     crypto::{Ed25519KeyPair, KeyMaterial, KeyPair},
     database::{Error as DbError, DB},
     distribution::{error::DistributionErrorResponses, DistributionMessagesNew},
-    governance::{stage::ValidationStage, GovernanceAPI, GovernanceInterface},
+    evaluator::dependencies::check_dependencies,
+    governance::{
+        stage::ValidationStage, total_weight, votes_weight, GovernanceAPI, GovernanceInterface,
+        SignerWeights,
+    },
     identifier::{Derivable, DigestIdentifier, KeyIdentifier},
     message::{MessageConfig, MessageTaskCommand},
     protocol::protocol_message_manager::TapleMessages,
-    request::EventRequest,
+    request::{EventRequest, FactRequest, MigrateRequest},
     signature::Signature,
-    utils::message::ledger::{request_event, request_gov_event},
-    DatabaseCollection,
+    utils::{
+        message::ledger::{lce_task_id, request_event, request_gov_event},
+        patch::apply_patch,
+    },
+    DatabaseCollection, WriteBatch,
 };
 use crate::{
     ApprovalResponse, DigestDerivator, Event, KeyDerivator, Metadata, Notification, ValueWrapper,
 };
+use serde_json::json;
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use super::errors::LedgerError;
@@ -162,19 +174,28 @@ impl<C: DatabaseCollection> Ledger<C> {
 
     fn set_finished_request(
         &self,
+        batch: &mut WriteBatch,
         request_id: &DigestIdentifier,
         event_request: Signed<EventRequest>,
         sn: u64,
         subject_id: DigestIdentifier,
         success: bool,
     ) -> Result<(), LedgerError> {
-        let mut taple_request: TapleRequest = event_request.clone().try_into()?;
+        // The requests of this node keep the stages they have gone through
+        let mut taple_request: TapleRequest = match self.database.get_taple_request(request_id) {
+            Ok(taple_request) => taple_request,
+            Err(DbError::EntryNotFound) => event_request.clone().try_into()?,
+            Err(error) => return Err(LedgerError::DatabaseError(error)),
+        };
         taple_request.sn = Some(sn);
         taple_request.subject_id = Some(subject_id.clone());
         taple_request.state = RequestState::Finished;
         taple_request.success = Some(success);
+        if taple_request.current_stage() != Some(&RequestStage::Distribution) {
+            taple_request.start_stage(RequestStage::Distribution);
+        }
         self.database
-            .set_taple_request(&request_id, &taple_request)?;
+            .set_taple_request_batch(batch, &request_id, &taple_request)?;
         Ok(())
     }
 
@@ -202,7 +223,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                 )
                 .await?
         };
-        let init_state = self
+        let (init_state, schema_version) = self
             .gov_api
             .get_init_state(
                 create_request.governance_id,
@@ -218,10 +239,12 @@ impl<C: DatabaseCollection> Ledger<C> {
                 ))
             }
         };
+        let init_state = genesis_state(&event, init_state)?;
         // Create subject from genesis and event
         let subject = Subject::from_genesis_event(
             event.clone(),
             init_state,
+            schema_version,
             Some(subject_keys),
             self.derivator,
         )
@@ -239,17 +262,29 @@ impl<C: DatabaseCollection> Ledger<C> {
             self.subject_is_gov.insert(subject_id.clone(), false);
         }
         let ev_request = event.content.event_request.clone();
+        let mut batch = WriteBatch::new();
+        self.database
+            .set_governance_index_batch(&mut batch, &subject_id, &subject.governance_id)?;
         self.database
-            .set_governance_index(&subject_id, &subject.governance_id)?;
-        self.database.set_subject(&subject_id, subject)?;
-        self.database.set_signatures(
+            .set_subject_batch(&mut batch, &subject_id, subject)?;
+        self.database.set_signatures_batch(
+            &mut batch,
             &subject_id,
             event.content.sn,
             signatures,
             validation_proof, // Current Owner
         )?;
-        self.database.set_event(&subject_id, event)?;
-        self.set_finished_request(&request_id, ev_request, sn, subject_id.clone(), true)?;
+        self.database
+            .set_event_batch(&mut batch, &subject_id, event)?;
+        self.set_finished_request(
+            &mut batch,
+            &request_id,
+            ev_request,
+            sn,
+            subject_id.clone(),
+            true,
+        )?;
+        self.database.write_batch(batch)?;
         let _ = self
             .notification_sender
             .send(Notification::NewEvent {
@@ -311,99 +346,33 @@ impl<C: DatabaseCollection> Ledger<C> {
             .map_err(|_| LedgerError::CryptoError("Error generating request hash".to_owned()))?;
         let sn = event.content.sn;
         let subject_id = match &event_request.content {
-            EventRequest::Fact(state_request) => {
-                let subject_id = state_request.subject_id.clone();
-                // Apply event sourcing
-                let mut subject =
-                    self.database
-                        .get_subject(&subject_id)
-                        .map_err(|error| match error {
-                            crate::DbError::EntryNotFound => {
-                                LedgerError::SubjectNotFound(subject_id.to_str())
-                            }
-                            _ => LedgerError::DatabaseError(error),
-                        })?;
-                self.database.set_signatures(
-                    &subject_id,
-                    event.content.sn,
-                    signatures,
-                    validation_proof, // Current Owner
-                )?;
-                let json_patch = event.content.patch.clone();
+            EventRequest::Fact(state_request) => state_request.subject_id.clone(),
+            EventRequest::Create(_) => return Err(LedgerError::StateInGenesis),
+            EventRequest::Transfer(transfer_request) => transfer_request.subject_id.clone(),
+            EventRequest::EOL(eol_request) => eol_request.subject_id.clone(),
+            EventRequest::Migrate(migrate_request) => migrate_request.subject_id.clone(),
+        };
+        // Apply event sourcing
+        let mut subject = self
+            .database
+            .get_subject(&subject_id)
+            .map_err(|error| match error {
+                crate::DbError::EntryNotFound => LedgerError::SubjectNotFound(subject_id.to_str()),
+                _ => LedgerError::DatabaseError(error),
+            })?;
+        match &event_request.content {
+            EventRequest::Fact(_) | EventRequest::Migrate(_) => {
                 if subject.sn != event.content.sn - 1 {
                     return Err(LedgerError::WrongSnInSubject(subject_id.to_str()));
                 }
                 if event.content.approved {
-                    subject.update_subject(json_patch, event.content.sn)?;
+                    subject.update_subject(event.content.patch.clone(), event.content.sn)?;
                 } else {
                     subject.sn = event.content.sn;
                 }
-                let _ = self
-                    .notification_sender
-                    .send(Notification::StateUpdated {
-                        sn: event.content.sn,
-                        subject_id: subject.subject_id.to_str(),
-                    })
-                    .await
-                    .map_err(|_| LedgerError::NotificationChannelError);
-                self.database.set_event(&subject_id, event.clone())?;
-                self.set_finished_request(
-                    &request_id,
-                    event_request.clone(),
-                    sn,
-                    subject_id.clone(),
-                    true,
-                )?;
-                let _ = self
-                    .notification_sender
-                    .send(Notification::NewEvent {
-                        sn,
-                        subject_id: subject_id.to_str(),
-                    })
-                    .await
-                    .map_err(|_| LedgerError::NotificationChannelError);
-                self.database.set_subject(&subject_id, subject)?;
-                // Check is_gov
-                let is_gov = self.subject_is_gov.get(&subject_id);
-                match is_gov {
-                    Some(true) => {
-                        // Send message to gov of governance updated with id and sn
-                        self.gov_api
-                            .governance_updated(subject_id.clone(), sn)
-                            .await?;
-                    }
-                    Some(false) => {
-                        self.database.del_signatures(&subject_id, sn - 1)?;
-                    }
-                    None => {
-                        // If not on the map, add it and send message to gov from subject updated with id and sn
-                        if self.gov_api.is_governance(subject_id.clone()).await? {
-                            self.subject_is_gov.insert(subject_id.clone(), true);
-                            // Send message to gov of governance updated with id and sn
-                            self.gov_api
-                                .governance_updated(subject_id.clone(), sn)
-                                .await?;
-                        } else {
-                            self.subject_is_gov.insert(subject_id.clone(), false);
-                            self.database.del_signatures(&subject_id, sn - 1)?;
-                        }
-                    }
-                }
-                state_request.subject_id.clone()
+                subject.upgrade_schema(&event.content);
             }
-            EventRequest::Create(_) => return Err(LedgerError::StateInGenesis),
             EventRequest::Transfer(transfer_request) => {
-                let subject_id = transfer_request.subject_id.clone();
-                // Apply event sourcing
-                let mut subject =
-                    self.database
-                        .get_subject(&subject_id)
-                        .map_err(|error| match error {
-                            crate::DbError::EntryNotFound => {
-                                LedgerError::SubjectNotFound(subject_id.to_str())
-                            }
-                            _ => LedgerError::DatabaseError(error),
-                        })?;
                 // Change subject's public key and remove cryptographic material
                 subject.public_key = transfer_request.public_key.clone();
                 subject.owner = event.content.event_request.signature.signer.clone();
@@ -413,121 +382,74 @@ impl<C: DatabaseCollection> Ledger<C> {
                 } else {
                     subject.keys = None;
                 }
-                self.database.set_signatures(
-                    &subject_id,
-                    event.content.sn,
-                    signatures,
-                    validation_proof,
-                )?;
-                self.database.set_event(&subject_id, event.clone())?;
-                self.set_finished_request(
-                    &request_id,
-                    event_request.clone(),
-                    sn,
-                    subject_id.clone(),
-                    true,
-                )?;
-                let _ = self
-                    .notification_sender
-                    .send(Notification::NewEvent {
-                        sn,
-                        subject_id: subject_id.to_str(),
-                    })
-                    .await
-                    .map_err(|_| LedgerError::NotificationChannelError);
-                subject.sn = event.content.sn;
-                self.database.set_subject(&subject_id, subject)?;
-                let is_gov = self.subject_is_gov.get(&subject_id);
-                match is_gov {
-                    Some(true) => {}
-                    Some(false) => {
-                        self.database.del_signatures(&subject_id, sn - 1)?;
-                    }
-                    None => {
-                        // If not on the map, add it and send message to gov from subject updated with id and sn
-                        if self.gov_api.is_governance(subject_id.clone()).await? {
-                            self.subject_is_gov.insert(subject_id.clone(), true);
-                        } else {
-                            self.subject_is_gov.insert(subject_id.clone(), false);
-                            self.database.del_signatures(&subject_id, sn - 1)?;
-                        }
-                    }
-                }
-                transfer_request.subject_id.clone()
+                // The patch is empty unless the contract evaluates transfers
+                subject.update_subject(event.content.patch.clone(), event.content.sn)?;
             }
-            EventRequest::EOL(eol_request) => {
-                let subject_id = eol_request.subject_id.clone();
-                // Apply event sourcing
-                let mut subject =
-                    self.database
-                        .get_subject(&subject_id)
-                        .map_err(|error| match error {
-                            crate::DbError::EntryNotFound => {
-                                LedgerError::SubjectNotFound(subject_id.to_str())
-                            }
-                            _ => LedgerError::DatabaseError(error),
-                        })?;
-                self.database.set_signatures(
-                    &subject_id,
-                    event.content.sn,
-                    signatures,
-                    validation_proof,
-                )?;
-                self.database.set_event(&subject_id, event.clone())?;
-                self.set_finished_request(
-                    &request_id,
-                    event_request.clone(),
-                    sn,
-                    subject_id.clone(),
-                    true,
-                )?;
-                let _ = self
-                    .notification_sender
-                    .send(Notification::NewEvent {
-                        sn,
-                        subject_id: subject_id.to_str(),
-                    })
-                    .await
-                    .map_err(|_| LedgerError::NotificationChannelError);
-                subject.sn = sn;
+            EventRequest::EOL(_) => {
+                subject.update_subject(event.content.patch.clone(), sn)?;
                 subject.eol_event();
-                self.database.set_subject(&subject_id, subject)?;
-                // Check is_gov
-                let is_gov = self.subject_is_gov.get(&subject_id);
-                match is_gov {
-                    Some(true) => {
-                        // Send message to gov of governance updated with id and sn
-                        self.gov_api
-                            .governance_updated(subject_id.clone(), sn)
-                            .await?;
-                    }
-                    Some(false) => {
-                        self.database.del_signatures(&subject_id, sn - 1)?;
-                    }
-                    None => {
-                        // If not on the map, add it and send message to gov from subject updated with id and sn
-                        if self.gov_api.is_governance(subject_id.clone()).await? {
-                            self.subject_is_gov.insert(subject_id.clone(), true);
-                            // Send message to gov of governance updated with id and sn
-                            self.gov_api
-                                .governance_updated(subject_id.clone(), sn)
-                                .await?;
-                        } else {
-                            self.subject_is_gov.insert(subject_id.clone(), false);
-                            self.database.del_signatures(&subject_id, sn - 1)?;
-                        }
-                    }
-                }
-                subject_id
+            }
+            EventRequest::Create(_) => unreachable!(),
+        }
+        // Check is_gov. If not on the map, add it
+        let is_gov = match self.subject_is_gov.get(&subject_id) {
+            Some(is_gov) => *is_gov,
+            None => {
+                let is_gov = self.gov_api.is_governance(subject_id.clone()).await?;
+                self.subject_is_gov.insert(subject_id.clone(), is_gov);
+                is_gov
             }
         };
+        // The whole state transition is stored as a single batch
+        let mut batch = WriteBatch::new();
+        self.database.set_signatures_batch(
+            &mut batch,
+            &subject_id,
+            sn,
+            signatures,
+            validation_proof, // Current Owner
+        )?;
+        self.database
+            .set_event_batch(&mut batch, &subject_id, event.clone())?;
         self.set_finished_request(
+            &mut batch,
             &request_id,
             event_request.clone(),
-            event.content.sn,
+            sn,
             subject_id.clone(),
             event.content.eval_success && event.content.approved,
         )?;
+        self.database
+            .set_subject_batch(&mut batch, &subject_id, subject)?;
+        if !is_gov {
+            self.database
+                .del_signatures_batch(&mut batch, &subject_id, sn - 1)?;
+        }
+        self.database.write_batch(batch)?;
+        if let EventRequest::Fact(_) | EventRequest::Migrate(_) = &event_request.content {
+            let _ = self
+                .notification_sender
+                .send(Notification::StateUpdated {
+                    sn,
+                    subject_id: subject_id.to_str(),
+                })
+                .await
+                .map_err(|_| LedgerError::NotificationChannelError);
+        }
+        let _ = self
+            .notification_sender
+            .send(Notification::NewEvent {
+                sn,
+                subject_id: subject_id.to_str(),
+            })
+            .await
+            .map_err(|_| LedgerError::NotificationChannelError);
+        if is_gov && !matches!(&event_request.content, EventRequest::Transfer(_)) {
+            // Send message to gov of governance updated with id and sn
+            self.gov_api
+                .governance_updated(subject_id.clone(), sn)
+                .await?;
+        }
         // Upgrade Ledger State
         match self.ledger_state.entry(subject_id.clone()) {
             Entry::Occupied(mut ledger_state) => {
@@ -556,6 +478,12 @@ impl<C: DatabaseCollection> Ledger<C> {
         sender: KeyIdentifier,
         validation_proof: ValidationProof,
     ) -> Result<(), LedgerError> {
+        // The last event of the subject may have been requested, and it has been answered
+        self.message_channel
+            .tell(MessageTaskCommand::Cancel(lce_task_id(
+                &event.content.subject_id,
+            )))
+            .await?;
         // Check that no request with the same hash exists
         let event_request = event.content.event_request.clone();
         let request_id = DigestIdentifier::generate_with_blake3(&event_request)
@@ -624,6 +552,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                             MessageConfig {
                                                 timeout: 2000,
                                                 replication_factor: 1.0,
+                                                retry: None,
                                             },
                                         ))
                                         .await?;
@@ -661,6 +590,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                             MessageConfig {
                                                 timeout: 2000,
                                                 replication_factor: 1.0,
+                                                retry: None,
                                             },
                                         ))
                                         .await?;
@@ -692,7 +622,6 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 },
                             }
                         }
-                        self.check_transfer_event(event.clone())?;
                         // If not on the map, add it and send message to gov from subject updated with id and sn
                         let subject_id = transfer_request.subject_id.clone();
                         let (signers, quorum) = self
@@ -754,7 +683,16 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 &subject,
                                 &event_hash,
                                 &transfer_request.public_key,
+                                &event.content.dependencies,
                             )?;
+                            self.check_event(
+                                event.clone(),
+                                metadata.clone(),
+                                subject.get_subject_context(
+                                    event.content.event_request.signature.signer.clone(),
+                                ),
+                            )
+                            .await?;
                             let sn: u64 = event.content.sn;
                             // We check if we are waiting for the transfer and if it is to us.
                             let (keypair, to_delete) =
@@ -779,27 +717,47 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 keypair,
                                 event.content.sn,
                             );
-                            self.database.set_signatures(
+                            subject.update_subject(event.content.patch.clone(), sn)?;
+                            let is_gov = self.subject_is_gov.get(&subject_id).unwrap().to_owned();
+                            let mut batch = WriteBatch::new();
+                            self.database.set_signatures_batch(
+                                &mut batch,
                                 &transfer_request.subject_id,
                                 sn,
                                 signatures,
                                 validation_proof.clone(),
                             )?;
                             self.set_finished_request(
+                                &mut batch,
                                 &request_id,
                                 event_request.clone(),
                                 event.content.sn,
                                 subject_id.clone(),
                                 true,
                             )?;
-                            self.database
-                                .set_event(&transfer_request.subject_id, event)?;
-                            self.database
-                                .set_subject(&transfer_request.subject_id, subject)?;
+                            self.database.set_event_batch(
+                                &mut batch,
+                                &transfer_request.subject_id,
+                                event,
+                            )?;
+                            self.database.set_subject_batch(
+                                &mut batch,
+                                &transfer_request.subject_id,
+                                subject,
+                            )?;
                             if to_delete {
-                                self.database.del_keys(&transfer_request.public_key)?;
+                                self.database
+                                    .del_keys_batch(&mut batch, &transfer_request.public_key)?;
                             }
-                            if self.subject_is_gov.get(&subject_id).unwrap().to_owned() {
+                            if !is_gov {
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &transfer_request.subject_id,
+                                    sn - 1,
+                                )?;
+                            }
+                            self.database.write_batch(batch)?;
+                            if is_gov {
                                 // Send message to gov of governance updated with id and sn
                                 let msg = request_gov_event(
                                     self.our_id.clone(),
@@ -814,15 +772,13 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
                                 self.gov_api
                                     .governance_updated(subject_id.clone(), sn)
                                     .await?;
-                            } else {
-                                self.database
-                                    .del_signatures(&transfer_request.subject_id, sn - 1)?;
                             }
                             self.ledger_state.insert(
                                 transfer_request.subject_id.clone(),
@@ -859,6 +815,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
@@ -866,6 +823,9 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     transfer_request.subject_id.to_str(),
                                 ));
                             }
+                            // The state the evaluation started from is not known yet
+                            self.check_transfer_or_eol_event(&event, &metadata, None)
+                                .await?;
                             // Check which LCE is larger and keep the smaller one if we have another one.
                             let last_lce = match ledger_state.head {
                                 Some(head) => {
@@ -881,36 +841,53 @@ impl<C: DatabaseCollection> Ledger<C> {
                             };
                             // If we have arrived here it is because it is going to be a new LCE
                             let sn = event.content.sn;
-                            self.database.set_signatures(
+                            let mut batch = WriteBatch::new();
+                            self.database.set_signatures_batch(
+                                &mut batch,
                                 &transfer_request.subject_id,
                                 sn,
                                 signatures,
                                 validation_proof.clone(),
                             )?;
                             self.set_finished_request(
+                                &mut batch,
                                 &request_id,
                                 event_request.clone(),
                                 event.content.sn,
                                 subject_id.clone(),
                                 event.content.eval_success && event.content.approved,
                             )?;
-                            self.database
-                                .set_event(&transfer_request.subject_id, event)?;
-                            self.database.set_lce_validation_proof(
+                            self.database.set_event_batch(
+                                &mut batch,
+                                &transfer_request.subject_id,
+                                event,
+                            )?;
+                            self.database.set_lce_validation_proof_batch(
+                                &mut batch,
                                 &transfer_request.subject_id,
                                 validation_proof,
                             )?;
                             if last_lce.is_some() {
                                 let last_lce_sn = last_lce.unwrap();
-                                self.database
-                                    .del_signatures(&transfer_request.subject_id, last_lce_sn)?;
-                                self.database
-                                    .del_event(&transfer_request.subject_id, last_lce_sn)?;
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &transfer_request.subject_id,
+                                    last_lce_sn,
+                                )?;
+                                self.database.del_event_batch(
+                                    &mut batch,
+                                    &transfer_request.subject_id,
+                                    last_lce_sn,
+                                )?;
                             } else {
                                 // Delete signatures of last validated event
-                                self.database
-                                    .del_signatures(&transfer_request.subject_id, subject.sn)?;
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &transfer_request.subject_id,
+                                    subject.sn,
+                                )?;
                             }
+                            self.database.write_batch(batch)?;
                             self.ledger_state.insert(
                                 transfer_request.subject_id.clone(),
                                 LedgerState {
@@ -930,6 +907,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     MessageConfig {
                                         timeout: 2000,
                                         replication_factor: 0.8,
+                                        retry: None,
                                     },
                                 ))
                                 .await?;
@@ -955,6 +933,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     MessageConfig {
                                         timeout: 2000,
                                         replication_factor: 1.0,
+                                        retry: None,
                                     },
                                 ))
                                 .await?;
@@ -983,7 +962,8 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 },
                             }
                         }
-                        self.check_transfer_event(event.clone())?;
+                        self.check_transfer_or_eol_event(&event, &metadata, None)
+                            .await?;
                         // self.check_event(event.clone(), metadata.clone()).await?;
                         // If not on the map, add it and send message to gov from subject updated with id and sn
                         // let notary_hash = DigestIdentifier::from_serializable_borsh(
@@ -999,26 +979,31 @@ impl<C: DatabaseCollection> Ledger<C> {
                             .await?;
                         verify_signatures(&signatures, &signers, quorum, &validation_proof)?;
                         let sn = event.content.sn;
-                        self.database.set_signatures(
+                        let mut batch = WriteBatch::new();
+                        self.database.set_signatures_batch(
+                            &mut batch,
                             &transfer_request.subject_id,
                             sn,
                             signatures,
                             validation_proof.clone(),
                         )?;
-                        self.database.set_lce_validation_proof(
+                        self.database.set_lce_validation_proof_batch(
+                            &mut batch,
                             &transfer_request.subject_id,
                             validation_proof,
                         )?;
                         let success = event.content.eval_success && event.content.approved;
                         self.database
-                            .set_event(&transfer_request.subject_id, event)?;
+                            .set_event_batch(&mut batch, &transfer_request.subject_id, event)?;
                         self.set_finished_request(
+                            &mut batch,
                             &request_id,
                             event_request.clone(),
                             sn,
                             subject_id.clone(),
                             success,
                         )?;
+                        self.database.write_batch(batch)?;
                         self.ledger_state.insert(
                             transfer_request.subject_id.clone(),
                             LedgerState {
@@ -1037,6 +1022,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 MessageConfig {
                                     timeout: 2000,
                                     replication_factor: 1.0,
+                                    retry: None,
                                 },
                             ))
                             .await?;
@@ -1162,13 +1148,17 @@ impl<C: DatabaseCollection> Ledger<C> {
                     .tell(DistributionMessagesNew::SignaturesNeeded { subject_id, sn: 0 })
                     .await?;
             }
-            EventRequest::Fact(state_request) => {
-                let is_gov = self
-                    .subject_is_gov
-                    .get(&state_request.subject_id)
-                    .unwrap_or(&false);
+            EventRequest::Fact(FactRequest {
+                subject_id: fact_subject_id,
+                ..
+            })
+            | EventRequest::Migrate(MigrateRequest {
+                subject_id: fact_subject_id,
+                ..
+            }) => {
+                let is_gov = self.subject_is_gov.get(&fact_subject_id).unwrap_or(&false);
                 // Cryptographic checks
-                let ledger_state = self.ledger_state.get(&state_request.subject_id);
+                let ledger_state = self.ledger_state.get(&fact_subject_id);
                 let metadata = validation_proof.get_metadata();
                 let sn = event.content.sn;
                 match ledger_state {
@@ -1185,13 +1175,11 @@ impl<C: DatabaseCollection> Ledger<C> {
                             }
                         }
                         // We must check if the subject is governance
-                        let mut subject = match self.database.get_subject(&state_request.subject_id)
-                        {
+                        let mut subject = match self.database.get_subject(&fact_subject_id) {
                             Ok(subject) => subject,
                             Err(crate::DbError::EntryNotFound) => {
                                 // ORDER GENESIS
-                                let msg =
-                                    request_event(self.our_id.clone(), state_request.subject_id, 0);
+                                let msg = request_event(self.our_id.clone(), fact_subject_id, 0);
                                 self.message_channel
                                     .tell(MessageTaskCommand::Request(
                                         None,
@@ -1200,6 +1188,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
@@ -1238,12 +1227,11 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
-                                return Err(LedgerError::GovernanceLCE(
-                                    state_request.subject_id.to_str(),
-                                ));
+                                return Err(LedgerError::GovernanceLCE(fact_subject_id.to_str()));
                             }
                         }
                         // Check that invoker has invocation permissions
@@ -1275,7 +1263,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 Err(error) => match error {
                                     crate::DbError::EntryNotFound => {
                                         return Err(LedgerError::WeAreNotWitnesses(
-                                            state_request.subject_id.to_str(),
+                                            fact_subject_id.to_str(),
                                         ));
                                     }
                                     _ => {
@@ -1293,7 +1281,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                         )
                         .await?;
                         // If not on the map, add it and send message to gov from subject updated with id and sn
-                        let subject_id = state_request.subject_id.clone();
+                        let subject_id = fact_subject_id.clone();
                         let (signers, quorum) = self
                             .get_signers_and_quorum(metadata.clone(), ValidationStage::Validate)
                             .await?;
@@ -1322,6 +1310,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 &subject,
                                 &event_hash,
                                 &subject.public_key,
+                                &event.content.dependencies,
                             )?;
                             let sn: u64 = event.content.sn;
                             let json_patch = event.content.patch.clone();
@@ -1330,6 +1319,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                             } else {
                                 subject.sn = event.content.sn;
                             }
+                            subject.upgrade_schema(&event.content);
                             let _ = self
                                 .notification_sender
                                 .send(Notification::StateUpdated {
@@ -1338,20 +1328,38 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 })
                                 .await
                                 .map_err(|_| LedgerError::NotificationChannelError);
-                            self.database.set_signatures(
-                                &state_request.subject_id,
+                            let is_gov = self.subject_is_gov.get(&subject_id).unwrap().to_owned();
+                            let mut batch = WriteBatch::new();
+                            self.database.set_signatures_batch(
+                                &mut batch,
+                                &fact_subject_id,
                                 sn,
                                 signatures,
                                 validation_proof,
                             )?;
                             self.set_finished_request(
+                                &mut batch,
                                 &request_id,
                                 event_request.clone(),
                                 event.content.sn,
                                 subject_id.clone(),
                                 event.content.eval_success && event.content.approved,
                             )?;
-                            self.database.set_event(&state_request.subject_id, event)?;
+                            self.database
+                                .set_event_batch(&mut batch, &fact_subject_id, event)?;
+                            self.database.set_subject_batch(
+                                &mut batch,
+                                &fact_subject_id,
+                                subject,
+                            )?;
+                            if !is_gov {
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &fact_subject_id,
+                                    sn - 1,
+                                )?;
+                            }
+                            self.database.write_batch(batch)?;
                             let _ = self
                                 .notification_sender
                                 .send(Notification::NewEvent {
@@ -1360,9 +1368,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 })
                                 .await
                                 .map_err(|_| LedgerError::NotificationChannelError);
-                            self.database
-                                .set_subject(&state_request.subject_id, subject)?;
-                            if self.subject_is_gov.get(&subject_id).unwrap().to_owned() {
+                            if is_gov {
                                 // Send message to gov of governance updated with id and sn
                                 let msg = request_gov_event(
                                     self.our_id.clone(),
@@ -1377,18 +1383,16 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
                                 self.gov_api
                                     .governance_updated(subject_id.clone(), sn)
                                     .await?;
-                            } else {
-                                self.database
-                                    .del_signatures(&state_request.subject_id, sn - 1)?;
                             }
                             self.ledger_state.insert(
-                                state_request.subject_id.clone(),
+                                fact_subject_id.clone(),
                                 LedgerState {
                                     current_sn: Some(sn),
                                     head: ledger_state.head,
@@ -1397,7 +1401,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                             // Send witnessing signature to distribution manager or the event itself
                             self.distribution_channel
                                 .tell(DistributionMessagesNew::SignaturesNeeded {
-                                    subject_id: state_request.subject_id,
+                                    subject_id: fact_subject_id,
                                     sn,
                                 })
                                 .await?;
@@ -1422,12 +1426,11 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
-                                return Err(LedgerError::GovernanceLCE(
-                                    state_request.subject_id.to_str(),
-                                ));
+                                return Err(LedgerError::GovernanceLCE(fact_subject_id.to_str()));
                             }
                             // Check which LCE is larger and keep the smaller one if we have another one.
                             let last_lce = match ledger_state.head {
@@ -1444,24 +1447,50 @@ impl<C: DatabaseCollection> Ledger<C> {
                             };
                             // If we have arrived here it is because it is going to be a new LCE
                             let sn = event.content.sn;
-                            self.database.set_signatures(
-                                &state_request.subject_id,
+                            let mut batch = WriteBatch::new();
+                            self.database.set_signatures_batch(
+                                &mut batch,
+                                &fact_subject_id,
                                 sn,
                                 signatures,
                                 validation_proof.clone(),
                             )?;
-                            self.database.set_lce_validation_proof(
-                                &state_request.subject_id,
+                            self.database.set_lce_validation_proof_batch(
+                                &mut batch,
+                                &fact_subject_id,
                                 validation_proof,
                             )?;
                             self.set_finished_request(
+                                &mut batch,
                                 &request_id,
                                 event_request.clone(),
                                 event.content.sn,
                                 subject_id.clone(),
                                 event.content.eval_success && event.content.approved,
                             )?;
-                            self.database.set_event(&state_request.subject_id, event)?;
+                            self.database
+                                .set_event_batch(&mut batch, &fact_subject_id, event)?;
+                            if last_lce.is_some() {
+                                let last_lce_sn = last_lce.unwrap();
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &fact_subject_id,
+                                    last_lce_sn,
+                                )?;
+                                self.database.del_event_batch(
+                                    &mut batch,
+                                    &fact_subject_id,
+                                    last_lce_sn,
+                                )?;
+                            } else {
+                                // Delete signatures of last validated event
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &fact_subject_id,
+                                    subject.sn,
+                                )?;
+                            }
+                            self.database.write_batch(batch)?;
                             let _ = self
                                 .notification_sender
                                 .send(Notification::NewEvent {
@@ -1470,19 +1499,8 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 })
                                 .await
                                 .map_err(|_| LedgerError::NotificationChannelError);
-                            if last_lce.is_some() {
-                                let last_lce_sn = last_lce.unwrap();
-                                self.database
-                                    .del_signatures(&state_request.subject_id, last_lce_sn)?;
-                                self.database
-                                    .del_event(&state_request.subject_id, last_lce_sn)?;
-                            } else {
-                                // Delete signatures of last validated event
-                                self.database
-                                    .del_signatures(&state_request.subject_id, subject.sn)?;
-                            }
                             self.ledger_state.insert(
-                                state_request.subject_id.clone(),
+                                fact_subject_id.clone(),
                                 LedgerState {
                                     current_sn: ledger_state.current_sn,
                                     head: Some(sn),
@@ -1490,8 +1508,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                             );
                             // Request next event to current_sn
                             witnesses.insert(subject.owner);
-                            let msg =
-                                request_event(self.our_id.clone(), state_request.subject_id, 0);
+                            let msg = request_event(self.our_id.clone(), fact_subject_id, 0);
                             self.message_channel
                                 .tell(MessageTaskCommand::Request(
                                     None,
@@ -1500,6 +1517,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     MessageConfig {
                                         timeout: 2000,
                                         replication_factor: 0.8,
+                                        retry: None,
                                     },
                                 ))
                                 .await?;
@@ -1534,7 +1552,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                     None => {
                         // Make checks with the ValidationProof
                         // Check that the signatures are valid and sufficient
-                        let subject_id = state_request.subject_id.clone();
+                        let subject_id = fact_subject_id.clone();
                         let metadata = validation_proof.get_metadata();
                         if &metadata.schema_id == "governance" {
                             self.subject_is_gov.insert(subject_id.clone(), true);
@@ -1548,12 +1566,11 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     MessageConfig {
                                         timeout: 2000,
                                         replication_factor: 1.0,
+                                        retry: None,
                                     },
                                 ))
                                 .await?;
-                            return Err(LedgerError::GovernanceLCE(
-                                state_request.subject_id.to_str(),
-                            ));
+                            return Err(LedgerError::GovernanceLCE(fact_subject_id.to_str()));
                         } else {
                             self.subject_is_gov.insert(subject_id.clone(), false);
                         }
@@ -1567,7 +1584,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 Err(error) => match error {
                                     crate::DbError::EntryNotFound => {
                                         return Err(LedgerError::WeAreNotWitnesses(
-                                            state_request.subject_id.to_str(),
+                                            fact_subject_id.to_str(),
                                         ));
                                     }
                                     _ => {
@@ -1599,25 +1616,31 @@ impl<C: DatabaseCollection> Ledger<C> {
                             .await?;
                         verify_signatures(&signatures, &signers, quorum, &validation_proof)?;
                         let sn = event.content.sn;
-                        self.database.set_signatures(
-                            &state_request.subject_id,
+                        let mut batch = WriteBatch::new();
+                        self.database.set_signatures_batch(
+                            &mut batch,
+                            &fact_subject_id,
                             sn,
                             signatures,
                             validation_proof.clone(),
                         )?;
-                        self.database.set_lce_validation_proof(
-                            &state_request.subject_id,
+                        self.database.set_lce_validation_proof_batch(
+                            &mut batch,
+                            &fact_subject_id,
                             validation_proof,
                         )?;
                         let success = event.content.eval_success && event.content.approved;
-                        self.database.set_event(&state_request.subject_id, event)?;
+                        self.database
+                            .set_event_batch(&mut batch, &fact_subject_id, event)?;
                         self.set_finished_request(
+                            &mut batch,
                             &request_id,
                             event_request.clone(),
                             sn,
                             subject_id.clone(),
-                            true,
+                            success,
                         )?;
+                        self.database.write_batch(batch)?;
                         let _ = self
                             .notification_sender
                             .send(Notification::NewEvent {
@@ -1626,22 +1649,15 @@ impl<C: DatabaseCollection> Ledger<C> {
                             })
                             .await
                             .map_err(|_| LedgerError::NotificationChannelError);
-                        self.set_finished_request(
-                            &request_id,
-                            event_request.clone(),
-                            sn,
-                            subject_id.clone(),
-                            success,
-                        )?;
                         self.ledger_state.insert(
-                            state_request.subject_id.clone(),
+                            fact_subject_id.clone(),
                             LedgerState {
                                 current_sn: None,
                                 head: Some(sn),
                             },
                         );
                         // Request event 0
-                        let msg = request_event(self.our_id.clone(), state_request.subject_id, 0);
+                        let msg = request_event(self.our_id.clone(), fact_subject_id, 0);
                         self.message_channel
                             .tell(MessageTaskCommand::Request(
                                 None,
@@ -1650,6 +1666,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 MessageConfig {
                                     timeout: 2000,
                                     replication_factor: 1.0,
+                                    retry: None,
                                 },
                             ))
                             .await?;
@@ -1688,6 +1705,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
@@ -1724,6 +1742,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
@@ -1786,24 +1805,40 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 &subject,
                                 &event_hash,
                                 &subject.public_key,
+                                &event.content.dependencies,
                             )?;
                             let sn: u64 = event.content.sn;
-                            subject.sn = sn;
+                            subject.update_subject(event.content.patch.clone(), sn)?;
                             subject.eol_event();
-                            self.database.set_signatures(
+                            let is_gov = self.subject_is_gov.get(&subject_id).unwrap().to_owned();
+                            let mut batch = WriteBatch::new();
+                            self.database.set_signatures_batch(
+                                &mut batch,
                                 &eol_request.subject_id,
                                 sn,
                                 signatures,
                                 validation_proof,
                             )?;
                             self.set_finished_request(
+                                &mut batch,
                                 &request_id,
                                 event_request.clone(),
                                 event.content.sn,
                                 subject_id.clone(),
                                 true,
                             )?;
-                            self.database.set_event(&eol_request.subject_id, event)?;
+                            self.database
+                                .set_event_batch(&mut batch, &eol_request.subject_id, event)?;
+                            self.database
+                                .set_subject_batch(&mut batch, &eol_request.subject_id, subject)?;
+                            if !is_gov {
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &eol_request.subject_id,
+                                    sn - 1,
+                                )?;
+                            }
+                            self.database.write_batch(batch)?;
                             let _ = self
                                 .notification_sender
                                 .send(Notification::NewEvent {
@@ -1812,9 +1847,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 })
                                 .await
                                 .map_err(|_| LedgerError::NotificationChannelError);
-                            self.database
-                                .set_subject(&eol_request.subject_id, subject)?;
-                            if self.subject_is_gov.get(&subject_id).unwrap().to_owned() {
+                            if is_gov {
                                 // Send message to gov of governance updated with id and sn
                                 let msg = request_gov_event(
                                     self.our_id.clone(),
@@ -1829,15 +1862,13 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
                                 self.gov_api
                                     .governance_updated(subject_id.clone(), sn)
                                     .await?;
-                            } else {
-                                self.database
-                                    .del_signatures(&eol_request.subject_id, sn - 1)?;
                             }
                             self.ledger_state.insert(
                                 eol_request.subject_id.clone(),
@@ -1874,6 +1905,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         MessageConfig {
                                             timeout: 2000,
                                             replication_factor: 1.0,
+                                            retry: None,
                                         },
                                     ))
                                     .await?;
@@ -1899,24 +1931,50 @@ impl<C: DatabaseCollection> Ledger<C> {
                             };
                             // If we have arrived here it is because it is going to be a new LCE
                             let sn = event.content.sn;
-                            self.database.set_signatures(
+                            let mut batch = WriteBatch::new();
+                            self.database.set_signatures_batch(
+                                &mut batch,
                                 &eol_request.subject_id,
                                 sn,
                                 signatures,
                                 validation_proof.clone(),
                             )?;
-                            self.database.set_lce_validation_proof(
+                            self.database.set_lce_validation_proof_batch(
+                                &mut batch,
                                 &eol_request.subject_id,
                                 validation_proof,
                             )?;
                             self.set_finished_request(
+                                &mut batch,
                                 &request_id,
                                 event_request.clone(),
                                 event.content.sn,
                                 subject_id.clone(),
                                 event.content.eval_success && event.content.approved,
                             )?;
-                            self.database.set_event(&eol_request.subject_id, event)?;
+                            self.database
+                                .set_event_batch(&mut batch, &eol_request.subject_id, event)?;
+                            if last_lce.is_some() {
+                                let last_lce_sn = last_lce.unwrap();
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &eol_request.subject_id,
+                                    last_lce_sn,
+                                )?;
+                                self.database.del_event_batch(
+                                    &mut batch,
+                                    &eol_request.subject_id,
+                                    last_lce_sn,
+                                )?;
+                            } else {
+                                // Delete signatures of last validated event
+                                self.database.del_signatures_batch(
+                                    &mut batch,
+                                    &eol_request.subject_id,
+                                    subject.sn,
+                                )?;
+                            }
+                            self.database.write_batch(batch)?;
                             let _ = self
                                 .notification_sender
                                 .send(Notification::NewEvent {
@@ -1925,17 +1983,6 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 })
                                 .await
                                 .map_err(|_| LedgerError::NotificationChannelError);
-                            if last_lce.is_some() {
-                                let last_lce_sn = last_lce.unwrap();
-                                self.database
-                                    .del_signatures(&eol_request.subject_id, last_lce_sn)?;
-                                self.database
-                                    .del_event(&eol_request.subject_id, last_lce_sn)?;
-                            } else {
-                                // Delete signatures of last validated event
-                                self.database
-                                    .del_signatures(&eol_request.subject_id, subject.sn)?;
-                            }
                             self.ledger_state.insert(
                                 eol_request.subject_id.clone(),
                                 LedgerState {
@@ -1954,6 +2001,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     MessageConfig {
                                         timeout: 2000,
                                         replication_factor: 0.8,
+                                        retry: None,
                                     },
                                 ))
                                 .await?;
@@ -1980,6 +2028,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                     MessageConfig {
                                         timeout: 2000,
                                         replication_factor: 1.0,
+                                        retry: None,
                                     },
                                 ))
                                 .await?;
@@ -2030,24 +2079,35 @@ impl<C: DatabaseCollection> Ledger<C> {
                             .await?;
                         verify_signatures(&signatures, &signers, quorum, &validation_proof)?;
                         let sn = event.content.sn;
-                        self.database.set_signatures(
+                        let mut batch = WriteBatch::new();
+                        self.database.set_signatures_batch(
+                            &mut batch,
                             &eol_request.subject_id,
                             sn,
                             signatures,
                             validation_proof.clone(),
                         )?;
-                        self.database
-                            .set_lce_validation_proof(&eol_request.subject_id, validation_proof)?;
+                        self.database.set_lce_validation_proof_batch(
+                            &mut batch,
+                            &eol_request.subject_id,
+                            validation_proof,
+                        )?;
                         let sn = event.content.sn;
                         let success = event.content.eval_success && event.content.approved;
-                        self.database.set_event(&eol_request.subject_id, event)?;
+                        self.database.set_event_batch(
+                            &mut batch,
+                            &eol_request.subject_id,
+                            event,
+                        )?;
                         self.set_finished_request(
+                            &mut batch,
                             &request_id,
                             event_request.clone(),
                             sn,
                             subject_id.clone(),
                             success,
                         )?;
+                        self.database.write_batch(batch)?;
                         let _ = self
                             .notification_sender
                             .send(Notification::NewEvent {
@@ -2073,6 +2133,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                 MessageConfig {
                                     timeout: 2000,
                                     replication_factor: 1.0,
+                                    retry: None,
                                 },
                             ))
                             .await?;
@@ -2113,6 +2174,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                 )?
             }
             EventRequest::Fact(state_request) => state_request.subject_id.clone(),
+            EventRequest::Migrate(migrate_request) => migrate_request.subject_id.clone(),
             EventRequest::Transfer(transfer_request) => transfer_request.subject_id.clone(),
             EventRequest::EOL(eol_request) => {
                 return Err(LedgerError::IntermediateEOL(
@@ -2152,7 +2214,9 @@ impl<C: DatabaseCollection> Ledger<C> {
                                         EventRequest::Create(_) => {
                                             return Err(LedgerError::UnexpectedCreateEvent)
                                         }
-                                        EventRequest::Fact(_) => {
+                                        EventRequest::Fact(_)
+                                        | EventRequest::Migrate(_)
+                                        | EventRequest::Transfer(_) => {
                                             self.check_event(
                                                 event.clone(),
                                                 metadata.clone(),
@@ -2167,19 +2231,20 @@ impl<C: DatabaseCollection> Ledger<C> {
                                             )
                                             .await?;
                                         }
-                                        EventRequest::Transfer(_) => {
-                                            self.check_transfer_event(event.clone())?;
-                                        }
                                         EventRequest::EOL(_) => unreachable!(),
                                     }
-                                    self.database.set_event(&subject_id, event.clone())?;
+                                    let mut batch = WriteBatch::new();
+                                    self.database
+                                        .set_event_batch(&mut batch, &subject_id, event.clone())?;
                                     self.set_finished_request(
+                                        &mut batch,
                                         &request_id,
                                         event_request.clone(),
                                         event.content.sn,
                                         subject_id.clone(),
                                         event.content.eval_success && event.content.approved,
                                     )?;
+                                    self.database.write_batch(batch)?;
                                     let _ = self
                                         .notification_sender
                                         .send(Notification::NewEvent {
@@ -2261,6 +2326,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                             &subject,
                                             &event_hash,
                                             &public_key,
+                                            &head_event.content.dependencies,
                                         )?;
                                         self.event_sourcing(head_event).await?;
                                         self.ledger_state.insert(
@@ -2306,6 +2372,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                                 MessageConfig {
                                                     timeout: 2000,
                                                     replication_factor: 0.8,
+                                                    retry: None,
                                                 },
                                             ))
                                             .await?;
@@ -2378,6 +2445,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                             &subject,
                                             &event_hash,
                                             &public_key,
+                                            &head_event.content.dependencies,
                                         )?;
                                         // Hacer event sourcing del evento 1 tambien y actualizar subject
                                         self.event_sourcing(head_event).await?;
@@ -2417,6 +2485,7 @@ impl<C: DatabaseCollection> Ledger<C> {
                                                 MessageConfig {
                                                     timeout: 2000,
                                                     replication_factor: 0.8,
+                                                    retry: None,
                                                 },
                                             ))
                                             .await?;
@@ -2527,10 +2596,10 @@ impl<C: DatabaseCollection> Ledger<C> {
         &self,
         metadata: Metadata,
         stage: ValidationStage,
-    ) -> Result<(HashSet<KeyIdentifier>, u32), LedgerError> {
+    ) -> Result<(SignerWeights, u32), LedgerError> {
         let signers = self
             .gov_api
-            .get_signers(metadata.clone(), stage.clone())
+            .get_signer_weights(metadata.clone(), stage.clone())
             .await?;
         let quorum_size = self.gov_api.get_quorum(metadata, stage).await?;
         Ok((signers, quorum_size))
@@ -2542,6 +2611,7 @@ impl<C: DatabaseCollection> Ledger<C> {
         subject: &Subject,
         event_hash: &DigestIdentifier,
         public_key: &KeyIdentifier,
+        dependencies: &[SubjectDependency],
     ) -> Result<(), LedgerError> {
         let hash_prev_event = match self.database.get_event(&subject.subject_id, subject.sn) {
             Ok(event) => DigestIdentifier::from_serializable_borsh(
@@ -2602,6 +2672,10 @@ impl<C: DatabaseCollection> Ledger<C> {
             return Err(LedgerError::ValidationProofError(
                 "Genesis gov versiob does not match".to_string(),
             ));
+        } else if validation_proof.dependencies != dependencies {
+            return Err(LedgerError::ValidationProofError(
+                "Dependencies do not match".to_string(),
+            ));
         }
         Ok(())
     }
@@ -2636,7 +2710,7 @@ impl<C: DatabaseCollection> Ledger<C> {
             } // TODO: We are not checking that it could be an external that creates the subject and we allow it if it had permissions.
         }
         // Create subject and add to database
-        let init_state = self
+        let (init_state, schema_version) = self
             .gov_api
             .get_init_state(
                 metadata.governance_id.clone(),
@@ -2644,22 +2718,57 @@ impl<C: DatabaseCollection> Ledger<C> {
                 metadata.governance_version.clone(),
             )
             .await?;
-        let subject = Subject::from_genesis_event(event.clone(), init_state, None, derivator)?;
-        self.database
-            .set_governance_index(&subject_id, &subject.governance_id)?;
+        if create_request.payload.is_some() {
+            // The initial state built by the schema contract must have been evaluated and approved
+            if &create_request.schema_id == "governance" || !event.content.approved {
+                return Err(LedgerError::Unauthorized(
+                    "Genesis event with payload not accepted".into(),
+                ));
+            }
+            let subject_context = SubjectContext {
+                governance_id: metadata.governance_id.clone(),
+                schema_id: metadata.schema_id.clone(),
+                schema_version,
+                is_owner: true,
+                state: init_state.clone(),
+                namespace: metadata.namespace.clone(),
+            };
+            self.check_event(event.clone(), metadata.clone(), subject_context)
+                .await?;
+        } else if !event.content.dependencies.is_empty() {
+            return Err(LedgerError::InvalidSubjectDependency(
+                "Dependencies in an event not evaluated".to_owned(),
+            ));
+        }
+        let init_state = genesis_state(&event, init_state)?;
+        let subject = Subject::from_genesis_event(
+            event.clone(),
+            init_state,
+            schema_version,
+            None,
+            derivator,
+        )?;
         let event_request = event.content.event_request.clone();
         let request_id = DigestIdentifier::generate_with_blake3(&event_request)
             .map_err(|_| LedgerError::CryptoError("Error generating request hash".to_owned()))?;
         let sn = event.content.sn;
         let success = event.content.eval_success && event.content.approved;
-        self.database.set_event(&subject_id, event)?;
+        let mut batch = WriteBatch::new();
+        self.database
+            .set_governance_index_batch(&mut batch, &subject_id, &subject.governance_id)?;
+        self.database
+            .set_event_batch(&mut batch, &subject_id, event)?;
         self.set_finished_request(
+            &mut batch,
             &request_id,
             event_request.clone(),
             sn,
             subject_id.clone(),
             success,
         )?;
+        self.database
+            .set_subject_batch(&mut batch, &subject_id, subject)?;
+        self.database.write_batch(batch)?;
         let _ = self
             .notification_sender
             .send(Notification::NewEvent {
@@ -2668,7 +2777,6 @@ impl<C: DatabaseCollection> Ledger<C> {
             })
             .await
             .map_err(|_| LedgerError::NotificationChannelError);
-        self.database.set_subject(&subject_id, subject)?;
         let _ = self
             .notification_sender
             .send(Notification::NewSubject {
@@ -2706,6 +2814,7 @@ impl<C: DatabaseCollection> Ledger<C> {
             return Err(LedgerError::EventDoesNotFitHash);
         }
         let mut subject = self.database.get_subject(&subject_id)?;
+        subject.update_subject(event.content.patch, sn)?;
         subject.eol_event();
         self.database.set_subject(&subject_id, subject.clone())?;
         Ok(subject)
@@ -2760,10 +2869,14 @@ impl<C: DatabaseCollection> Ledger<C> {
             (None, false)
         };
         subject.transfer_subject(owner, public_key.clone(), keypair, event.content.sn);
-        self.database.set_subject(&subject_id, subject.clone())?;
+        subject.update_subject(event.content.patch, event.content.sn)?;
+        let mut batch = WriteBatch::new();
+        self.database
+            .set_subject_batch(&mut batch, &subject_id, subject.clone())?;
         if to_delete {
-            self.database.del_keys(&public_key)?;
+            self.database.del_keys_batch(&mut batch, &public_key)?;
         }
+        self.database.write_batch(batch)?;
         Ok(subject)
     }
 
@@ -2787,6 +2900,14 @@ impl<C: DatabaseCollection> Ledger<C> {
                     )
                     .await
                 }
+                EventRequest::Migrate(migrate_request) => {
+                    self.event_sourcing_state(
+                        migrate_request.subject_id.clone(),
+                        event.content.sn,
+                        event,
+                    )
+                    .await
+                }
                 EventRequest::EOL(_) => self.event_sourcing_eol(event),
             }
         }
@@ -2868,6 +2989,7 @@ impl<C: DatabaseCollection> Ledger<C> {
         // };
         // check_context(&event, metadata, subject.properties.clone())?;
         subject.update_subject(event.content.patch, event.content.sn)?;
+        subject.upgrade_schema(&event.content);
         let _ = self
             .notification_sender
             .send(Notification::StateUpdated {
@@ -2880,7 +3002,44 @@ impl<C: DatabaseCollection> Ledger<C> {
         Ok(subject)
     }
 
-    fn check_transfer_event(&self, event: Signed<Event>) -> Result<(), LedgerError> {
+    /// Checks the outcome of a transfer or EOL event and returns whether it was evaluated, which
+    /// only happens when the contract of its schema declares a hook for it. Without the schema
+    /// version of the subject, as for events received ahead of the previous ones, the event
+    /// itself tells whether it was evaluated.
+    async fn check_transfer_or_eol_event(
+        &self,
+        event: &Signed<Event>,
+        metadata: &Metadata,
+        schema_version: Option<u64>,
+    ) -> Result<bool, LedgerError> {
+        let Some(hook) = event.content.event_request.content.contract_hook() else {
+            unreachable!()
+        };
+        let evaluated = match schema_version {
+            Some(schema_version) => self
+                .gov_api
+                .get_contract_hooks(
+                    metadata.governance_id.clone(),
+                    metadata.schema_id.clone(),
+                    schema_version,
+                    event.content.gov_version,
+                )
+                .await?
+                .contains(&hook),
+            None => !event.content.evaluators.is_empty(),
+        };
+        if !evaluated {
+            self.check_transfer_event(event)?;
+            return Ok(false);
+        }
+        // Vetoed or rejected transfers and EOLs never become events
+        if !event.content.eval_success || !event.content.approved {
+            return Err(LedgerError::RejectedByContractHook);
+        }
+        Ok(true)
+    }
+
+    fn check_transfer_event(&self, event: &Signed<Event>) -> Result<(), LedgerError> {
         if !event.content.eval_success
             || event.content.patch
                 != ValueWrapper(serde_json::from_str("[]").map_err(|_| {
@@ -2901,16 +3060,40 @@ impl<C: DatabaseCollection> Ledger<C> {
         metadata: Metadata,
         subject_context: SubjectContext,
     ) -> Result<(), LedgerError> {
+        let evaluated = match &event.content.event_request.content {
+            EventRequest::Transfer(_) | EventRequest::EOL(_) => {
+                self.check_transfer_or_eol_event(
+                    &event,
+                    &metadata,
+                    Some(subject_context.schema_version),
+                )
+                .await?
+            }
+            event_request => event_request.requires_eval_appr(),
+        };
+        // The states of other subjects read by the contract must match the ones of this node
+        if !evaluated && !event.content.dependencies.is_empty() {
+            return Err(LedgerError::InvalidSubjectDependency(
+                "Dependencies in an event not evaluated".to_owned(),
+            ));
+        }
+        check_dependencies(
+            &self.database,
+            &metadata.governance_id,
+            &event.content.dependencies,
+        )
+        .map_err(|error| LedgerError::InvalidSubjectDependency(error.to_string()))?;
         // Verify that the evaluation and/or approval signatures make quorum
         let (signers_eval, quorum_eval) = self
             .get_signers_and_quorum(metadata.clone(), ValidationStage::Evaluate)
             .await?;
-        let quorum_neg_eval = (signers_eval.len() as u32 - quorum_eval) + 1;
+        let quorum_neg_eval = total_weight(&signers_eval).saturating_sub(quorum_eval) + 1;
         let (signers, quorum) = self
             .get_signers_and_quorum(metadata, ValidationStage::Approve)
             .await?;
-        let quorum_neg = (signers.len() as u32 - quorum) + 1;
+        let quorum_neg = total_weight(&signers).saturating_sub(quorum) + 1;
         event.verify_eval_appr(
+            evaluated,
             subject_context,
             (&signers_eval, quorum_eval, quorum_neg_eval),
             (&signers, quorum, quorum_neg),
@@ -2919,6 +3102,32 @@ impl<C: DatabaseCollection> Ledger<C> {
     }
 }
 
+/// Returns the state a subject is created with. The genesis events of creation requests with
+/// payload carry the initial state built by the schema contract.
+fn genesis_state(
+    event: &Signed<Event>,
+    init_state: ValueWrapper,
+) -> Result<ValueWrapper, LedgerError> {
+    let with_payload = matches!(
+        &event.content.event_request.content,
+        EventRequest::Create(create_request) if create_request.payload.is_some()
+    );
+    if !with_payload {
+        return Ok(init_state);
+    }
+    let state = ValueWrapper(
+        apply_patch(event.content.patch.0.clone(), json!({}))
+            .map_err(|error| LedgerError::ErrorApplyingPatch(error.to_string()))?,
+    );
+    let state_hash =
+        DigestIdentifier::from_serializable_borsh(&state, event.content.state_hash.derivator)
+            .map_err(|_| LedgerError::CryptoError("Error generating state hash".to_owned()))?;
+    if state_hash != event.content.state_hash {
+        return Err(LedgerError::EventDoesNotFitHash);
+    }
+    Ok(state)
+}
+
 #[allow(dead_code)]
 fn verify_approval_signatures(
     approvals: &HashSet<Signed<ApprovalResponse>>,
@@ -2964,7 +3173,7 @@ fn verify_approval_signatures(
 
 fn verify_signatures(
     signatures: &HashSet<Signature>,
-    signers: &HashSet<KeyIdentifier>,
+    signers: &SignerWeights,
     quorum_size: u32,
     validation_proof: &ValidationProof,
 ) -> Result<(), LedgerError> {
@@ -2978,7 +3187,7 @@ fn verify_signatures(
                 continue;
             }
         }
-        if !signers.contains(&signer) {
+        if !signers.contains_key(&signer) {
             log::error!("Signer {} not allowed", signer.to_str());
             continue;
         }
@@ -2990,11 +3199,12 @@ fn verify_signatures(
             continue;
         }
     }
-    if actual_signers.len() < quorum_size as usize {
+    let signatures_weight = votes_weight(signers, &actual_signers);
+    if signatures_weight < quorum_size {
         log::error!(
             "Not enough signatures Validation/Evaluation. Expected: {}, Actual: {}",
             quorum_size,
-            actual_signers.len()
+            signatures_weight
         );
         return Err(LedgerError::NotEnoughSignatures("buenas tardes".into()));
     }
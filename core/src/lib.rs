@@ -53,6 +53,7 @@
 //!         namespace: "".to_string(),
 //!         schema_id: "governance".to_string(),
 //!         public_key: governance_key,
+//!         payload: None,
 //!     });
 //!     let signed_request = Signed::<EventRequest> {
 //!         content: create_subject_request.clone(),
@@ -102,6 +103,7 @@ pub(crate) mod validation;
 
 pub(crate) mod event;
 pub(crate) mod protocol;
+pub(crate) mod subscription;
 
 mod node;
 pub use api::{Api, ApiError};
@@ -111,8 +113,12 @@ pub use commons::identifier::{Derivable, DigestIdentifier, KeyIdentifier, Signat
 pub use commons::models::approval::ApprovalRequest;
 pub use commons::models::approval::ApprovalResponse;
 pub use commons::models::approval::{ApprovalEntity, ApprovalState};
+pub use commons::models::evaluation::{ContractContext, ContractLog, ContractLogLevel};
 pub use commons::models::evaluation::EvaluationRequest;
 pub use commons::models::evaluation::EvaluationResponse;
+pub use commons::models::evaluation::{ContractSubjectState, SubjectDependency};
+pub use commons::models::evaluation::FactSimulation;
+pub use commons::models::evaluation::GovernanceDiagnostic;
 pub use commons::models::event::Event;
 pub use commons::models::event::Metadata;
 pub use commons::models::request;
@@ -122,15 +128,24 @@ pub use commons::models::state::SubjectData;
 pub use commons::{
     errors::ListenAddrErrors,
     identifier::derive::{digest::DigestDerivator, KeyDerivator},
-    models::notification::Notification,
+    models::notification::{
+        Notification, NotificationEntry, NotificationFilter, NotificationKind,
+    },
     models::timestamp::TimeStamp,
     models::validation::ValidationProof,
     models::value_wrapper::ValueWrapper,
-    settings::{ListenAddr, NetworkSettings, NodeSettings, Settings},
+    settings::{
+        ContractLimits, ListenAddr, NetworkSettings, NodeSettings, RetryPolicies, RetryPolicy,
+        Settings,
+    },
 };
 pub(crate) use database::DB;
 pub use database::{
     DatabaseCollection, DatabaseManager, Error as DbError, MemoryCollection, MemoryManager,
+    WriteBatch, WriteOperation,
 };
+#[cfg(feature = "file-database")]
+pub use database::{FileCollection, FileManager};
 pub use error::Error;
 pub use node::Node;
+pub use subscription::{NotificationSubscription, SubscriptionError};
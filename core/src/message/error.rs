@@ -32,4 +32,6 @@ pub enum Error {
     ChannelClosed,
     #[error("Error Creating message")]
     CreatingMessageError,
+    #[error("Retries exhausted")]
+    RetriesExhausted,
 }
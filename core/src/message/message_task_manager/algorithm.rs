@@ -2,43 +2,43 @@ use crate::commons::identifier::{Derivable, KeyIdentifier};
 use futures::{future::BoxFuture, prelude::*};
 use log::debug;
 
-use super::super::{error::Error, MessageConfig, MessageSender, TaskCommandContent};
+use super::super::{error::Error, MessageConfig, MessageSender, TaskCommandContent, TaskExhausted};
+use crate::commons::settings::RetryPolicy;
 use std::time::Duration;
+use tokio::{sync::broadcast, time::Instant};
 
 use rand::Rng;
 
 pub struct Algorithm {}
 
 impl Algorithm {
-    fn get_targets(all_targets: Vec<KeyIdentifier>, replication_factor: f64) -> Vec<KeyIdentifier> {
-        let number_to_select =
-            1.max((all_targets.len() as f64 * replication_factor).floor() as u32);
-        get_n_distinct_random_data(number_to_select, all_targets)
-    }
-
     pub fn make_indefinite_future<T: 'static + TaskCommandContent>(
         sender: MessageSender,
         config: MessageConfig,
         request: T,
         targets: Vec<KeyIdentifier>,
+        id: String,
+        exhausted_tx: broadcast::Sender<TaskExhausted<T>>,
     ) -> BoxFuture<'static, Result<(), Error>> {
         async move {
-            let mut interval =
-                tokio::time::interval(Duration::from_millis(config.timeout() as u64));
-            loop {
-                // The message to be sent is obtained
-                interval.tick().await;
-                // Targets are selected
-                let targets_selected =
-                    Algorithm::get_targets(targets.clone(), config.replication_factor());
-                for target in targets_selected {
-                    debug!("Message sent to {}", target.to_str());
-                    sender
-                        .send_message(target, request.clone())
-                        .await
-                        .map_err(|_| Error::SenderChannelError)?;
+            let Some(policy) = config.retry().cloned() else {
+                let mut interval =
+                    tokio::time::interval(Duration::from_millis(config.timeout() as u64));
+                let mut rotation = TargetRotation::new(targets.len());
+                loop {
+                    // The message to be sent is obtained
+                    interval.tick().await;
+                    let targets_selected = rotation.select(&targets, config.replication_factor());
+                    Algorithm::send(&sender, &request, targets_selected).await?;
                 }
-            }
+            };
+            Algorithm::retry(&sender, &request, &targets, &config, &policy).await?;
+            debug!("Retries exhausted for task {}", id);
+            let _ = exhausted_tx.send(TaskExhausted {
+                id,
+                content: request,
+            });
+            Err(Error::RetriesExhausted)
         }
         .boxed()
     }
@@ -50,48 +50,172 @@ impl Algorithm {
         config: MessageConfig,
     ) -> BoxFuture<'static, Result<(), Error>> {
         async move {
-            // Targets are selected
-            let targets_selected = Algorithm::get_targets(targets, config.replication_factor());
-            for target in targets_selected {
-                let result_sending = sender.send_message(target, request.clone()).await;
-                result_sending.map_err(|_| Error::SenderChannelError)?;
+            match config.retry() {
+                // The task cannot be cancelled, so it is only retried while the policy allows it
+                Some(policy) if policy.is_bounded() => {
+                    Algorithm::retry(&sender, &request, &targets, &config, policy).await
+                }
+                _ => {
+                    let targets_selected = TargetRotation::new(targets.len())
+                        .select(&targets, config.replication_factor());
+                    Algorithm::send(&sender, &request, targets_selected).await
+                }
             }
-            Ok(())
         }
         .boxed()
     }
+
+    /// Sends the message to the targets until the retry policy is exhausted, waiting after
+    /// each attempt the time given by the policy.
+    async fn retry<T: TaskCommandContent>(
+        sender: &MessageSender,
+        request: &T,
+        targets: &[KeyIdentifier],
+        config: &MessageConfig,
+        policy: &RetryPolicy,
+    ) -> Result<(), Error> {
+        let deadline = policy
+            .deadline
+            .map(|deadline| Instant::now() + Duration::from_millis(deadline as u64));
+        let mut attempts = 0u32;
+        let mut rotation = TargetRotation::new(targets.len());
+        loop {
+            if matches!(policy.max_attempts, Some(max) if attempts >= max)
+                || matches!(deadline, Some(deadline) if Instant::now() >= deadline)
+            {
+                return Ok(());
+            }
+            let targets_selected = rotation.select(targets, config.replication_factor());
+            Algorithm::send(sender, request, targets_selected).await?;
+            attempts += 1;
+            let delay = retry_delay(policy, attempts);
+            match deadline {
+                Some(deadline) => {
+                    tokio::time::sleep_until(deadline.min(Instant::now() + delay)).await
+                }
+                None => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+
+    async fn send<T: TaskCommandContent>(
+        sender: &MessageSender,
+        request: &T,
+        targets: Vec<KeyIdentifier>,
+    ) -> Result<(), Error> {
+        for target in targets {
+            debug!("Message sent to {}", target.to_str());
+            sender
+                .send_message(target, request.clone())
+                .await
+                .map_err(|_| Error::SenderChannelError)?;
+        }
+        Ok(())
+    }
+}
+
+/// Time to wait after the given attempt, starting with 1. The delay grows exponentially up
+/// to the maximum of the policy and is then randomly shifted by the jitter.
+fn retry_delay(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+    let delay = (policy.initial_delay as f64 * policy.multiplier.powi(exponent))
+        .min(policy.max_delay as f64);
+    let jitter = delay * policy.jitter.clamp(0.0, 1.0);
+    let delay = if jitter > 0.0 {
+        rand::thread_rng().gen_range(delay - jitter..=delay + jitter)
+    } else {
+        delay
+    };
+    Duration::from_millis(delay.max(0.0) as u64)
 }
 
-fn get_n_distinct_random_data<D>(quantity: u32, mut data: Vec<D>) -> Vec<D> {
-    if quantity as usize >= data.len() {
-        return data;
+/// Selects the targets of each attempt. The targets are taken in turns starting from a random
+/// one, so no target receives the message again before all the others have received it.
+struct TargetRotation {
+    next: usize,
+}
+
+impl TargetRotation {
+    fn new(targets_len: usize) -> Self {
+        let next = if targets_len > 0 {
+            rand::thread_rng().gen_range(0..targets_len)
+        } else {
+            0
+        };
+        Self { next }
     }
-    let mut result: Vec<D> = Vec::new();
-    let mut counter = 0u32;
-    let mut rng = rand::thread_rng();
-    while counter < quantity {
-        let index = rng.gen_range(0..data.len());
-        let value = data.remove(index);
-        result.push(value);
-        counter += 1;
+
+    /// Returns the next `replication_factor` fraction of the targets, at least one of them.
+    fn select<D: Clone>(&mut self, targets: &[D], replication_factor: f64) -> Vec<D> {
+        if targets.is_empty() {
+            return Vec::new();
+        }
+        let quantity =
+            ((targets.len() as f64 * replication_factor).floor() as usize).clamp(1, targets.len());
+        let result = (0..quantity)
+            .map(|i| targets[(self.next + i) % targets.len()].clone())
+            .collect();
+        self.next = (self.next + quantity) % targets.len();
+        result
     }
-    result
 }
 
 #[cfg(test)]
 mod test {
 
-    use super::get_n_distinct_random_data;
+    use std::time::Duration;
+
+    use std::collections::HashSet;
+
+    use super::{retry_delay, TargetRotation};
+    use crate::commons::settings::RetryPolicy;
 
     #[test]
-    fn test_random_select() {
-        let first = vec![1, 2, 3, 4, 5];
+    fn test_target_rotation() {
+        let targets = vec![1, 2, 3, 4, 5];
         let empty: Vec<i32> = vec![];
-        assert_eq!(get_n_distinct_random_data(0, first.clone()), empty);
-        assert_eq!(
-            get_n_distinct_random_data(10, first.clone()),
-            vec![1, 2, 3, 4, 5]
-        );
-        assert_eq!(get_n_distinct_random_data(3, first.clone()).len(), 3);
+        assert_eq!(TargetRotation::new(0).select(&empty, 0.5), empty);
+        assert_eq!(TargetRotation::new(5).select(&targets, 0.0).len(), 1);
+        assert_eq!(TargetRotation::new(5).select(&targets, 10.0).len(), 5);
+        // Every target is selected before any of them is selected again
+        let mut rotation = TargetRotation::new(targets.len());
+        let mut selected = HashSet::new();
+        for _ in 0..5 {
+            let round = rotation.select(&targets, 0.2);
+            assert_eq!(round.len(), 1);
+            assert!(selected.insert(round[0]));
+        }
+        assert_eq!(selected.len(), 5);
+        let mut rotation = TargetRotation::new(targets.len());
+        let mut first_rounds = rotation.select(&targets, 0.4);
+        first_rounds.extend(rotation.select(&targets, 0.4));
+        first_rounds.extend(rotation.select(&targets, 0.4));
+        let first_rounds: HashSet<i32> = first_rounds.into_iter().collect();
+        assert_eq!(first_rounds.len(), 5);
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy {
+            initial_delay: 100,
+            max_delay: 1000,
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: None,
+            deadline: None,
+        };
+        assert_eq!(retry_delay(&policy, 1), Duration::from_millis(100));
+        assert_eq!(retry_delay(&policy, 2), Duration::from_millis(200));
+        assert_eq!(retry_delay(&policy, 4), Duration::from_millis(800));
+        assert_eq!(retry_delay(&policy, 5), Duration::from_millis(1000));
+        assert_eq!(retry_delay(&policy, u32::MAX), Duration::from_millis(1000));
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        for _ in 0..100 {
+            let delay = retry_delay(&policy, 1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
     }
 }
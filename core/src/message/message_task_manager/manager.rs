@@ -9,14 +9,14 @@ use futures::future::{AbortHandle, Abortable, Aborted};
 use log::debug;
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
-use tokio::task::JoinHandle;
+use tokio::{sync::broadcast, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
 
 use super::algorithm::Algorithm;
 
 use super::super::{
     error::Error, message_sender::MessageSender, MessageConfig, MessageTaskCommand,
-    TaskCommandContent,
+    TaskCommandContent, TaskExhausted,
 };
 
 pub struct MessageTaskManager<T>
@@ -28,6 +28,7 @@ where
     sender: MessageSender,
     token: CancellationToken,
     notification_tx: tokio::sync::mpsc::Sender<Notification>,
+    exhausted_tx: broadcast::Sender<TaskExhausted<T>>,
 }
 
 impl<T: TaskCommandContent + Serialize + DeserializeOwned + 'static> MessageTaskManager<T> {
@@ -36,6 +37,7 @@ impl<T: TaskCommandContent + Serialize + DeserializeOwned + 'static> MessageTask
         receiver: MpscChannel<MessageTaskCommand<T>, ()>,
         token: CancellationToken,
         notification_tx: tokio::sync::mpsc::Sender<Notification>,
+        exhausted_tx: broadcast::Sender<TaskExhausted<T>>,
     ) -> MessageTaskManager<T> {
         MessageTaskManager {
             list: HashMap::new(),
@@ -43,6 +45,7 @@ impl<T: TaskCommandContent + Serialize + DeserializeOwned + 'static> MessageTask
             sender,
             token,
             notification_tx,
+            exhausted_tx,
         }
     }
 
@@ -107,7 +110,7 @@ impl<T: TaskCommandContent + Serialize + DeserializeOwned + 'static> MessageTask
         }
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         self.list.insert(
-            id,
+            id.clone(),
             (
                 tokio::spawn(Abortable::new(
                     Algorithm::make_indefinite_future(
@@ -115,6 +118,8 @@ impl<T: TaskCommandContent + Serialize + DeserializeOwned + 'static> MessageTask
                         config,
                         content,
                         targets,
+                        id,
+                        self.exhausted_tx.clone(),
                     ),
                     abort_registration,
                 )),
@@ -10,6 +10,7 @@ use crate::{
         identifier::KeyIdentifier,
         models::HashId,
         self_signature_manager::{SelfSignatureInterface, SelfSignatureManager},
+        settings::RetryPolicy,
     },
     signature::Signed,
     DigestIdentifier, DigestDerivator,
@@ -114,6 +115,8 @@ where
 pub struct MessageConfig {
     pub timeout: u32,
     pub replication_factor: f64,
+    /// Replaces the fixed timeout between attempts if present
+    pub retry: Option<RetryPolicy>,
 }
 
 impl MessageConfig {
@@ -125,10 +128,31 @@ impl MessageConfig {
         self.replication_factor
     }
 
+    pub fn retry(&self) -> Option<&RetryPolicy> {
+        self.retry.as_ref()
+    }
+
     pub fn direct_response() -> Self {
         Self {
             timeout: 0,
             replication_factor: 1.0,
+            retry: None,
+        }
+    }
+
+    pub fn with_retry(retry: RetryPolicy, replication_factor: f64) -> Self {
+        Self {
+            timeout: retry.initial_delay,
+            replication_factor,
+            retry: Some(retry),
         }
     }
 }
+
+/// Sent by a message task that stops resending its message because its retry policy has
+/// been exhausted.
+#[derive(Clone, Debug)]
+pub struct TaskExhausted<M> {
+    pub id: String,
+    pub content: M,
+}
@@ -10,15 +10,18 @@ use crate::commons::identifier::derive::KeyDerivator;
 use crate::commons::identifier::{Derivable, KeyIdentifier};
 use crate::commons::models::notification::Notification;
 use crate::commons::self_signature_manager::{SelfSignatureInterface, SelfSignatureManager};
-use crate::commons::settings::Settings;
-use crate::database::{DatabaseCollection, DatabaseManager, DB};
+use crate::commons::settings::{Settings, DEFAULT_TIMEOUT};
+use crate::database::{run_migrations, DatabaseCollection, DatabaseManager, DB};
 use crate::distribution::error::DistributionErrorResponses;
 use crate::distribution::manager::DistributionManager;
 use crate::distribution::DistributionMessagesNew;
 #[cfg(feature = "evaluation")]
-use crate::evaluator::{EvaluatorManager, EvaluatorMessage, EvaluatorResponse};
+use crate::evaluator::{
+    EvaluatorAPI, EvaluatorAPIMessage, EvaluatorAPIResponse, EvaluatorManager, EvaluatorMessage,
+    EvaluatorResponse,
+};
 use crate::event::manager::{EventAPI, EventManager};
-use crate::event::{EventCommand, EventResponse};
+use crate::event::{EventAPICommand, EventCommand, EventResponse};
 use crate::governance::GovernanceAPI;
 use crate::governance::{governance::Governance, GovernanceMessage, GovernanceResponse};
 use crate::ledger::manager::EventManagerAPI;
@@ -30,13 +33,14 @@ use crate::message::{
 use crate::network::network::NetworkProcessor;
 use crate::protocol::protocol_message_manager::{ProtocolManager, TapleMessages};
 use crate::signature::Signed;
+use crate::subscription::NotificationHub;
 #[cfg(feature = "validation")]
 use crate::validation::manager::ValidationManager;
 #[cfg(feature = "validation")]
 use crate::validation::{ValidationCommand, ValidationResponse};
 use ::futures::Future;
 use libp2p::{Multiaddr, PeerId};
-use log::{error, info};
+use log::{error, info, warn};
 use std::marker::PhantomData;
 use std::sync::Arc;
 use tokio::sync::*;
@@ -69,14 +73,23 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
     /// # Panics
     /// This method panics if it has not been possible to generate the network layer.
     pub fn build(settings: Settings, database: M) -> Result<(Self, Api), Error> {
+        if settings.node.timeout != DEFAULT_TIMEOUT {
+            warn!("The timeout setting is deprecated and ignored, use the retry policies instead");
+        }
         let (api_rx, api_tx) = MpscChannel::new(BUFFER_SIZE);
 
-        let (notification_tx, notification_rx) = mpsc::channel(BUFFER_SIZE);
+        let (notification_tx, hub_rx) = mpsc::channel(BUFFER_SIZE);
+
+        let (hub_tx, notification_rx) = mpsc::channel(BUFFER_SIZE);
+
+        let (subscription_tx, _) = broadcast::channel(BUFFER_SIZE);
 
         let (network_tx, network_rx): (mpsc::Sender<NetworkEvent>, mpsc::Receiver<NetworkEvent>) =
             mpsc::channel(BUFFER_SIZE);
 
         let (event_rx, event_tx) = MpscChannel::<EventCommand, EventResponse>::new(BUFFER_SIZE);
+        let (event_api_rx, event_api_tx) =
+            MpscChannel::<EventAPICommand, EventResponse>::new(BUFFER_SIZE);
 
         let (ledger_rx, ledger_tx) = MpscChannel::<LedgerCommand, LedgerResponse>::new(BUFFER_SIZE);
 
@@ -92,6 +105,8 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
         let (task_rx, task_tx) =
             MpscChannel::<MessageTaskCommand<TapleMessages>, ()>::new(BUFFER_SIZE);
 
+        let (task_exhausted_sx, task_exhausted_rx) = broadcast::channel(BUFFER_SIZE);
+
         let (protocol_rx, protocol_tx) =
             MpscChannel::<Signed<MessageContent<TapleMessages>>, ()>::new(BUFFER_SIZE);
 
@@ -107,6 +122,9 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
         #[cfg(feature = "evaluation")]
         let (evaluation_rx, evaluation_tx) =
             MpscChannel::<EvaluatorMessage, EvaluatorResponse>::new(BUFFER_SIZE);
+        #[cfg(feature = "evaluation")]
+        let (evaluation_api_rx, evaluation_api_tx) =
+            MpscChannel::<EvaluatorAPIMessage, EvaluatorAPIResponse>::new(BUFFER_SIZE);
 
         #[cfg(feature = "validation")]
         let (validation_rx, validation_tx) =
@@ -114,6 +132,8 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
         let database = Arc::new(database);
 
+        run_migrations(&database).map_err(|e| Error::DatabaseError(e.to_string()))?;
+
         let kp = Self::register_node_key(
             &settings.node.key_derivator,
             &settings.node.secret_key,
@@ -125,6 +145,15 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
         let token = CancellationToken::new();
 
+        let notification_hub = NotificationHub::new(
+            hub_rx,
+            hub_tx,
+            subscription_tx.clone(),
+            DB::new(database.clone()),
+            settings.node.notification_retention,
+        )
+        .map_err(|e| Error::DatabaseError(e.to_string()))?;
+
         let network_manager = NetworkProcessor::new(
             settings.network.listen_addr.clone(),
             network_access_points(&settings.network.known_nodes)?,
@@ -155,14 +184,19 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
             settings.node.digest_derivator
         );
 
-        let task_manager =
-            MessageTaskManager::new(network_tx, task_rx, token.clone(), notification_tx.clone());
+        let task_manager = MessageTaskManager::new(
+            network_tx,
+            task_rx,
+            token.clone(),
+            notification_tx.clone(),
+            task_exhausted_sx,
+        );
 
         let protocol_manager = ProtocolManager::new(
             protocol_rx,
             distribution_tx.clone(),
             #[cfg(feature = "evaluation")]
-            evaluation_tx,
+            evaluation_tx.clone(),
             #[cfg(feature = "validation")]
             validation_tx,
             event_tx.clone(),
@@ -183,7 +217,9 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
         let event_manager = EventManager::new(
             event_rx,
+            event_api_rx,
             governance_update_rx,
+            task_exhausted_rx,
             GovernanceAPI::new(governance_tx.clone()),
             DB::new(database.clone()),
             token.clone(),
@@ -192,6 +228,8 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
             ledger_tx.clone(),
             signature_manager.get_own_identifier(),
             signature_manager.clone(),
+            settings.node.retry.clone(),
+            settings.node.all_at_once_signers,
             settings.node.digest_derivator
         );
 
@@ -218,9 +256,11 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
         let api_manager = ApiManager::new(
             api_rx,
-            EventAPI::new(event_tx),
+            EventAPI::new(event_tx, event_api_tx),
             #[cfg(feature = "approval")]
             ApprovalAPI::new(approval_tx),
+            #[cfg(feature = "evaluation")]
+            EvaluatorAPI::new(evaluation_api_tx),
             AuthorizedSubjectsAPI::new(as_tx),
             EventManagerAPI::new(ledger_tx),
             token.clone(),
@@ -231,6 +271,7 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
         #[cfg(feature = "evaluation")]
         let evaluator_manager = EvaluatorManager::new(
             evaluation_rx,
+            evaluation_api_rx,
             database.clone(),
             signature_manager.clone(),
             governance_update_sx.subscribe(),
@@ -239,7 +280,8 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
             GovernanceAPI::new(governance_tx.clone()),
             settings.node.smartcontracts_directory.clone(),
             task_tx.clone(),
-            settings.node.digest_derivator
+            settings.node.digest_derivator,
+            settings.node.contract_limits.clone(),
         );
 
         #[cfg(feature = "approval")]
@@ -293,8 +335,14 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
             controller_id.to_str(),
             kp.public_key_bytes(),
             api_tx,
+            subscription_tx,
+            taple.token.clone(),
         );
 
+        tokio::spawn(async move {
+            notification_hub.run().await;
+        });
+
         tokio::spawn(async move {
             governance_manager.run().await;
         });
@@ -355,9 +403,9 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
     /// Receive a single notification
     ///
-    /// All notifications must be consumed. If the notification buffer is full the node
-    /// will be blocked until there is space in the buffer. Notifications can be consumed
-    /// in different ways.
+    /// Notifications should be consumed. If the notification buffer is full the new
+    /// notifications are dropped, although they are still delivered to the subscriptions
+    /// created with `Api::subscribe`. Notifications can be consumed in different ways.
     ///
     /// `recv_notification` allows to consume the notifications one by one and keep control
     /// of the execution flow.  
@@ -367,9 +415,9 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
     /// Handle all notifications
     ///
-    /// All notifications must be consumed. If the notification buffer is full the node
-    /// will be blocked until there is space in the buffer. Notifications can be consumed
-    /// in different ways.
+    /// Notifications should be consumed. If the notification buffer is full the new
+    /// notifications are dropped, although they are still delivered to the subscriptions
+    /// created with `Api::subscribe`. Notifications can be consumed in different ways.
     ///
     /// `handle_notifications` processes all notifications from the node. For this purpose,
     /// the function in charge of processing the notifications is passed as input.  This
@@ -385,9 +433,9 @@ impl<M: DatabaseManager<C> + 'static, C: DatabaseCollection + 'static> Node<M, C
 
     /// Drop all notifications
     ///
-    /// All notifications must be consumed. If the notification buffer is full the node
-    /// will be blocked until there is space in the buffer. Notifications can be consumed
-    /// in different ways.
+    /// Notifications should be consumed. If the notification buffer is full the new
+    /// notifications are dropped, although they are still delivered to the subscriptions
+    /// created with `Api::subscribe`. Notifications can be consumed in different ways.
     ///
     /// `drop_notifications` discards all notifications from the node.
     pub async fn drop_notifications(self) {
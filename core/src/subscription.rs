@@ -0,0 +1,576 @@
+//! Distribution of the node notifications to multiple independent consumers.
+//!
+//! Every notification is stored in the notification log of the database before being
+//! distributed, so a consumer can resume the stream after a restart from the last cursor
+//! it processed. The log keeps the most recent notifications given by
+//! [NodeSettings::notification_retention](crate::NodeSettings::notification_retention).
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use crate::api::{APICommands, ApiResponses};
+use crate::commons::channel::SenderEnd;
+use crate::commons::models::notification::{NotificationEntry, NotificationFilter};
+use crate::identifier::DigestIdentifier;
+use crate::{DatabaseCollection, DbError, Notification, DB};
+
+/// Number of notifications requested to the log at once while replaying it.
+const REPLAY_PAGE_SIZE: usize = 100;
+/// Maximum number of waiting notifications stored in the log with a single write.
+const MAX_STORED_AT_ONCE: usize = 100;
+/// Maximum number of subjects whose governance and schema are kept in memory.
+const MAX_CACHED_SUBJECTS: usize = 1000;
+
+/// Receives the notifications generated by the managers, stores them in the notification log
+/// and forwards them both to the node receiver and to all the subscriptions created through
+/// the [Api](crate::Api).
+///
+/// Sending never blocks: slow subscribers lose the oldest notifications and are informed of it
+/// through [SubscriptionError::Lagged], while the notifications that do not fit in the channel
+/// of the node receiver are dropped and counted.
+pub(crate) struct NotificationHub<C: DatabaseCollection> {
+    input: mpsc::Receiver<Notification>,
+    output: mpsc::Sender<Notification>,
+    broadcast: broadcast::Sender<NotificationEntry>,
+    database: DB<C>,
+    subjects: HashMap<String, (DigestIdentifier, String)>,
+    /// Cached subjects in insertion order, so the oldest one is evicted first
+    cached_subjects: VecDeque<String>,
+    next_cursor: u64,
+    /// Number of notifications kept in the log
+    retention: u64,
+    /// Notifications dropped because the channel of the node receiver was full
+    dropped: u64,
+}
+
+impl<C: DatabaseCollection> NotificationHub<C> {
+    pub fn new(
+        input: mpsc::Receiver<Notification>,
+        output: mpsc::Sender<Notification>,
+        broadcast: broadcast::Sender<NotificationEntry>,
+        database: DB<C>,
+        retention: u64,
+    ) -> Result<Self, DbError> {
+        let next_cursor = match database.get_last_notification_cursor()? {
+            Some(cursor) => cursor + 1,
+            None => 0,
+        };
+        // The last notification is always kept so that the cursors continue after a restart
+        let retention = retention.max(1);
+        // The retention may have been reduced since the log was written
+        database.del_notifications_before(next_cursor.saturating_sub(retention))?;
+        Ok(Self {
+            input,
+            output,
+            broadcast,
+            database,
+            subjects: HashMap::new(),
+            cached_subjects: VecDeque::new(),
+            next_cursor,
+            retention,
+            dropped: 0,
+        })
+    }
+
+    /// Runs until every manager has dropped its notification sender.
+    pub async fn run(mut self) {
+        while let Some(notification) = self.input.recv().await {
+            // The notifications already waiting are stored together, so the log is not synced
+            // once per notification
+            let mut notifications = vec![notification];
+            while notifications.len() < MAX_STORED_AT_ONCE {
+                match self.input.try_recv() {
+                    Ok(notification) => notifications.push(notification),
+                    Err(_) => break,
+                }
+            }
+            let entries: Vec<NotificationEntry> = notifications
+                .iter()
+                .zip(self.next_cursor..)
+                .map(|(notification, cursor)| self.create_entry(notification.clone(), cursor))
+                .collect();
+            // The notifications are stored before being distributed so that a subscription
+            // that resumes from the log never misses them. If they can not be stored, the
+            // subscriptions do not receive them and their cursors are given to the next ones,
+            // so every cursor received by a subscription can be found in the log
+            match self.database.set_notifications(&entries, self.retention) {
+                Ok(()) => {
+                    self.next_cursor += entries.len() as u64;
+                    for entry in entries {
+                        // An error only means that there are no subscriptions
+                        let _ = self.broadcast.send(entry);
+                    }
+                }
+                Err(error) => {
+                    log::error!(
+                        "Error storing notifications from {}: {}",
+                        self.next_cursor,
+                        error
+                    );
+                }
+            }
+            for notification in notifications {
+                self.forward(notification);
+            }
+        }
+        log::info!("Ended");
+    }
+
+    fn forward(&mut self, notification: Notification) {
+        match self.output.try_send(notification) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.dropped += 1;
+                log::warn!(
+                    "Node notification receiver is full, {} notifications dropped",
+                    self.dropped
+                );
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                log::debug!("Node notification receiver dropped");
+            }
+        }
+    }
+
+    fn create_entry(&mut self, notification: Notification, cursor: u64) -> NotificationEntry {
+        let subject = notification
+            .subject_id()
+            .and_then(|subject_id| self.get_subject_metadata(subject_id));
+        let (governance_id, schema_id) = match subject {
+            Some((governance_id, schema_id)) => (Some(governance_id), Some(schema_id)),
+            None => (None, None),
+        };
+        NotificationEntry {
+            cursor,
+            notification,
+            governance_id,
+            schema_id,
+        }
+    }
+
+    fn get_subject_metadata(&mut self, subject_id: &str) -> Option<(DigestIdentifier, String)> {
+        if let Some(metadata) = self.subjects.get(subject_id) {
+            return Some(metadata.clone());
+        }
+        let id = DigestIdentifier::from_str(subject_id).ok()?;
+        let subject = self.database.get_subject(&id).ok()?;
+        // The events of a governance belong to the governance itself
+        let governance_id = if subject.governance_id.digest.is_empty() {
+            id
+        } else {
+            subject.governance_id
+        };
+        let metadata = (governance_id, subject.schema_id);
+        // The governance and schema of a subject never change, so the cache only needs a bound
+        if self.cached_subjects.len() >= MAX_CACHED_SUBJECTS {
+            if let Some(evicted) = self.cached_subjects.pop_front() {
+                self.subjects.remove(&evicted);
+            }
+        }
+        self.cached_subjects.push_back(subject_id.to_owned());
+        self.subjects
+            .insert(subject_id.to_owned(), metadata.clone());
+        Some(metadata)
+    }
+}
+
+/// Errors that can be returned when receiving from a [NotificationSubscription]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SubscriptionError {
+    /// The subscription did not keep up and the given number of notifications were lost.
+    /// The subscription can continue receiving the newer notifications.
+    #[error("Subscription lagged behind, {0} notifications were lost")]
+    Lagged(u64),
+    /// The notification log could not be read
+    #[error("Notification log error: {0}")]
+    LogError(String),
+    /// The node has been stopped
+    #[error("The node has been stopped")]
+    Closed,
+}
+
+/// Reads the notification log through the API of the node.
+struct LogReader {
+    sender: SenderEnd<APICommands, ApiResponses>,
+    /// Next cursor to read from the log. `None` when the subscription receives live notifications
+    next_cursor: Option<u64>,
+}
+
+/// Independent stream of the node notifications that pass a [NotificationFilter].
+/// It is created through [Api::subscribe](crate::Api::subscribe) or
+/// [Api::subscribe_from](crate::Api::subscribe_from).
+pub struct NotificationSubscription {
+    receiver: broadcast::Receiver<NotificationEntry>,
+    filter: NotificationFilter,
+    token: CancellationToken,
+    log: Option<LogReader>,
+    pending: VecDeque<NotificationEntry>,
+    last_cursor: Option<u64>,
+}
+
+impl NotificationSubscription {
+    pub(crate) fn new(
+        receiver: broadcast::Receiver<NotificationEntry>,
+        filter: NotificationFilter,
+        token: CancellationToken,
+    ) -> Self {
+        Self {
+            receiver,
+            filter,
+            token,
+            log: None,
+            pending: VecDeque::new(),
+            last_cursor: None,
+        }
+    }
+
+    /// Creates a subscription that first replays the notification log from `cursor` and then
+    /// continues with the live notifications. If it lags behind, the lost notifications are
+    /// read again from the log instead of being reported.
+    pub(crate) fn resume(
+        receiver: broadcast::Receiver<NotificationEntry>,
+        filter: NotificationFilter,
+        token: CancellationToken,
+        sender: SenderEnd<APICommands, ApiResponses>,
+        cursor: u64,
+    ) -> Self {
+        Self {
+            log: Some(LogReader {
+                sender,
+                next_cursor: Some(cursor),
+            }),
+            last_cursor: cursor.checked_sub(1),
+            ..Self::new(receiver, filter, token)
+        }
+    }
+
+    /// Receives the next notification that passes the filter.
+    pub async fn recv(&mut self) -> Result<Notification, SubscriptionError> {
+        Ok(self.recv_entry().await?.notification)
+    }
+
+    /// Receives the next notification that passes the filter together with its cursor.
+    /// Storing the cursor of the last processed entry allows resuming the stream with
+    /// [Api::subscribe_from](crate::Api::subscribe_from).
+    pub async fn recv_entry(&mut self) -> Result<NotificationEntry, SubscriptionError> {
+        loop {
+            let entry = match self.pending.pop_front() {
+                Some(entry) => entry,
+                None => {
+                    if self.read_log().await? {
+                        continue;
+                    }
+                    let Some(entry) = self.recv_live().await? else {
+                        continue;
+                    };
+                    entry
+                }
+            };
+            // Notifications already read from the log are received again from the live stream
+            if matches!(self.last_cursor, Some(last) if entry.cursor <= last) {
+                continue;
+            }
+            self.last_cursor = Some(entry.cursor);
+            if self.filter.matches(
+                &entry.notification,
+                entry.governance_id.as_ref(),
+                entry.schema_id.as_deref(),
+            ) {
+                return Ok(entry);
+            }
+        }
+    }
+
+    pub fn filter(&self) -> &NotificationFilter {
+        &self.filter
+    }
+
+    /// Reads the next page of the log while replaying it. Returns `false` once the end of the
+    /// log has been reached.
+    async fn read_log(&mut self) -> Result<bool, SubscriptionError> {
+        let Some(log) = self.log.as_mut() else {
+            return Ok(false);
+        };
+        let Some(from) = log.next_cursor else {
+            return Ok(false);
+        };
+        let response = log
+            .sender
+            .ask(APICommands::GetNotifications(from, REPLAY_PAGE_SIZE))
+            .await
+            .map_err(|_| SubscriptionError::Closed)?;
+        let ApiResponses::GetNotifications(entries) = response else {
+            unreachable!()
+        };
+        let entries = entries.map_err(|error| SubscriptionError::LogError(error.to_string()))?;
+        let Some(last) = entries.last() else {
+            log.next_cursor = None;
+            return Ok(false);
+        };
+        log.next_cursor = Some(last.cursor + 1);
+        self.pending.extend(entries);
+        Ok(true)
+    }
+
+    /// Receives the next live notification. Returns `None` if the subscription lagged behind
+    /// and has to read the lost notifications from the log.
+    async fn recv_live(&mut self) -> Result<Option<NotificationEntry>, SubscriptionError> {
+        let entry = tokio::select! {
+            biased;
+            entry = self.receiver.recv() => entry,
+            _ = self.token.cancelled() => return Err(SubscriptionError::Closed),
+        };
+        match entry {
+            Ok(entry) => Ok(Some(entry)),
+            Err(broadcast::error::RecvError::Lagged(lost)) => match self.log.as_mut() {
+                Some(log) => {
+                    log.next_cursor = Some(self.last_cursor.map_or(0, |cursor| cursor + 1));
+                    Ok(None)
+                }
+                None => Err(SubscriptionError::Lagged(lost)),
+            },
+            Err(broadcast::error::RecvError::Closed) => Err(SubscriptionError::Closed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use tokio::sync::{broadcast, mpsc};
+    use tokio_util::sync::CancellationToken;
+
+    use super::{
+        NotificationHub, NotificationSubscription, SubscriptionError, MAX_CACHED_SUBJECTS,
+    };
+    use crate::commons::models::notification::{NotificationFilter, NotificationKind};
+    use crate::commons::models::state::Subject;
+    use crate::identifier::{DigestIdentifier, KeyIdentifier};
+    use crate::{MemoryManager, Notification, ValueWrapper, DB};
+
+    fn new_event(sn: u64) -> Notification {
+        Notification::NewEvent {
+            sn,
+            subject_id: "JXtZRpNgBWVg9v5YG9AaTNfCpPd-rCTTKrFW9cV8-JKs".to_owned(),
+        }
+    }
+
+    async fn run_hub(
+        manager: &Arc<MemoryManager>,
+        notifications: Vec<Notification>,
+        retention: u64,
+    ) {
+        let (input_tx, input_rx) = mpsc::channel(10);
+        let (output_tx, mut output_rx) = mpsc::channel(10);
+        let (broadcast_tx, _) = broadcast::channel(10);
+        let hub = NotificationHub::new(
+            input_rx,
+            output_tx,
+            broadcast_tx,
+            DB::new(manager.clone()),
+            retention,
+        )
+        .unwrap();
+        let handle = tokio::spawn(hub.run());
+        for notification in notifications {
+            input_tx.send(notification).await.unwrap();
+            assert!(output_rx.recv().await.is_some());
+        }
+        drop(input_tx);
+        handle.await.unwrap();
+        assert!(output_rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn notification_log_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let manager = Arc::new(MemoryManager::new());
+            run_hub(&manager, vec![new_event(0), new_event(1)], 10).await;
+            // Cursors continue after a restart
+            run_hub(&manager, vec![new_event(2)], 10).await;
+            let db = DB::new(manager.clone());
+            let entries = db.get_notifications(0, 10).unwrap();
+            let cursors: Vec<u64> = entries.iter().map(|entry| entry.cursor).collect();
+            assert_eq!(cursors, vec![0, 1, 2]);
+            let entries = db.get_notifications(1, 1).unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].cursor, 1);
+            assert_eq!(db.get_last_notification_cursor().unwrap(), Some(2));
+        });
+    }
+
+    #[test]
+    fn notification_retention_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let manager = Arc::new(MemoryManager::new());
+            let notifications = (0..5).map(new_event).collect();
+            run_hub(&manager, notifications, 3).await;
+            let db = DB::new(manager.clone());
+            // Reading from a removed cursor starts with the oldest notification kept
+            let entries = db.get_notifications(0, 10).unwrap();
+            let cursors: Vec<u64> = entries.iter().map(|entry| entry.cursor).collect();
+            assert_eq!(cursors, vec![2, 3, 4]);
+            let entries = db.get_notifications(3, 1).unwrap();
+            assert_eq!(entries[0].cursor, 3);
+            assert!(db.get_notifications(5, 10).unwrap().is_empty());
+            // A lower retention is applied when the log is opened again
+            run_hub(&manager, vec![new_event(5)], 1).await;
+            let entries = db.get_notifications(0, 10).unwrap();
+            let cursors: Vec<u64> = entries.iter().map(|entry| entry.cursor).collect();
+            assert_eq!(cursors, vec![5]);
+        });
+    }
+
+    #[test]
+    fn waiting_notifications_are_stored_together_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let manager = Arc::new(MemoryManager::new());
+            let (input_tx, input_rx) = mpsc::channel(10);
+            let (output_tx, mut output_rx) = mpsc::channel(10);
+            let (broadcast_tx, mut broadcast_rx) = broadcast::channel(10);
+            let hub = NotificationHub::new(
+                input_rx,
+                output_tx,
+                broadcast_tx,
+                DB::new(manager.clone()),
+                3,
+            )
+            .unwrap();
+            // The notifications are waiting when the hub starts
+            for sn in 0..5 {
+                input_tx.send(new_event(sn)).await.unwrap();
+            }
+            drop(input_tx);
+            hub.run().await;
+            for cursor in 0..5 {
+                assert_eq!(broadcast_rx.recv().await.unwrap().cursor, cursor);
+                assert!(output_rx.recv().await.is_some());
+            }
+            // The retention also applies to the notifications stored in the same write
+            let entries = DB::new(manager).get_notifications(0, 10).unwrap();
+            let cursors: Vec<u64> = entries.iter().map(|entry| entry.cursor).collect();
+            assert_eq!(cursors, vec![2, 3, 4]);
+        });
+    }
+
+    #[test]
+    fn cached_subjects_are_bounded_test() {
+        let manager = Arc::new(MemoryManager::new());
+        let db = DB::new(manager.clone());
+        let key = KeyIdentifier::new(crate::KeyDerivator::Ed25519, &[0u8; 32]);
+        let subject_ids: Vec<DigestIdentifier> = (0..=MAX_CACHED_SUBJECTS as u64)
+            .map(|index| DigestIdentifier::generate_with_blake3(index).unwrap())
+            .collect();
+        for subject_id in subject_ids.iter() {
+            let subject = Subject {
+                keys: None,
+                subject_id: subject_id.clone(),
+                governance_id: DigestIdentifier::default(),
+                sn: 0,
+                genesis_gov_version: 0,
+                public_key: key.clone(),
+                namespace: "".to_owned(),
+                name: "governance".to_owned(),
+                schema_id: "governance".to_owned(),
+                schema_version: 0,
+                owner: key.clone(),
+                creator: key.clone(),
+                properties: ValueWrapper(serde_json::json!({})),
+                active: true,
+            };
+            db.set_subject(subject_id, subject).unwrap();
+        }
+        let (_input_tx, input_rx) = mpsc::channel(1);
+        let (output_tx, _output_rx) = mpsc::channel(1);
+        let (broadcast_tx, _) = broadcast::channel(1);
+        let mut hub = NotificationHub::new(input_rx, output_tx, broadcast_tx, db, 10).unwrap();
+        for subject_id in subject_ids.iter() {
+            let metadata = hub.get_subject_metadata(&subject_id.to_str()).unwrap();
+            assert_eq!(&metadata.0, subject_id);
+        }
+        assert_eq!(hub.subjects.len(), MAX_CACHED_SUBJECTS);
+        // The oldest subject is evicted first
+        assert!(!hub.subjects.contains_key(&subject_ids[0].to_str()));
+        assert!(hub.subjects.contains_key(&subject_ids[1].to_str()));
+    }
+
+    #[test]
+    fn full_node_receiver_does_not_block_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let manager = Arc::new(MemoryManager::new());
+            let (input_tx, input_rx) = mpsc::channel(10);
+            let (output_tx, mut output_rx) = mpsc::channel(1);
+            let (broadcast_tx, _) = broadcast::channel(10);
+            let hub = NotificationHub::new(
+                input_rx,
+                output_tx,
+                broadcast_tx,
+                DB::new(manager.clone()),
+                10,
+            )
+            .unwrap();
+            let handle = tokio::spawn(hub.run());
+            for sn in 0..3 {
+                input_tx.send(new_event(sn)).await.unwrap();
+            }
+            drop(input_tx);
+            handle.await.unwrap();
+            // Only the first notification fits in the channel, but all of them are stored
+            let Some(Notification::NewEvent { sn: 0, .. }) = output_rx.recv().await else {
+                panic!("Unexpected notification");
+            };
+            assert!(output_rx.recv().await.is_none());
+            let entries = DB::new(manager).get_notifications(0, 10).unwrap();
+            assert_eq!(entries.len(), 3);
+        });
+    }
+
+    #[test]
+    fn subscription_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let manager = Arc::new(MemoryManager::new());
+            let (input_tx, input_rx) = mpsc::channel(10);
+            let (output_tx, mut output_rx) = mpsc::channel(10);
+            let (broadcast_tx, broadcast_rx) = broadcast::channel(1);
+            let token = CancellationToken::new();
+            let hub = NotificationHub::new(input_rx, output_tx, broadcast_tx, DB::new(manager), 10)
+                .unwrap();
+            tokio::spawn(hub.run());
+            let mut filter = NotificationFilter::default();
+            filter.kinds.insert(NotificationKind::UnrecoverableError);
+            let mut subscription =
+                NotificationSubscription::new(broadcast_rx, filter, token.clone());
+            input_tx.send(new_event(0)).await.unwrap();
+            input_tx
+                .send(Notification::UnrecoverableError {
+                    error: "error".to_owned(),
+                })
+                .await
+                .unwrap();
+            output_rx.recv().await.unwrap();
+            output_rx.recv().await.unwrap();
+            // The buffer only holds the last notification
+            assert!(matches!(
+                subscription.recv().await,
+                Err(SubscriptionError::Lagged(1))
+            ));
+            let Ok(Notification::UnrecoverableError { .. }) = subscription.recv().await else {
+                panic!("Unexpected notification");
+            };
+            token.cancel();
+            assert!(matches!(
+                subscription.recv().await,
+                Err(SubscriptionError::Closed)
+            ));
+        });
+    }
+}
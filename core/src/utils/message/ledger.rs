@@ -1,5 +1,5 @@
 use crate::{
-    identifier::{DigestIdentifier, KeyIdentifier},
+    identifier::{Derivable, DigestIdentifier, KeyIdentifier},
     ledger::LedgerCommand,
 };
 
@@ -12,6 +12,12 @@ pub fn request_lce(who_asked: KeyIdentifier, subject_id: DigestIdentifier) -> Ta
     })
 }
 
+/// Identifier of the message task that requests the last event of a subject. The task is
+/// cancelled when an event of the subject is received.
+pub fn lce_task_id(subject_id: &DigestIdentifier) -> String {
+    format!("LCE/{}", subject_id.to_str())
+}
+
 pub fn request_event(
     who_asked: KeyIdentifier,
     subject_id: DigestIdentifier,
@@ -3,6 +3,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use crate::Error;
 pub mod message;
 pub mod patch;
+#[cfg(test)]
+pub mod test_utils;
 
 pub fn serialize<T: BorshSerialize>(data: &T) -> Result<Vec<u8>, Error> {
     data.try_to_vec().map_err(|_| Error::SerializeError)
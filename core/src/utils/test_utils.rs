@@ -0,0 +1,9 @@
+//! Fixtures shared by the tests of the crate.
+use crate::commons::crypto::{Ed25519KeyPair, KeyGenerator, KeyMaterial};
+use crate::identifier::KeyIdentifier;
+
+/// Identifier of the Ed25519 key generated from a single byte seed.
+pub fn key(seed: u8) -> KeyIdentifier {
+    let keypair = Ed25519KeyPair::from_seed(&[seed]);
+    KeyIdentifier::new(crate::KeyDerivator::Ed25519, &keypair.public_key_bytes())
+}
@@ -44,6 +44,8 @@ pub enum ValidationError {
     QuorumNotReached,
     #[error("Subject Signature Not Valid")]
     SubjectSignatureNotValid,
+    #[error("Invalid state of other subject read by the contract: {0}")]
+    InvalidSubjectDependency(String),
     #[error("Diferent genesis_gov_version and gov_version for subject: {0}")]
     GenesisGovVersionsDoesNotMatch(String),
 }
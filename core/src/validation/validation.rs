@@ -7,8 +7,11 @@ use crate::{
         models::validation::ValidationProof,
         self_signature_manager::{SelfSignatureInterface, SelfSignatureManager},
     },
+    evaluator::dependencies::check_dependencies,
     event::EventCommand,
-    governance::{stage::ValidationStage, GovernanceAPI, GovernanceInterface},
+    governance::{
+        stage::ValidationStage, votes_weight, GovernanceAPI, GovernanceInterface, SignerWeights,
+    },
     message::{MessageConfig, MessageTaskCommand},
     protocol::protocol_message_manager::TapleMessages,
     signature::Signature,
@@ -125,6 +128,13 @@ impl<C: DatabaseCollection> Validation<C> {
         if validation_event.subject_signature.signer != subject_pk {
             return Err(ValidationError::SubjectSignatureNotValid);
         }
+        // The states of other subjects read by the contract must match the ones of this node
+        check_dependencies(
+            &self.database,
+            &validation_event.proof.governance_id,
+            &validation_event.proof.dependencies,
+        )
+        .map_err(|error| ValidationError::InvalidSubjectDependency(error.to_string()))?;
         self.database
             .set_validation_register(&validation_event.proof.subject_id, &validation_event.proof)
             .map_err(|_| ValidationError::DatabaseError)?;
@@ -277,10 +287,13 @@ impl<C: DatabaseCollection> Validation<C> {
                         ValidationStage::Validate,
                     )
                     .await?;
-                if !actual_signers.is_subset(&signers) {
+                if !actual_signers
+                    .iter()
+                    .all(|signer| signers.contains_key(signer))
+                {
                     return Err(ValidationError::InvalidSigner);
                 }
-                if actual_signers.len() < quorum_size as usize {
+                if votes_weight(&signers, &actual_signers) < quorum_size {
                     return Err(ValidationError::QuorumNotReached);
                 }
             }
@@ -293,10 +306,10 @@ impl<C: DatabaseCollection> Validation<C> {
         &self,
         metadata: Metadata,
         stage: ValidationStage,
-    ) -> Result<(HashSet<KeyIdentifier>, u32), ValidationError> {
+    ) -> Result<(SignerWeights, u32), ValidationError> {
         let signers = self
             .gov_api
-            .get_signers(metadata.clone(), stage.clone())
+            .get_signer_weights(metadata.clone(), stage.clone())
             .await
             .map_err(ValidationError::GovernanceError)?;
         let quorum_size = self
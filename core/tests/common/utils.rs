@@ -73,5 +73,6 @@ pub fn create_governance_request<S: Into<String>>(
         namespace: namespace.into(),
         name: name.into(),
         public_key,
+        payload: None,
     })
 }
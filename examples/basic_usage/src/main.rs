@@ -37,6 +37,7 @@ async fn main() {
         namespace: "".to_string(),
         schema_id: "governance".to_string(),
         public_key: governance_key,
+        payload: None,
     });
     let signed_request = Signed::<EventRequest> {
         content: create_subject_request.clone(),